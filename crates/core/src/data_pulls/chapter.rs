@@ -3,19 +3,19 @@ pub mod ids;
 pub mod images;
 pub mod list;
 
-use std::{cmp::Ordering, fs::File, io::BufReader};
+use std::cmp::Ordering;
 
 pub use filter::ChapterListDataPullFilterParams;
 
 use ids::ChapterIdsListDataPull;
 use list::ChapterListDataPull;
-use mangadex_api_schema_rust::v5::{ChapterData, ChapterObject};
+use mangadex_api_schema_rust::v5::ChapterObject;
 use mangadex_api_types_rust::{ChapterSortOrder, OrderDirection};
 #[cfg(feature = "stream")]
 use tokio_stream::{Stream, StreamExt};
 use uuid::Uuid;
 
-use crate::{DirsOptions, ManagerCoreResult};
+use crate::{DirsOptions, ManagerCoreResult, file_dirs::storage::read_stored_file};
 
 #[cfg(feature = "stream")]
 use super::{AsyncIntoSorted, IntoParamedFilteredStream};
@@ -199,10 +199,10 @@ impl<I> IntoFiltered<ChapterListDataPullFilterParams> for I where I: Iterator<It
 impl Pull<ChapterObject, Uuid> for DirsOptions {
     type Error = crate::Error;
     fn pull(&self, id: Uuid) -> crate::ManagerCoreResult<ChapterObject> {
-        let manga_id_path = self.chapters_add(format!("{id}")).join("data.json");
-        let file = BufReader::new(File::open(manga_id_path)?);
-        let manga: ChapterData = serde_json::from_reader(file)?;
-        Ok(manga.data)
+        let chapter_path = self
+            .chapter_file(id)
+            .ok_or(crate::Error::InvalidFileName(self.chapters_add(id.to_string())))?;
+        read_stored_file(&chapter_path)
     }
 }
 
@@ -211,6 +211,6 @@ impl DirsOptions {
         ChapterListDataPull::new(self.chapters_add(""))
     }
     pub fn pull_chapter_ids(&self, ids: Vec<Uuid>) -> ChapterIdsListDataPull {
-        ChapterIdsListDataPull::new(self.chapters.clone(), ids)
+        ChapterIdsListDataPull::new(self.chapters_add(""), ids, self.storage_format)
     }
 }
@@ -1,37 +1,40 @@
-use std::{fs::File, io::BufReader, path::PathBuf, vec::IntoIter};
+use std::{path::PathBuf, vec::IntoIter};
 
-use mangadex_api_schema_rust::v5::{ChapterData, ChapterObject};
+use mangadex_api_schema_rust::v5::ChapterObject;
 #[cfg(feature = "stream")]
 use std::task::Poll;
 #[cfg(feature = "stream")]
 use tokio_stream::Stream;
 use uuid::Uuid;
 
-use crate::ManagerCoreResult;
+use crate::{
+    ManagerCoreResult,
+    file_dirs::{StorageFormat, storage::read_stored_file},
+};
 
 #[derive(Debug)]
 #[cfg_attr(feature = "actix", derive(actix::MessageResponse))]
 pub struct ChapterIdsListDataPull {
     chapter_path: PathBuf,
     iter: IntoIter<Uuid>,
+    format: StorageFormat,
 }
 
 impl ChapterIdsListDataPull {
-    pub(crate) fn new(chapter_path: PathBuf, ids: Vec<Uuid>) -> Self {
+    pub(crate) fn new(chapter_path: PathBuf, ids: Vec<Uuid>, format: StorageFormat) -> Self {
         Self {
             chapter_path,
             iter: ids.into_iter(),
+            format,
         }
     }
-    // TODO add cbor support
     fn id_to_chapter(&self, entry: Uuid) -> ManagerCoreResult<ChapterObject> {
         let entry = self.chapter_path.join(format!("{entry}"));
-        if !entry.exists() || !entry.is_dir() || !entry.join("data.json").exists() {
-            return Err(crate::Error::InvalidFileName(entry));
-        }
-        let file = BufReader::new(File::open(entry.join("data.json"))?);
-        let o: ChapterData = serde_json::from_reader(file)?;
-        Ok(o.data)
+        let file = self
+            .format
+            .find_file(&entry.join("data"))
+            .ok_or(crate::Error::InvalidFileName(entry))?;
+        read_stored_file(&file)
     }
 }
 
@@ -1,9 +1,13 @@
+pub mod verify;
+
 use std::{fs::read_dir, path::Path};
 
 use uuid::Uuid;
 
 use crate::{DirsOptions, ManagerCoreResult, data_pulls::Pull};
 
+use self::verify::{ImageIntegrity, check_image_file};
+
 #[derive(Debug, Clone, Hash, Default)]
 #[cfg_attr(feature = "actix", derive(actix::MessageResponse))]
 pub struct ChapterImagesData {
@@ -17,7 +21,7 @@ impl ChapterImagesData {
     }
 }
 
-fn m_read_dir<P: AsRef<Path>>(path: P) -> Vec<String> {
+fn m_read_dir<P: AsRef<Path>>(path: P, verify_hashes: bool) -> Vec<String> {
     read_dir(path)
         .map(|dir| {
             dir.flatten()
@@ -34,6 +38,11 @@ fn m_read_dir<P: AsRef<Path>>(path: P) -> Vec<String> {
                     {
                         return None::<String>;
                     }
+                    if verify_hashes
+                        && matches!(check_image_file(&e.path()), Ok(ImageIntegrity::Corrupted))
+                    {
+                        return None;
+                    }
                     e.file_name().to_str().map(String::from)
                 })
                 .collect::<Vec<_>>()
@@ -55,14 +64,29 @@ fn string_f_usize_sort(data: &mut [String]) -> Result<(), regex::Error> {
     Ok(())
 }
 
+impl DirsOptions {
+    /// Pull the stored images filenames of a chapter.
+    ///
+    /// With `verify_hashes`, the images whose content doesn't match
+    /// the hash in their filename are hidden like the empty ones.
+    /// Unlike the [`Pull`] implementation, this doesn't fail if the chapter has no images.
+    pub fn pull_chapter_images(
+        &self,
+        id: Uuid,
+        verify_hashes: bool,
+    ) -> ManagerCoreResult<ChapterImagesData> {
+        let mut data = m_read_dir(self.chapters_id_data_add(id), verify_hashes);
+        string_f_usize_sort(&mut data)?;
+        let mut data_saver = m_read_dir(self.chapters_id_data_saver_add(id), verify_hashes);
+        string_f_usize_sort(&mut data_saver)?;
+        Ok(ChapterImagesData { data, data_saver })
+    }
+}
+
 impl Pull<ChapterImagesData, Uuid> for DirsOptions {
     type Error = crate::Error;
     fn pull(&self, id: Uuid) -> ManagerCoreResult<ChapterImagesData> {
-        let mut data = m_read_dir(self.chapters_id_data_add(id));
-        string_f_usize_sort(&mut data)?;
-        let mut data_saver = m_read_dir(self.chapters_id_data_saver_add(id));
-        string_f_usize_sort(&mut data_saver)?;
-        let images = ChapterImagesData { data, data_saver };
+        let images = self.pull_chapter_images(id, false)?;
         if images.is_empty() {
             Err(crate::Error::NoChapterImages(id))
         } else {
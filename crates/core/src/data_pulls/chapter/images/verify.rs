@@ -0,0 +1,199 @@
+use std::{
+    fs::{File, read_dir},
+    io::{self, BufReader, Read},
+    path::Path,
+};
+
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{DirsOptions, ManagerCoreResult, data_push::chapter::image::Mode};
+
+const SHA256_HEX_LEN: usize = 64;
+
+/// The SHA-256 hash embedded in a MangaDex@Home image filename.
+///
+/// The images filenames looks like `1-{sha256}.png`
+/// (or `x1-{sha256}.jpg` for the data-saver ones).
+/// The hash must be a whole hex encoded SHA-256 (64 characters).
+pub fn filename_hash(filename: &str) -> Option<&str> {
+    let stem = Path::new(filename).file_stem()?.to_str()?;
+    let (_, hash) = stem.split_once('-')?;
+    (hash.len() == SHA256_HEX_LEN && hash.bytes().all(|b| b.is_ascii_hexdigit())).then_some(hash)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImageIntegrity {
+    /// The image content matches the hash in its filename
+    Valid,
+    /// The image is empty or its content doesn't match the hash in its filename
+    Corrupted,
+    /// The filename doesn't contain a hash
+    Unknown,
+}
+
+/// Hash the `reader` content and compare it to the hash embedded in `filename`.
+pub fn check_image_integrity<R: Read>(filename: &str, mut reader: R) -> io::Result<ImageIntegrity> {
+    let Some(expected) = filename_hash(filename) else {
+        return Ok(ImageIntegrity::Unknown);
+    };
+    let mut hasher = Sha256::new();
+    if io::copy(&mut reader, &mut hasher)? == 0 {
+        return Ok(ImageIntegrity::Corrupted);
+    }
+    let hash = hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect::<String>();
+    if hash.eq_ignore_ascii_case(expected) {
+        Ok(ImageIntegrity::Valid)
+    } else {
+        Ok(ImageIntegrity::Corrupted)
+    }
+}
+
+/// Same as [`check_image_integrity`] but with a stored image file.
+///
+/// Empty files are always [`ImageIntegrity::Corrupted`].
+pub fn check_image_file(path: &Path) -> io::Result<ImageIntegrity> {
+    let file = File::open(path)?;
+    if file.metadata()?.len() == 0 {
+        return Ok(ImageIntegrity::Corrupted);
+    }
+    let Some(filename) = path.file_name().and_then(|name| name.to_str()) else {
+        return Ok(ImageIntegrity::Unknown);
+    };
+    check_image_integrity(filename, BufReader::new(file))
+}
+
+/// The result of [`DirsOptions::verify_chapter_images`]
+#[derive(Debug, Clone, Default)]
+pub struct ChapterImagesVerificationReport {
+    /// The verified images mode
+    pub mode: Mode,
+    pub valid: Vec<String>,
+    pub corrupted: Vec<String>,
+    /// The images whose filename doesn't contain a hash
+    pub unknown: Vec<String>,
+    /// The images that can't be read (e.g. a permission error or a concurrent delete)
+    pub unreadable: Vec<String>,
+}
+
+impl ChapterImagesVerificationReport {
+    pub fn is_success(&self) -> bool {
+        self.corrupted.is_empty() && self.unreadable.is_empty()
+    }
+    /// Record the `integrity` check result of the image `filename`.
+    fn add(&mut self, filename: String, integrity: io::Result<ImageIntegrity>) {
+        match integrity {
+            Ok(ImageIntegrity::Valid) => self.valid.push(filename),
+            Ok(ImageIntegrity::Corrupted) => self.corrupted.push(filename),
+            Ok(ImageIntegrity::Unknown) => self.unknown.push(filename),
+            Err(_) => self.unreadable.push(filename),
+        }
+    }
+}
+
+impl DirsOptions {
+    /// Hash every stored `mode` image of a chapter and compare it to the hash in its filename.
+    ///
+    /// Every image is read and hashed, so it shouldn't run on an async executor thread.
+    /// The images that can't be read are listed in [`ChapterImagesVerificationReport::unreadable`].
+    pub fn verify_chapter_images(
+        &self,
+        id: Uuid,
+        mode: Mode,
+    ) -> ManagerCoreResult<ChapterImagesVerificationReport> {
+        let mut report = ChapterImagesVerificationReport {
+            mode,
+            ..Default::default()
+        };
+        let Ok(entries) = read_dir(self.chapter_images_dir(id, mode)) else {
+            return Ok(report);
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_file() {
+                continue;
+            }
+            let Some(filename) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            // An unreadable image shouldn't hide the others results
+            report.add(String::from(filename), check_image_file(&path));
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{
+        ChapterImagesVerificationReport, ImageIntegrity, check_image_integrity, filename_hash,
+    };
+
+    #[test]
+    fn find_filename_hash() {
+        let hash = "0c7c6ee5".repeat(8);
+        assert_eq!(
+            filename_hash(&format!("x1-{hash}.jpg")),
+            Some(hash.as_str())
+        );
+        assert_eq!(filename_hash("1.png"), None);
+        // only a part of a hash
+        assert_eq!(filename_hash("1-0c7c6ee5.png"), None);
+        assert_eq!(filename_hash("1-notahash.png"), None);
+    }
+
+    #[test]
+    fn detect_corrupted_images() {
+        use sha2::{Digest, Sha256};
+        let hash = Sha256::digest(b"eureka")
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<String>();
+        let filename = format!("1-{hash}.png");
+        assert_eq!(
+            check_image_integrity(&filename, &b"eureka"[..]).unwrap(),
+            ImageIntegrity::Valid
+        );
+        assert_eq!(
+            check_image_integrity(&filename.to_uppercase(), &b"eureka"[..]).unwrap(),
+            ImageIntegrity::Valid
+        );
+        assert_eq!(
+            check_image_integrity(&filename, &b"eurek"[..]).unwrap(),
+            ImageIntegrity::Corrupted
+        );
+        assert_eq!(
+            check_image_integrity(&filename, &b""[..]).unwrap(),
+            ImageIntegrity::Corrupted
+        );
+        // a prefix of the hash isn't enough
+        assert_eq!(
+            check_image_integrity(&format!("1-{}.png", &hash[..16]), &b"eureka"[..]).unwrap(),
+            ImageIntegrity::Unknown
+        );
+        assert_eq!(
+            check_image_integrity("1.png", &b"eureka"[..]).unwrap(),
+            ImageIntegrity::Unknown
+        );
+    }
+
+    #[test]
+    fn unreadable_images_are_reported() {
+        let mut report = ChapterImagesVerificationReport::default();
+        report.add("1.png".into(), Ok(ImageIntegrity::Unknown));
+        report.add(
+            "2.png".into(),
+            Err(io::Error::from(io::ErrorKind::PermissionDenied)),
+        );
+        report.add("3.png".into(), Ok(ImageIntegrity::Unknown));
+        assert_eq!(report.unknown, ["1.png", "3.png"]);
+        assert_eq!(report.unreadable, ["2.png"]);
+        assert!(!report.is_success());
+    }
+}
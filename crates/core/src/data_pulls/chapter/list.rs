@@ -1,17 +1,19 @@
 #[cfg(feature = "stream")]
 use std::task::Poll;
 use std::{
-    fs::{read_dir, DirEntry, File, ReadDir},
-    io::BufReader,
+    fs::{read_dir, DirEntry, ReadDir},
     iter::Flatten,
     path::PathBuf,
 };
 
-use mangadex_api_schema_rust::v5::{ChapterData, ChapterObject};
+use mangadex_api_schema_rust::v5::ChapterObject;
 #[cfg(feature = "stream")]
 use tokio_stream::Stream;
 
-use crate::ManagerCoreResult;
+use crate::{
+    ManagerCoreResult,
+    file_dirs::{StorageFormat, storage::read_stored_file},
+};
 
 #[derive(Debug)]
 pub struct ChapterListDataPull {
@@ -23,16 +25,15 @@ impl ChapterListDataPull {
         let read_dir = read_dir(chapter_path)?.flatten();
         Ok(Self { read_dir })
     }
-    // TODO add cbor support
     fn dir_entry_to_chapter(entry: DirEntry) -> ManagerCoreResult<ChapterObject> {
-        if !entry.path().exists()
-            || !entry.path().is_dir() && !entry.path().join("data.json").exists()
-        {
-            return Err(crate::Error::InvalidFileName(entry.path()));
+        let path = entry.path();
+        if !path.is_dir() {
+            return Err(crate::Error::InvalidFileName(path));
         }
-        let file = BufReader::new(File::open(entry.path().join("data.json"))?);
-        let o: ChapterData = serde_json::from_reader(file)?;
-        Ok(o.data)
+        let file = StorageFormat::default()
+            .find_file(&path.join("data"))
+            .ok_or(crate::Error::InvalidFileName(path))?;
+        read_stored_file(&file)
     }
 }
 
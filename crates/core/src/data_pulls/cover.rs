@@ -6,15 +6,15 @@ pub use self::{
     filter::CoverListDataPullFilterParams, ids::CoverIdsListDataPull, list::CoverListDataPull,
 };
 
-use std::{cmp::Ordering, fs::File, io::BufReader};
+use std::cmp::Ordering;
 
-use mangadex_api_schema_rust::v5::{CoverData, CoverObject};
+use mangadex_api_schema_rust::v5::CoverObject;
 use mangadex_api_types_rust::{CoverSortOrder, OrderDirection};
 #[cfg(feature = "stream")]
 use tokio_stream::{Stream, StreamExt};
 use uuid::Uuid;
 
-use crate::{DirsOptions, ManagerCoreResult};
+use crate::{DirsOptions, ManagerCoreResult, file_dirs::storage::read_stored_file};
 
 #[cfg(feature = "stream")]
 use super::{AsyncIntoSorted, IntoParamedFilteredStream};
@@ -127,10 +127,10 @@ impl<I> IntoFiltered<CoverListDataPullFilterParams> for I where I: Iterator<Item
 impl Pull<CoverObject, Uuid> for DirsOptions {
     type Error = crate::Error;
     fn pull(&self, id: Uuid) -> crate::ManagerCoreResult<CoverObject> {
-        let manga_id_path = self.covers_add(format!("{id}.json"));
-        let file = BufReader::new(File::open(manga_id_path)?);
-        let manga: CoverData = serde_json::from_reader(file)?;
-        Ok(manga.data)
+        let cover_path = self
+            .cover_file(id)
+            .ok_or(crate::Error::InvalidFileName(self.covers_add(id.to_string())))?;
+        read_stored_file(&cover_path)
     }
 }
 
@@ -139,6 +139,6 @@ impl DirsOptions {
         CoverListDataPull::new(self.covers.clone())
     }
     pub fn pull_covers_ids(&self, ids: Vec<Uuid>) -> CoverIdsListDataPull {
-        CoverIdsListDataPull::new(self.covers.clone(), ids)
+        CoverIdsListDataPull::new(self.covers.clone(), ids, self.storage_format)
     }
 }
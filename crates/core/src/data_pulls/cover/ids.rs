@@ -1,52 +1,40 @@
 #[cfg(feature = "stream")]
 use std::task::Poll;
-use std::{fs::File, io::BufReader, path::PathBuf, vec::IntoIter};
+use std::{path::PathBuf, vec::IntoIter};
 
-use mangadex_api_schema_rust::v5::{CoverData, CoverObject};
+use mangadex_api_schema_rust::v5::CoverObject;
 #[cfg(feature = "stream")]
 use tokio_stream::Stream;
 use uuid::Uuid;
 
-use crate::ManagerCoreResult;
+use crate::{
+    ManagerCoreResult,
+    file_dirs::{StorageFormat, storage::read_stored_file},
+};
 
 #[derive(Debug)]
 #[cfg_attr(feature = "actix", derive(actix::MessageResponse))]
 pub struct CoverIdsListDataPull {
     cover_path: PathBuf,
     iter: IntoIter<Uuid>,
+    format: StorageFormat,
 }
 
 impl CoverIdsListDataPull {
-    pub(crate) fn new(cover_path: PathBuf, ids: Vec<Uuid>) -> Self {
+    pub(crate) fn new(cover_path: PathBuf, ids: Vec<Uuid>, format: StorageFormat) -> Self {
         Self {
             cover_path,
             iter: ids.into_iter(),
+            format,
         }
     }
-    fn id_to_cover_json(&self, entry: &Uuid) -> ManagerCoreResult<CoverObject> {
-        let entry = self.cover_path.join(format!("{entry}.json"));
-        if !entry.exists() || !entry.is_file() {
-            return Err(crate::Error::InvalidFileName(entry));
-        }
-        let file = BufReader::new(File::open(entry)?);
-        let o: CoverData = serde_json::from_reader(file)?;
-        Ok(o.data)
-    }
-    fn id_to_cover_cbor(&self, entry: &Uuid) -> ManagerCoreResult<CoverObject> {
-        let entry = self.cover_path.join(format!("{entry}.cbor"));
-        if !entry.exists() || !entry.is_file() {
-            return Err(crate::Error::InvalidFileName(entry));
-        }
-        let file = BufReader::new(File::open(entry)?);
-        let o: CoverObject = ciborium::from_reader(file)?;
-        Ok(o)
-    }
     fn id_to_cover(&self, entry: Uuid) -> ManagerCoreResult<CoverObject> {
-        if let Ok(o) = self.id_to_cover_cbor(&entry) {
-            Ok(o)
-        } else {
-            self.id_to_cover_json(&entry)
-        }
+        let entry = self.cover_path.join(format!("{entry}"));
+        let file = self
+            .format
+            .find_file(&entry)
+            .ok_or(crate::Error::InvalidFileName(entry))?;
+        read_stored_file(&file)
     }
 }
 
@@ -1,16 +1,15 @@
-use mangadex_api_schema_rust::v5::{CoverData, CoverObject};
+use mangadex_api_schema_rust::v5::CoverObject;
 #[cfg(feature = "stream")]
 use std::task::Poll;
 use std::{
-    fs::{read_dir, DirEntry, File, ReadDir},
-    io::BufReader,
+    fs::{read_dir, DirEntry, ReadDir},
     iter::Flatten,
     path::PathBuf,
 };
 #[cfg(feature = "stream")]
 use tokio_stream::Stream;
 
-use crate::ManagerCoreResult;
+use crate::{ManagerCoreResult, file_dirs::storage::read_stored_file};
 
 #[derive(Debug)]
 pub struct CoverListDataPull {
@@ -25,21 +24,7 @@ impl CoverListDataPull {
     fn dir_entry_to_cover(entry: DirEntry) -> ManagerCoreResult<CoverObject> {
         let path = entry.path();
         if path.exists() && path.is_file() {
-            let ext = path
-                .extension()
-                .and_then(|e| e.to_str())
-                .ok_or(crate::Error::InvalidFileName(path.clone()))?;
-            if ext == "json" {
-                let file = BufReader::new(File::open(&path)?);
-                let o: CoverData = serde_json::from_reader(file)?;
-                Ok(o.data)
-            } else if ext == "cbor" {
-                let file = BufReader::new(File::open(&path)?);
-                let o: CoverObject = ciborium::from_reader(file)?;
-                Ok(o)
-            } else {
-                Err(crate::Error::InvalidFileName(path.clone()))
-            }
+            read_stored_file(&path)
         } else {
             Err(crate::Error::InvalidFileName(path))
         }
@@ -3,18 +3,16 @@ pub mod filter;
 pub mod ids;
 pub mod list;
 
-use std::{fs::File, io::BufReader};
-
 pub use filter::MangaListDataPullFilterParams;
 pub use ids::MangaIdsListDataPull;
 pub use list::MangaListDataPull;
-use mangadex_api_schema_rust::v5::{MangaData, MangaObject};
+use mangadex_api_schema_rust::v5::MangaObject;
 use mangadex_api_types_rust::{MangaSortOrder, OrderDirection};
 #[cfg(feature = "stream")]
 use tokio_stream::{Stream, StreamExt};
 use uuid::Uuid;
 
-use crate::{DirsOptions, ManagerCoreResult};
+use crate::{DirsOptions, ManagerCoreResult, file_dirs::storage::read_stored_file};
 
 #[cfg(feature = "stream")]
 use super::{AsyncIntoSorted, IntoParamedFilteredStream};
@@ -130,12 +128,11 @@ impl<I> IntoFiltered<MangaListDataPullFilterParams> for I where I: Iterator<Item
 
 impl Pull<MangaObject, Uuid> for DirsOptions {
     type Error = crate::Error;
-    // TODO add cbor support
     fn pull(&self, id: Uuid) -> crate::ManagerCoreResult<MangaObject> {
-        let manga_id_path = self.mangas_add(format!("{id}.json"));
-        let file = BufReader::new(File::open(manga_id_path)?);
-        let manga: MangaData = serde_json::from_reader(file)?;
-        Ok(manga.data)
+        let manga_path = self
+            .manga_file(id)
+            .ok_or(crate::Error::InvalidFileName(self.mangas_add(id.to_string())))?;
+        read_stored_file(&manga_path)
     }
 }
 
@@ -144,6 +141,6 @@ impl DirsOptions {
         MangaListDataPull::new(self.mangas.clone())
     }
     pub fn pull_mangas_ids(&self, ids: Vec<Uuid>) -> MangaIdsListDataPull {
-        MangaIdsListDataPull::new(self.covers.clone(), ids)
+        MangaIdsListDataPull::new(self.mangas.clone(), ids, self.storage_format)
     }
 }
@@ -1,14 +1,17 @@
 #[cfg(feature = "stream")]
 use std::task::Poll;
-use std::{collections::HashMap, fs::File, io::BufReader, path::PathBuf, vec::IntoIter};
+use std::{collections::HashMap, path::PathBuf, vec::IntoIter};
 
-use mangadex_api_schema_rust::v5::{MangaData, MangaObject};
+use mangadex_api_schema_rust::v5::MangaObject;
 use mangadex_api_types_rust::Language;
 #[cfg(feature = "stream")]
 use tokio_stream::Stream;
 use uuid::Uuid;
 
-use crate::ManagerCoreResult;
+use crate::{
+    ManagerCoreResult,
+    file_dirs::{StorageFormat, storage::read_stored_file},
+};
 
 #[derive(Debug)]
 #[cfg_attr(feature = "actix", derive(actix::MessageResponse))]
@@ -16,49 +19,35 @@ pub struct MangaIdsListDataPull {
     available_langs: HashMap<Uuid, Vec<Language>>,
     manga_path: PathBuf,
     iter: IntoIter<Uuid>,
+    format: StorageFormat,
 }
 
 impl MangaIdsListDataPull {
-    pub(crate) fn new(manga_path: PathBuf, ids: Vec<Uuid>) -> Self {
+    pub(crate) fn new(manga_path: PathBuf, ids: Vec<Uuid>, format: StorageFormat) -> Self {
         Self {
             manga_path,
             iter: ids.into_iter(),
             available_langs: Default::default(),
+            format,
         }
     }
     pub fn with_available_langs(mut self, available_langs: HashMap<Uuid, Vec<Language>>) -> Self {
         self.available_langs = available_langs;
         self
     }
-    fn id_to_manga_json(&self, entry: &Uuid) -> ManagerCoreResult<MangaObject> {
-        let entry = self.manga_path.join(format!("{entry}.json"));
-        if !entry.exists() || !entry.is_file() {
-            return Err(crate::Error::InvalidFileName(entry));
-        }
-        let file = BufReader::new(File::open(entry)?);
-        let o: MangaData = serde_json::from_reader(file)?;
-        Ok(o.data)
-    }
-    fn id_to_manga_cbor(&self, entry: &Uuid) -> ManagerCoreResult<MangaObject> {
-        let entry = self.manga_path.join(format!("{entry}.cbor"));
-        if !entry.exists() || !entry.is_file() {
-            return Err(crate::Error::InvalidFileName(entry));
-        }
-        let file = BufReader::new(File::open(entry)?);
-        let o: MangaObject = ciborium::from_reader(file)?;
-        Ok(o)
-    }
     fn id_to_manga(&self, entry: Uuid) -> ManagerCoreResult<MangaObject> {
-        if let Ok(mut o) = self.id_to_manga_cbor(&entry) {
-            if let Some(langs) = self.available_langs.get(&o.id) {
-                o.attributes
-                    .available_translated_languages
-                    .clone_from(langs);
-            }
-            Ok(o)
-        } else {
-            self.id_to_manga_json(&entry)
+        let entry = self.manga_path.join(format!("{entry}"));
+        let file = self
+            .format
+            .find_file(&entry)
+            .ok_or(crate::Error::InvalidFileName(entry))?;
+        let mut o: MangaObject = read_stored_file(&file)?;
+        if let Some(langs) = self.available_langs.get(&o.id) {
+            o.attributes
+                .available_translated_languages
+                .clone_from(langs);
         }
+        Ok(o)
     }
 }
 
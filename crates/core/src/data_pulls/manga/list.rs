@@ -2,20 +2,19 @@
 use std::task::Poll;
 use std::{
     collections::HashMap,
-    fs::{read_dir, DirEntry, File, ReadDir},
-    io::BufReader,
+    fs::{read_dir, DirEntry, ReadDir},
     iter::Flatten,
     path::PathBuf,
 };
 
-use mangadex_api_schema_rust::v5::{MangaData, MangaObject};
+use mangadex_api_schema_rust::v5::MangaObject;
 use mangadex_api_types_rust::Language;
 #[cfg(feature = "stream")]
 #[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
 use tokio_stream::Stream;
 use uuid::Uuid;
 
-use crate::ManagerCoreResult;
+use crate::{ManagerCoreResult, file_dirs::storage::read_stored_file};
 
 #[derive(Debug)]
 pub struct MangaListDataPull {
@@ -38,21 +37,7 @@ impl MangaListDataPull {
     fn dir_entry_to_manga(&self, entry: DirEntry) -> ManagerCoreResult<MangaObject> {
         let path = entry.path();
         if path.exists() && path.is_file() {
-            let ext = path
-                .extension()
-                .and_then(|e| e.to_str())
-                .ok_or(crate::Error::InvalidFileName(path.clone()))?;
-            let mut data = if ext == "json" {
-                let file = BufReader::new(File::open(&path)?);
-                let o: MangaData = serde_json::from_reader(file)?;
-                o.data
-            } else if ext == "cbor" {
-                let file = BufReader::new(File::open(&path)?);
-                let o: MangaObject = ciborium::from_reader(file)?;
-                o
-            } else {
-                return Err(crate::Error::InvalidFileName(path.clone()));
-            };
+            let mut data: MangaObject = read_stored_file(&path)?;
             if let Some(langs) = self.available_langs.get(&data.id) {
                 data.attributes
                     .available_translated_languages
@@ -1,15 +1,10 @@
 pub mod image;
 
-use std::{
-    fs::{File, create_dir_all},
-    io::{BufWriter, Write},
-};
+use std::fs::create_dir_all;
 
 use itertools::Itertools;
-use mangadex_api_schema_rust::{ApiData, v5::ChapterObject};
-use mangadex_api_types_rust::{
-    ReferenceExpansionResource, RelationshipType, ResponseType, ResultType,
-};
+use mangadex_api_schema_rust::v5::ChapterObject;
+use mangadex_api_types_rust::{ReferenceExpansionResource, RelationshipType};
 use uuid::Uuid;
 
 use crate::{DirsOptions, ManagerCoreResult, data_pulls::Pull};
@@ -66,16 +61,8 @@ impl Push<ChapterObject> for DirsOptions {
     fn push(&mut self, data: ChapterObject) -> crate::ManagerCoreResult<()> {
         let chapter_path = self.chapters_id_add(data.id);
         create_dir_all(&chapter_path)?;
-        let mut file = BufWriter::new(File::create(chapter_path.join("data.json"))?);
-        serde_json::to_writer(
-            &mut file,
-            &non_exhaustive::non_exhaustive!(ApiData<ChapterObject> {
-                response: ResponseType::Entity,
-                result: ResultType::Ok,
-                data: data,
-            }),
-        )?;
-        file.flush()?;
+        self.write_stored_file(&chapter_path.join("data"), &data)?;
+        self.update_index(|index| index.insert_chapter(&data));
         Ok(())
     }
     fn verify_and_push(&mut self, data: ChapterObject) -> ManagerCoreResult<()> {
@@ -7,7 +7,7 @@ use uuid::Uuid;
 
 use crate::{DirsOptions, data_push::Push};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub enum Mode {
     #[default]
     Data,
@@ -52,6 +52,15 @@ impl<R> ChapterImagePushEntry<R> {
             ..self
         }
     }
+    pub fn get_id(&self) -> Uuid {
+        self.id
+    }
+    pub fn get_filename(&self) -> &str {
+        &self.filename
+    }
+    pub fn get_mode(&self) -> Mode {
+        self.mode
+    }
 }
 
 impl<R> Push<ChapterImagePushEntry<R>> for DirsOptions
@@ -3,13 +3,8 @@ use std::{
     io::{self, BufWriter, Read, Write},
 };
 
-use mangadex_api_schema_rust::{
-    ApiData,
-    v5::{CoverObject, RelatedAttributes},
-};
-use mangadex_api_types_rust::{
-    ReferenceExpansionResource, RelationshipType, ResponseType, ResultType,
-};
+use mangadex_api_schema_rust::v5::{CoverObject, RelatedAttributes};
+use mangadex_api_types_rust::{ReferenceExpansionResource, RelationshipType};
 
 use crate::DirsOptions;
 
@@ -25,17 +20,8 @@ pub fn required_cover_references() -> Vec<ReferenceExpansionResource> {
 impl Push<CoverObject> for DirsOptions {
     type Error = crate::Error;
     fn push(&mut self, data: CoverObject) -> crate::ManagerCoreResult<()> {
-        let cover_path = self.covers_add(format!("{}.json", data.id));
-        let mut file = BufWriter::new(File::create(cover_path)?);
-        serde_json::to_writer(
-            &mut file,
-            &non_exhaustive::non_exhaustive!(ApiData<CoverObject> {
-                response: ResponseType::Entity,
-                result: ResultType::Ok,
-                data: data,
-            }),
-        )?;
-        file.flush()?;
+        self.write_stored_file(&self.covers_add(data.id.to_string()), &data)?;
+        self.update_index(|index| index.insert_cover(&data));
         Ok(())
     }
     fn verify_and_push(&mut self, data: CoverObject) -> crate::ManagerCoreResult<()> {
@@ -1,13 +1,6 @@
-use std::{
-    fs::File,
-    io::{BufWriter, Write},
-};
-
 use itertools::Itertools;
-use mangadex_api_schema_rust::{ApiData, v5::MangaObject};
-use mangadex_api_types_rust::{
-    ReferenceExpansionResource, RelationshipType, ResponseType, ResultType,
-};
+use mangadex_api_schema_rust::v5::MangaObject;
+use mangadex_api_types_rust::{ReferenceExpansionResource, RelationshipType};
 use uuid::Uuid;
 
 use crate::{DirsOptions, data_pulls::Pull};
@@ -69,16 +62,8 @@ impl MangaRequiredRelationship {
 impl Push<MangaObject> for DirsOptions {
     type Error = crate::Error;
     fn push(&mut self, data: MangaObject) -> crate::ManagerCoreResult<()> {
-        let mut file = BufWriter::new(File::create(self.mangas_add(format!("{}.json", data.id)))?);
-        serde_json::to_writer(
-            &mut file,
-            &non_exhaustive::non_exhaustive!(ApiData<MangaObject> {
-                response: ResponseType::Entity,
-                data: data,
-                result: ResultType::Ok,
-            }),
-        )?;
-        file.flush()?;
+        self.write_stored_file(&self.mangas_add(data.id.to_string()), &data)?;
+        self.update_index(|index| index.insert_manga(&data));
         Ok(())
     }
     fn verify_and_push(&mut self, data: MangaObject) -> crate::ManagerCoreResult<()> {
@@ -22,4 +22,6 @@ pub enum Error {
     MissingRelationships(Vec<RelationshipType>),
     #[error("No chapter images for {0}")]
     NoChapterImages(Uuid),
+    #[error("This library issue can't be repaired with {0:?}")]
+    UnsupportedRepairAction(crate::file_dirs::verification::RepairAction),
 }
@@ -1,6 +1,10 @@
+#[cfg(feature = "clap")]
+#[cfg_attr(docsrs, doc(cfg(feature = "clap")))]
+pub mod args;
 mod chapters;
 mod covers;
 mod mangas;
+pub mod storage;
 pub mod verification;
 
 use std::{
@@ -10,9 +14,10 @@ use std::{
 };
 
 use serde::{Deserialize, Serialize};
+pub use storage::StorageFormat;
 use verification::DirsOptionsVerificationError;
 
-use crate::ManagerCoreResult;
+use crate::{ManagerCoreResult, index::LibraryIndexHandle};
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct DirsOptions {
@@ -22,6 +27,11 @@ pub struct DirsOptions {
     pub covers: PathBuf,
     #[serde(default)]
     pub init_dirs_if_not_exists: Option<bool>,
+    /// The format used when pushing new data.
+    #[serde(default)]
+    pub storage_format: StorageFormat,
+    #[serde(skip)]
+    pub(crate) index: LibraryIndexHandle,
 }
 
 impl DirsOptions {
@@ -37,9 +47,17 @@ impl DirsOptions {
             mangas: data_dir.join("mangas"),
             covers: data_dir.join("covers"),
             init_dirs_if_not_exists: Some(true),
+            storage_format: Default::default(),
+            index: Default::default(),
             data_dir,
         }
     }
+    pub fn storage_format(self, storage_format: StorageFormat) -> Self {
+        Self {
+            storage_format,
+            ..self
+        }
+    }
     pub fn data_dir_add<P: AsRef<Path>>(&self, path: P) -> PathBuf {
         self.data_dir.join(path)
     }
@@ -0,0 +1,73 @@
+//! The [`clap`] arguments of a [`DirsOptions`], shared by the command-line tools.
+//!
+//! Enabled with the `clap` feature.
+
+use std::path::PathBuf;
+
+use clap::{Args, ValueEnum};
+
+use super::{DirsOptions, StorageFormat};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, ValueEnum, Default)]
+pub enum StorageFormatArg {
+    /// `.json` files (the default)
+    #[default]
+    Json,
+    /// `.cbor` files
+    Cbor,
+    /// zstd compressed `.cbor` files
+    ZstdCbor,
+}
+
+impl From<StorageFormatArg> for StorageFormat {
+    fn from(value: StorageFormatArg) -> Self {
+        match value {
+            StorageFormatArg::Json => Self::Json,
+            StorageFormatArg::Cbor => Self::Cbor,
+            StorageFormatArg::ZstdCbor => Self::ZstdCbor,
+        }
+    }
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct DirsOptionsArgs {
+    /// data directory path
+    ///
+    /// Default: "output"
+    #[arg(long)]
+    pub data_dir: Option<PathBuf>,
+    /// chapter directory relative to `data_dir` (you can put an absolute path if you wanted to)
+    #[arg(long)]
+    pub chapters: Option<PathBuf>,
+    /// manga directory relative to `data_dir` (you can put an absolute path if you wanted to)
+    #[arg(long)]
+    pub mangas: Option<PathBuf>,
+    /// covers directory relative to `data_dir` (you can put an absolute path if you wanted to)
+    #[arg(long)]
+    pub covers: Option<PathBuf>,
+    /// The format used to write the mangas, covers and chapters data
+    ///
+    /// Every format can be read regardless of this option
+    #[arg(long)]
+    pub storage_format: Option<StorageFormatArg>,
+}
+
+impl From<DirsOptionsArgs> for DirsOptions {
+    fn from(value: DirsOptionsArgs) -> Self {
+        let mut options =
+            DirsOptions::new_from_data_dir(value.data_dir.unwrap_or(From::from("output")));
+        if let Some(chapters) = value.chapters {
+            options.chapters = options.data_dir_add(chapters);
+        }
+        if let Some(mangas) = value.mangas {
+            options.mangas = options.data_dir_add(mangas);
+        }
+        if let Some(covers) = value.covers {
+            options.covers = options.data_dir_add(covers);
+        }
+        if let Some(storage_format) = value.storage_format {
+            options.storage_format = storage_format.into();
+        }
+        options
+    }
+}
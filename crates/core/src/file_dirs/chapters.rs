@@ -1,6 +1,8 @@
 use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
+use crate::data_push::chapter::image::Mode;
+
 use super::DirsOptions;
 
 impl DirsOptions {
@@ -32,4 +34,12 @@ impl DirsOptions {
         }
         res
     }
+    /// The images directory of a chapter, without creating it.
+    pub fn chapter_images_dir(&self, id: Uuid, mode: Mode) -> PathBuf {
+        let chapter_path = self.chapters_add(id.to_string());
+        match mode {
+            Mode::Data => chapter_path.join("data"),
+            Mode::DataSaver => chapter_path.join("data-saver"),
+        }
+    }
 }
@@ -0,0 +1,263 @@
+use std::{
+    fs::{File, read_dir, remove_file},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use mangadex_api_schema_rust::v5::{ChapterObject, CoverObject, MangaObject};
+use mangadex_api_types_rust::{ResponseType, ResultType};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use uuid::Uuid;
+
+use crate::ManagerCoreResult;
+
+use super::DirsOptions;
+
+const ZSTD_MAGIC_NUMBER: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+/// The format used to store the mangas, covers and chapters metadata.
+///
+/// Every format can be read regardless of this setting,
+/// it only changes how the data is written.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageFormat {
+    /// `{id}.json` files wrapped in an `ApiData` like the MangaDex API responses
+    #[default]
+    Json,
+    /// Raw `{id}.cbor` files, like the ones in the `.emdx` packages
+    Cbor,
+    /// `{id}.cbor` files compressed with zstd
+    ZstdCbor,
+}
+
+impl StorageFormat {
+    pub const ALL: [Self; 3] = [Self::Json, Self::Cbor, Self::ZstdCbor];
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Cbor | Self::ZstdCbor => "cbor",
+        }
+    }
+    /// Guess the format of a stored file from its extension and its first bytes.
+    pub fn from_file(path: &Path) -> ManagerCoreResult<Self> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => Ok(Self::Json),
+            Some("cbor") => {
+                let mut file = BufReader::new(File::open(path)?);
+                if file.fill_buf()?.starts_with(&ZSTD_MAGIC_NUMBER) {
+                    Ok(Self::ZstdCbor)
+                } else {
+                    Ok(Self::Cbor)
+                }
+            }
+            _ => Err(crate::Error::InvalidFileName(path.to_path_buf())),
+        }
+    }
+    /// Find the stored file for `stem` (a path without extension).
+    ///
+    /// The file with this format extension is prefered if both exists.
+    pub fn find_file(&self, stem: &Path) -> Option<PathBuf> {
+        let first = stem.with_extension(self.extension());
+        if first.is_file() {
+            return Some(first);
+        }
+        Self::ALL
+            .iter()
+            .map(|format| stem.with_extension(format.extension()))
+            .find(|path| path.is_file())
+    }
+}
+
+#[derive(Serialize)]
+struct JsonEntityRef<'a, T> {
+    result: ResultType,
+    response: ResponseType,
+    data: &'a T,
+}
+
+#[derive(Deserialize)]
+struct JsonEntity<T> {
+    data: T,
+}
+
+/// Read a `.json` or `.cbor` (compressed or not) stored file.
+pub fn read_stored_file<T: DeserializeOwned>(path: &Path) -> ManagerCoreResult<T> {
+    let format = StorageFormat::from_file(path)?;
+    let file = BufReader::new(File::open(path)?);
+    match format {
+        StorageFormat::Json => {
+            let entity: JsonEntity<T> = serde_json::from_reader(file)?;
+            Ok(entity.data)
+        }
+        StorageFormat::Cbor => Ok(ciborium::from_reader(file)?),
+        StorageFormat::ZstdCbor => Ok(ciborium::from_reader(
+            zstd::stream::Decoder::with_buffer(file)?,
+        )?),
+    }
+}
+
+/// Write `data` at `stem` (a path without extension) with the given format.
+///
+/// The files of the same entity stored in other formats are removed.
+pub fn write_stored_file<T: Serialize>(
+    format: StorageFormat,
+    stem: &Path,
+    data: &T,
+) -> ManagerCoreResult<PathBuf> {
+    let path = stem.with_extension(format.extension());
+    {
+        let mut file = BufWriter::new(File::create(&path)?);
+        match format {
+            StorageFormat::Json => {
+                serde_json::to_writer(
+                    &mut file,
+                    &JsonEntityRef {
+                        result: ResultType::Ok,
+                        response: ResponseType::Entity,
+                        data,
+                    },
+                )?;
+            }
+            StorageFormat::Cbor => {
+                ciborium::into_writer(data, &mut file)?;
+            }
+            StorageFormat::ZstdCbor => {
+                let mut encoder = zstd::stream::Encoder::new(&mut file, 0)?;
+                ciborium::into_writer(data, &mut encoder)?;
+                encoder.finish()?;
+            }
+        }
+        file.flush()?;
+    }
+    for other in StorageFormat::ALL {
+        let other_path = stem.with_extension(other.extension());
+        if other_path != path && other_path.exists() {
+            remove_file(other_path)?;
+        }
+    }
+    Ok(path)
+}
+
+/// The result of [`DirsOptions::migrate_storage`]
+#[derive(Debug, Default)]
+pub struct StorageMigrationReport {
+    /// The files that were rewritten, with their new path
+    pub migrated: Vec<PathBuf>,
+    /// The files that were already in the target format
+    pub skipped: Vec<PathBuf>,
+    pub failed: Vec<(PathBuf, crate::Error)>,
+}
+
+impl StorageMigrationReport {
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+    fn migrate<T: Serialize + DeserializeOwned>(&mut self, format: StorageFormat, path: PathBuf) {
+        let res = StorageFormat::from_file(&path).and_then(|current| {
+            if current == format {
+                return Ok(None);
+            }
+            let data: T = read_stored_file(&path)?;
+            write_stored_file(format, &path.with_extension(""), &data).map(Some)
+        });
+        match res {
+            Ok(Some(new_path)) => self.migrated.push(new_path),
+            Ok(None) => self.skipped.push(path),
+            Err(err) => self.failed.push((path, err)),
+        }
+    }
+}
+
+fn stored_files_in(dir: &Path) -> ManagerCoreResult<Vec<PathBuf>> {
+    Ok(read_dir(dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| ext == "json" || ext == "cbor")
+        })
+        .collect())
+}
+
+impl DirsOptions {
+    pub fn manga_file(&self, id: Uuid) -> Option<PathBuf> {
+        self.storage_format.find_file(&self.mangas_add(format!("{id}")))
+    }
+    pub fn cover_file(&self, id: Uuid) -> Option<PathBuf> {
+        self.storage_format.find_file(&self.covers_add(format!("{id}")))
+    }
+    pub fn chapter_file(&self, id: Uuid) -> Option<PathBuf> {
+        self.storage_format.find_file(&self.chapters_add(format!("{id}")).join("data"))
+    }
+    /// Write `data` at `stem` (a path without extension) with [`DirsOptions::storage_format`].
+    pub fn write_stored_file<T: Serialize>(
+        &self,
+        stem: &Path,
+        data: &T,
+    ) -> ManagerCoreResult<PathBuf> {
+        write_stored_file(self.storage_format, stem, data)
+    }
+    /// Every stored manga, cover and chapter file of the library, in any format.
+    pub(crate) fn stored_files(&self) -> ManagerCoreResult<Vec<PathBuf>> {
+        let mut files = stored_files_in(&self.mangas_add(""))?;
+        files.extend(stored_files_in(&self.covers_add(""))?);
+        for entry in read_dir(self.chapters_add(""))?.flatten() {
+            if entry.path().is_dir() {
+                files.extend(stored_files_in(&entry.path())?);
+            }
+        }
+        Ok(files)
+    }
+    /// Rewrite every stored manga, cover and chapter in the given format.
+    ///
+    /// [`DirsOptions::storage_format`] is set to `format` at the end,
+    /// even if some files failed to be migrated.
+    pub fn migrate_storage(
+        &mut self,
+        format: StorageFormat,
+    ) -> ManagerCoreResult<StorageMigrationReport> {
+        let mut report = StorageMigrationReport::default();
+        for path in stored_files_in(&self.mangas_add(""))? {
+            report.migrate::<MangaObject>(format, path);
+        }
+        for path in stored_files_in(&self.covers_add(""))? {
+            report.migrate::<CoverObject>(format, path);
+        }
+        for entry in read_dir(self.chapters_add(""))?.flatten() {
+            if !entry.path().is_dir() {
+                continue;
+            }
+            if let Some(path) = format.find_file(&entry.path().join("data")) {
+                report.migrate::<ChapterObject>(format, path);
+            }
+        }
+        self.storage_format = format;
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::{StorageFormat, read_stored_file, write_stored_file};
+
+    #[test]
+    fn write_and_read_back_every_format() {
+        let dir = TempDir::new().unwrap();
+        let stem = dir.path().join("data");
+        let data = vec![String::from("1.png"), String::from("2.png")];
+        for format in StorageFormat::ALL {
+            let path = write_stored_file(format, &stem, &data).unwrap();
+            assert_eq!(StorageFormat::from_file(&path).unwrap(), format);
+            assert_eq!(format.find_file(&stem), Some(path.clone()));
+            assert_eq!(read_stored_file::<Vec<String>>(&path).unwrap(), data);
+        }
+        // Writing a format removes the files in the other formats
+        assert!(!stem.with_extension("json").exists());
+    }
+}
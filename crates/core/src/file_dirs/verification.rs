@@ -1,3 +1,7 @@
+pub mod library;
+
+pub use library::{LibraryCheckReport, LibraryIssue, RepairAction};
+
 #[derive(Debug, thiserror::Error)]
 pub enum DirsOptionsVerificationError {
     #[error("The data dir doesn:t exist")]
@@ -0,0 +1,247 @@
+use std::{
+    fs::{read_dir, remove_dir_all, remove_file},
+    path::Path,
+};
+
+use mangadex_api_schema_rust::v5::ChapterObject;
+use mangadex_api_types_rust::RelationshipType;
+use uuid::Uuid;
+
+use crate::{
+    DirsOptions, ManagerCoreResult, data_push::chapter::image::Mode,
+    file_dirs::storage::read_stored_file,
+};
+
+/// A problem found in the library by [`DirsOptions::check_library`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LibraryIssue {
+    /// A chapter directory without its `data.json` (or `data.cbor`) file
+    ChapterWithoutData { id: Uuid },
+    /// A zero-byte chapter image, usually left by an interrupted download
+    EmptyChapterImage {
+        id: Uuid,
+        mode: Mode,
+        filename: String,
+    },
+    /// A cover whose image file is missing
+    MissingCoverImage { id: Uuid, filename: String },
+    /// A chapter whose manga isn't stored
+    OrphanChapter { id: Uuid, manga: Uuid },
+    /// A manga whose `CoverArt` relationship points to a missing cover
+    MissingMangaCover { id: Uuid, cover: Uuid },
+}
+
+/// How a [`LibraryIssue`] can be repaired
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RepairAction {
+    /// Delete the broken data
+    Delete,
+    /// Download the missing data again
+    Redownload,
+    /// Add the missing data to the download history,
+    /// so it will be downloaded when resuming the unfinished downloads
+    AddToHistory,
+}
+
+impl LibraryIssue {
+    /// The repair actions supported by this issue, the first one is the recommended one.
+    pub fn repair_actions(&self) -> &'static [RepairAction] {
+        match self {
+            Self::ChapterWithoutData { .. } | Self::OrphanChapter { .. } => &[
+                RepairAction::Redownload,
+                RepairAction::AddToHistory,
+                RepairAction::Delete,
+            ],
+            Self::EmptyChapterImage { .. } | Self::MissingCoverImage { .. } => &[
+                RepairAction::Redownload,
+                RepairAction::Delete,
+                RepairAction::AddToHistory,
+            ],
+            Self::MissingMangaCover { .. } => &[RepairAction::Redownload, RepairAction::AddToHistory],
+        }
+    }
+    pub fn can_be_repaired_with(&self, action: RepairAction) -> bool {
+        self.repair_actions().contains(&action)
+    }
+    /// The data to download (or to put in the history) to repair this issue.
+    pub fn download_target(&self) -> (Uuid, RelationshipType) {
+        match self {
+            Self::ChapterWithoutData { id } | Self::EmptyChapterImage { id, .. } => {
+                (*id, RelationshipType::Chapter)
+            }
+            Self::MissingCoverImage { id, .. } => (*id, RelationshipType::CoverArt),
+            Self::OrphanChapter { manga, .. } => (*manga, RelationshipType::Manga),
+            Self::MissingMangaCover { cover, .. } => (*cover, RelationshipType::CoverArt),
+        }
+    }
+}
+
+/// The result of [`DirsOptions::check_library`]
+#[derive(Debug, Clone, Default)]
+pub struct LibraryCheckReport {
+    pub issues: Vec<LibraryIssue>,
+}
+
+impl LibraryCheckReport {
+    pub fn is_healthy(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+fn empty_images(dir: &Path) -> Vec<String> {
+    read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| {
+                    entry
+                        .metadata()
+                        .is_ok_and(|metadata| metadata.is_file() && metadata.len() == 0)
+                })
+                .filter_map(|entry| entry.file_name().to_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+impl DirsOptions {
+    fn check_chapters(&self, report: &mut LibraryCheckReport) -> ManagerCoreResult<()> {
+        for entry in read_dir(self.chapters_add(""))?.flatten() {
+            let Some(id) = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| Uuid::parse_str(name).ok())
+            else {
+                continue;
+            };
+            if !entry.path().is_dir() {
+                continue;
+            }
+            for mode in [Mode::Data, Mode::DataSaver] {
+                report.issues.extend(
+                    empty_images(&self.chapter_images_dir(id, mode))
+                        .into_iter()
+                        .map(|filename| LibraryIssue::EmptyChapterImage { id, mode, filename }),
+                );
+            }
+            let Some(chapter_file) = self.chapter_file(id) else {
+                report.issues.push(LibraryIssue::ChapterWithoutData { id });
+                continue;
+            };
+            let Ok(chapter) = read_stored_file::<ChapterObject>(&chapter_file) else {
+                report.issues.push(LibraryIssue::ChapterWithoutData { id });
+                continue;
+            };
+            if let Some(manga) = chapter
+                .find_first_relationships(RelationshipType::Manga)
+                .map(|rel| rel.id)
+                && self.manga_file(manga).is_none()
+            {
+                report.issues.push(LibraryIssue::OrphanChapter { id, manga });
+            }
+        }
+        Ok(())
+    }
+    fn check_covers(&self, report: &mut LibraryCheckReport) -> ManagerCoreResult<()> {
+        for cover in self.pull_all_covers()?.flatten() {
+            if !self.cover_images_add(&cover.attributes.file_name).is_file() {
+                report.issues.push(LibraryIssue::MissingCoverImage {
+                    id: cover.id,
+                    filename: cover.attributes.file_name,
+                });
+            }
+        }
+        Ok(())
+    }
+    fn check_mangas(&self, report: &mut LibraryCheckReport) -> ManagerCoreResult<()> {
+        for manga in self.pull_all_mangas()?.flatten() {
+            for cover in manga.find_relationships(RelationshipType::CoverArt) {
+                if self.cover_file(cover.id).is_none() {
+                    report.issues.push(LibraryIssue::MissingMangaCover {
+                        id: manga.id,
+                        cover: cover.id,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+    /// Walk the whole library and report the problems found.
+    pub fn check_library(&self) -> ManagerCoreResult<LibraryCheckReport> {
+        let mut report = LibraryCheckReport::default();
+        self.check_chapters(&mut report)?;
+        self.check_covers(&mut report)?;
+        self.check_mangas(&mut report)?;
+        Ok(report)
+    }
+    /// Repair an issue with [`RepairAction::Delete`].
+    ///
+    /// The other actions needs a download manager.
+    pub fn delete_issue_data(&self, issue: &LibraryIssue) -> ManagerCoreResult<()> {
+        match issue {
+            LibraryIssue::ChapterWithoutData { id } | LibraryIssue::OrphanChapter { id, .. } => {
+                remove_dir_all(self.chapters_add(id.to_string()))?;
+                self.update_index(|index| {
+                    index.remove_chapter(*id);
+                });
+            }
+            LibraryIssue::EmptyChapterImage { id, mode, filename } => {
+                remove_file(self.chapter_images_dir(*id, *mode).join(filename))?;
+            }
+            LibraryIssue::MissingCoverImage { id, .. } => {
+                if let Some(cover_file) = self.cover_file(*id) {
+                    remove_file(cover_file)?;
+                }
+                self.update_index(|index| {
+                    index.remove_cover(*id);
+                });
+            }
+            LibraryIssue::MissingMangaCover { .. } => {
+                return Err(crate::Error::UnsupportedRepairAction(RepairAction::Delete));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+    use uuid::Uuid;
+
+    use super::{LibraryIssue, RepairAction};
+    use crate::{DirsOptions, data_push::chapter::image::Mode};
+
+    #[test]
+    fn find_and_delete_broken_chapters() {
+        let dir = TempDir::new().unwrap();
+        let options = DirsOptions::new_from_data_dir(dir.path());
+        options.init_dirs().unwrap();
+        let id = Uuid::new_v4();
+        let images = options.chapters_add(id.to_string()).join("data");
+        std::fs::create_dir_all(&images).unwrap();
+        std::fs::File::create(images.join("1.png")).unwrap();
+
+        let report = options.check_library().unwrap();
+        let empty_image = LibraryIssue::EmptyChapterImage {
+            id,
+            mode: Mode::Data,
+            filename: String::from("1.png"),
+        };
+        assert!(report.issues.contains(&empty_image));
+        assert!(report.issues.contains(&LibraryIssue::ChapterWithoutData { id }));
+
+        options.delete_issue_data(&empty_image).unwrap();
+        assert!(!images.join("1.png").exists());
+        let missing_cover = LibraryIssue::MissingMangaCover {
+            id: Uuid::new_v4(),
+            cover: Uuid::new_v4(),
+        };
+        assert!(!missing_cover.can_be_repaired_with(RepairAction::Delete));
+        assert!(options.delete_issue_data(&missing_cover).is_err());
+        options
+            .delete_issue_data(&LibraryIssue::ChapterWithoutData { id })
+            .unwrap();
+        assert!(options.check_library().unwrap().is_healthy());
+    }
+}
@@ -0,0 +1,605 @@
+//! A persistent index of the library metadata.
+//!
+//! The index maps mangas to their chapters and covers, and keeps the languages and groups of each chapter,
+//! so that the list pulls don't need to deserialize every file of the library to find the data of one manga.
+//!
+//! It is stored in `{data_dir}/index.cbor`, kept in sync by the [`crate::data_push::Push`] implementations
+//! and rebuilt from scratch if it is missing or older than the library files.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{File, rename},
+    io::{BufReader, BufWriter, Write},
+    path::PathBuf,
+    sync::{Arc, PoisonError, RwLock},
+    time::SystemTime,
+};
+
+use mangadex_api_schema_rust::v5::{ChapterObject, CoverObject, MangaObject};
+use mangadex_api_types_rust::{Language, RelationshipType};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    DirsOptions, ManagerCoreResult,
+    data_pulls::{
+        chapter::{ChapterListDataPullFilterParams, ids::ChapterIdsListDataPull},
+        cover::{CoverIdsListDataPull, CoverListDataPullFilterParams},
+    },
+};
+
+pub const INDEX_FILENAME: &str = "index.cbor";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChapterIndexEntry {
+    pub manga: Option<Uuid>,
+    pub translated_language: Language,
+    pub groups: Vec<Uuid>,
+}
+
+impl From<&ChapterObject> for ChapterIndexEntry {
+    fn from(value: &ChapterObject) -> Self {
+        Self {
+            manga: value
+                .find_first_relationships(RelationshipType::Manga)
+                .map(|rel| rel.id),
+            translated_language: value.attributes.translated_language,
+            groups: value
+                .find_relationships(RelationshipType::ScanlationGroup)
+                .into_iter()
+                .map(|rel| rel.id)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoverIndexEntry {
+    pub manga: Option<Uuid>,
+    pub locale: Option<Language>,
+}
+
+impl From<&CoverObject> for CoverIndexEntry {
+    fn from(value: &CoverObject) -> Self {
+        Self {
+            manga: value
+                .find_first_relationships(RelationshipType::Manga)
+                .map(|rel| rel.id),
+            locale: value.attributes.locale,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LibraryIndex {
+    saved_at: Option<SystemTime>,
+    mangas: HashSet<Uuid>,
+    chapters: HashMap<Uuid, ChapterIndexEntry>,
+    covers: HashMap<Uuid, CoverIndexEntry>,
+    #[serde(skip)]
+    manga_chapters: HashMap<Uuid, HashSet<Uuid>>,
+    #[serde(skip)]
+    manga_covers: HashMap<Uuid, HashSet<Uuid>>,
+    #[serde(skip)]
+    dirty: bool,
+}
+
+impl LibraryIndex {
+    /// Build the index by reading every manga, chapter and cover of the library.
+    pub fn build(dirs: &DirsOptions) -> ManagerCoreResult<Self> {
+        let mut index = Self::default();
+        for manga in dirs.pull_all_mangas()?.flatten() {
+            index.insert_manga(&manga);
+        }
+        for chapter in dirs.pull_all_chapter()?.flatten() {
+            index.insert_chapter(&chapter);
+        }
+        for cover in dirs.pull_all_covers()?.flatten() {
+            index.insert_cover(&cover);
+        }
+        Ok(index)
+    }
+    fn reindex(&mut self) {
+        self.manga_chapters.clear();
+        self.manga_covers.clear();
+        for (id, chapter) in &self.chapters {
+            if let Some(manga) = chapter.manga {
+                self.manga_chapters.entry(manga).or_default().insert(*id);
+            }
+        }
+        for (id, cover) in &self.covers {
+            if let Some(manga) = cover.manga {
+                self.manga_covers.entry(manga).or_default().insert(*id);
+            }
+        }
+    }
+    /// `true` if the index has changes that are not saved yet.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+    pub fn insert_manga(&mut self, manga: &MangaObject) {
+        self.dirty |= self.mangas.insert(manga.id);
+    }
+    pub fn insert_chapter(&mut self, chapter: &ChapterObject) {
+        let entry = ChapterIndexEntry::from(chapter);
+        if self.chapters.get(&chapter.id) == Some(&entry) {
+            return;
+        }
+        self.remove_chapter(chapter.id);
+        if let Some(manga) = entry.manga {
+            self.manga_chapters
+                .entry(manga)
+                .or_default()
+                .insert(chapter.id);
+        }
+        self.chapters.insert(chapter.id, entry);
+        self.dirty = true;
+    }
+    pub fn insert_cover(&mut self, cover: &CoverObject) {
+        let entry = CoverIndexEntry::from(cover);
+        if self.covers.get(&cover.id) == Some(&entry) {
+            return;
+        }
+        self.remove_cover(cover.id);
+        if let Some(manga) = entry.manga {
+            self.manga_covers.entry(manga).or_default().insert(cover.id);
+        }
+        self.covers.insert(cover.id, entry);
+        self.dirty = true;
+    }
+    /// Remove a manga from the index.
+    ///
+    /// Its chapters and covers are kept since they are still on the disk.
+    pub fn remove_manga(&mut self, id: Uuid) -> bool {
+        let removed = self.mangas.remove(&id);
+        self.dirty |= removed;
+        removed
+    }
+    pub fn remove_chapter(&mut self, id: Uuid) -> Option<ChapterIndexEntry> {
+        let entry = self.chapters.remove(&id)?;
+        if let Some(manga) = entry.manga
+            && let Some(chapters) = self.manga_chapters.get_mut(&manga)
+        {
+            chapters.remove(&id);
+            if chapters.is_empty() {
+                self.manga_chapters.remove(&manga);
+            }
+        }
+        self.dirty = true;
+        Some(entry)
+    }
+    pub fn remove_cover(&mut self, id: Uuid) -> Option<CoverIndexEntry> {
+        let entry = self.covers.remove(&id)?;
+        if let Some(manga) = entry.manga
+            && let Some(covers) = self.manga_covers.get_mut(&manga)
+        {
+            covers.remove(&id);
+            if covers.is_empty() {
+                self.manga_covers.remove(&manga);
+            }
+        }
+        self.dirty = true;
+        Some(entry)
+    }
+    pub fn contains_manga(&self, id: &Uuid) -> bool {
+        self.mangas.contains(id)
+    }
+    pub fn mangas(&self) -> impl Iterator<Item = &Uuid> {
+        self.mangas.iter()
+    }
+    pub fn chapter(&self, id: &Uuid) -> Option<&ChapterIndexEntry> {
+        self.chapters.get(id)
+    }
+    pub fn cover(&self, id: &Uuid) -> Option<&CoverIndexEntry> {
+        self.covers.get(id)
+    }
+    pub fn manga_chapters(&self, manga: &Uuid) -> Vec<Uuid> {
+        self.manga_chapters
+            .get(manga)
+            .map(|chapters| chapters.iter().copied().collect())
+            .unwrap_or_default()
+    }
+    pub fn manga_covers(&self, manga: &Uuid) -> Vec<Uuid> {
+        self.manga_covers
+            .get(manga)
+            .map(|covers| covers.iter().copied().collect())
+            .unwrap_or_default()
+    }
+    pub fn group_chapters(&self, group: &Uuid) -> Vec<Uuid> {
+        self.chapters
+            .iter()
+            .filter(|(_, chapter)| chapter.groups.contains(group))
+            .map(|(id, _)| *id)
+            .collect()
+    }
+    /// The translated languages of the chapters of each manga.
+    pub fn available_languages(&self) -> HashMap<Uuid, Vec<Language>> {
+        self.manga_chapters
+            .iter()
+            .map(|(manga, chapters)| {
+                let mut langs = Vec::<Language>::new();
+                for lang in chapters
+                    .iter()
+                    .flat_map(|id| self.chapters.get(id))
+                    .map(|chapter| chapter.translated_language)
+                {
+                    if !langs.contains(&lang) {
+                        langs.push(lang);
+                    }
+                }
+                (*manga, langs)
+            })
+            .collect()
+    }
+    /// The chapters that might match the `filter`.
+    ///
+    /// Only `manga_ids` and `translated_languages` are used here,
+    /// so the chapters still need to be filtered with [`crate::data_pulls::IntoFiltered`].
+    pub fn chapter_candidates(&self, filter: &ChapterListDataPullFilterParams) -> Vec<Uuid> {
+        let is_lang_valid = |chapter: &ChapterIndexEntry| {
+            filter.translated_languages.is_empty()
+                || filter
+                    .translated_languages
+                    .contains(&chapter.translated_language)
+        };
+        if filter.manga_ids.is_empty() {
+            self.chapters
+                .iter()
+                .filter(|(_, chapter)| is_lang_valid(chapter))
+                .map(|(id, _)| *id)
+                .collect()
+        } else {
+            filter
+                .manga_ids
+                .iter()
+                .flat_map(|manga| self.manga_chapters.get(manga))
+                .flatten()
+                .filter(|id| self.chapters.get(id).is_some_and(is_lang_valid))
+                .copied()
+                .collect()
+        }
+    }
+    /// The covers that might match the `filter`.
+    ///
+    /// Only `manga_ids` and `locales` are used here,
+    /// so the covers still need to be filtered with [`crate::data_pulls::IntoFiltered`].
+    pub fn cover_candidates(&self, filter: &CoverListDataPullFilterParams) -> Vec<Uuid> {
+        let is_locale_valid = |cover: &CoverIndexEntry| {
+            filter.locales.is_empty()
+                || cover
+                    .locale
+                    .is_some_and(|locale| filter.locales.contains(&locale))
+        };
+        if filter.manga_ids.is_empty() {
+            self.covers
+                .iter()
+                .filter(|(_, cover)| is_locale_valid(cover))
+                .map(|(id, _)| *id)
+                .collect()
+        } else {
+            filter
+                .manga_ids
+                .iter()
+                .flat_map(|manga| self.manga_covers.get(manga))
+                .flatten()
+                .filter(|id| self.covers.get(id).is_some_and(is_locale_valid))
+                .copied()
+                .collect()
+        }
+    }
+    /// `true` if none of the library directories and stored files changed since the index was saved.
+    ///
+    /// The files are checked too since rewriting one of them doesn't change the modification time of its directory.
+    fn is_fresh(&self, dirs: &DirsOptions) -> bool {
+        let Some(saved_at) = self.saved_at else {
+            return false;
+        };
+        let is_older = |path: &PathBuf| {
+            path.metadata()
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|modified| modified <= saved_at)
+        };
+        [
+            dirs.mangas_add(""),
+            dirs.chapters_add(""),
+            dirs.covers_add(""),
+        ]
+        .iter()
+        .all(is_older)
+            && dirs
+                .stored_files()
+                .is_ok_and(|files| files.iter().all(is_older))
+    }
+    /// Load the index file if it is still fresh, or rebuild it.
+    pub fn load_or_build(dirs: &DirsOptions) -> ManagerCoreResult<Self> {
+        let loaded = File::open(dirs.index_path())
+            .ok()
+            .and_then(|file| ciborium::from_reader::<Self, _>(BufReader::new(file)).ok())
+            .filter(|index| index.is_fresh(dirs));
+        if let Some(mut index) = loaded {
+            index.reindex();
+            Ok(index)
+        } else {
+            let mut index = Self::build(dirs)?;
+            index.dirty = true;
+            Ok(index)
+        }
+    }
+    /// Write the index in `{data_dir}/index.cbor`.
+    pub fn save(&mut self, dirs: &DirsOptions) -> ManagerCoreResult<()> {
+        let path = dirs.index_path();
+        let tmp_path = path.with_extension("cbor.tmp");
+        self.saved_at = Some(SystemTime::now());
+        {
+            let mut file = BufWriter::new(File::create(&tmp_path)?);
+            ciborium::into_writer(&*self, &mut file)?;
+            file.flush()?;
+        }
+        rename(tmp_path, path)?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct LoadedIndex {
+    paths: [PathBuf; 4],
+    index: LibraryIndex,
+}
+
+/// A shared handle to the lazily loaded [`LibraryIndex`] of a [`DirsOptions`].
+#[derive(Debug, Clone, Default)]
+pub struct LibraryIndexHandle(Arc<RwLock<Option<LoadedIndex>>>);
+
+impl LibraryIndexHandle {
+    /// Drop the loaded index so it will be loaded again on the next use.
+    pub fn reset(&self) {
+        self.0.write().unwrap_or_else(PoisonError::into_inner).take();
+    }
+}
+
+impl DirsOptions {
+    pub fn index_path(&self) -> PathBuf {
+        self.data_dir_add(INDEX_FILENAME)
+    }
+    fn index_paths(&self) -> [PathBuf; 4] {
+        [
+            self.index_path(),
+            self.mangas_add(""),
+            self.chapters_add(""),
+            self.covers_add(""),
+        ]
+    }
+    pub fn index_handle(&self) -> &LibraryIndexHandle {
+        &self.index
+    }
+    /// Run `f` with the library index, loading it first if needed.
+    pub fn with_index_mut<R, F>(&self, f: F) -> ManagerCoreResult<R>
+    where
+        F: FnOnce(&mut LibraryIndex) -> R,
+    {
+        let paths = self.index_paths();
+        let mut loaded = self
+            .index
+            .0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        match loaded.as_mut() {
+            Some(loaded) if loaded.paths == paths => Ok(f(&mut loaded.index)),
+            _ => {
+                let mut index = LibraryIndex::load_or_build(self)?;
+                let res = f(&mut index);
+                *loaded = Some(LoadedIndex { paths, index });
+                Ok(res)
+            }
+        }
+    }
+    pub fn with_index<R, F>(&self, f: F) -> ManagerCoreResult<R>
+    where
+        F: FnOnce(&LibraryIndex) -> R,
+    {
+        self.with_index_mut(|index| f(index))
+    }
+    /// Load the index now instead of on its first use, rebuilding it if it is stale.
+    ///
+    /// The index is locked while it loads, so its users wait for it instead of building it again.
+    pub fn load_index(&self) -> ManagerCoreResult<()> {
+        self.with_index(|_| ())
+    }
+    /// Update the index after a push or a deletion.
+    ///
+    /// Failing to update the index is not an error for the caller since the data is already on the disk,
+    /// the index is just reloaded (and rebuilt if needed) on its next use.
+    pub fn update_index<F>(&self, f: F)
+    where
+        F: FnOnce(&mut LibraryIndex),
+    {
+        if let Err(_e) = self.with_index_mut(f) {
+            #[cfg(feature = "log")]
+            log::error!("Cannot update the library index: {_e}");
+            self.index.reset();
+        }
+    }
+    /// Save the index if it has unsaved changes.
+    ///
+    /// Returns `true` if the index was written.
+    pub fn save_index(&self) -> ManagerCoreResult<bool> {
+        let paths = self.index_paths();
+        let mut loaded = self
+            .index
+            .0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        match loaded.as_mut() {
+            Some(loaded) if loaded.paths == paths && loaded.index.is_dirty() => {
+                loaded.index.save(self)?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+    /// Rebuild the index from scratch and save it.
+    pub fn rebuild_index(&self) -> ManagerCoreResult<()> {
+        let mut index = LibraryIndex::build(self)?;
+        index.save(self)?;
+        let paths = self.index_paths();
+        let mut loaded = self
+            .index
+            .0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        *loaded = Some(LoadedIndex { paths, index });
+        Ok(())
+    }
+    /// Pull the chapters that might match `filter` without reading the whole library.
+    ///
+    /// The result still needs to be filtered with [`crate::data_pulls::IntoFiltered`].
+    pub fn pull_indexed_chapters(
+        &self,
+        filter: &ChapterListDataPullFilterParams,
+    ) -> ManagerCoreResult<ChapterIdsListDataPull> {
+        let ids = self.with_index(|index| index.chapter_candidates(filter))?;
+        Ok(self.pull_chapter_ids(ids))
+    }
+    /// Pull the covers that might match `filter` without reading the whole library.
+    ///
+    /// The result still needs to be filtered with [`crate::data_pulls::IntoFiltered`].
+    pub fn pull_indexed_covers(
+        &self,
+        filter: &CoverListDataPullFilterParams,
+    ) -> ManagerCoreResult<CoverIdsListDataPull> {
+        let ids = self.with_index(|index| index.cover_candidates(filter))?;
+        Ok(self.pull_covers_ids(ids))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread::sleep, time::Duration};
+
+    use mangadex_api_schema_rust::v5::{ChapterObject, CoverObject, MangaObject, Relationship};
+    use mangadex_api_types_rust::{Language, RelationshipType};
+    use tempfile::TempDir;
+    use uuid::Uuid;
+
+    use super::LibraryIndex;
+    use crate::{
+        DirsOptions, data_pulls::chapter::ChapterListDataPullFilterParams, data_push::Push,
+    };
+
+    fn library() -> (TempDir, DirsOptions) {
+        let dir = TempDir::new().unwrap();
+        let options = DirsOptions::new_from_data_dir(dir.path());
+        options.init_dirs().unwrap();
+        (dir, options)
+    }
+
+    fn manga_relationship(manga: Uuid) -> Relationship {
+        let mut relationship = Relationship::default();
+        relationship.id = manga;
+        relationship.type_ = RelationshipType::Manga;
+        relationship
+    }
+
+    fn chapter(manga: Uuid, language: Language) -> ChapterObject {
+        let mut chapter = ChapterObject::default();
+        chapter.id = Uuid::new_v4();
+        chapter.attributes.translated_language = language;
+        chapter.relationships.push(manga_relationship(manga));
+        chapter
+    }
+
+    fn manga() -> MangaObject {
+        let mut manga = MangaObject::default();
+        manga.id = Uuid::new_v4();
+        manga
+    }
+
+    #[test]
+    fn build_from_the_library_files() {
+        let (_dir, mut options) = library();
+        let manga = manga();
+        let english = chapter(manga.id, Language::English);
+        let french = chapter(manga.id, Language::French);
+        let mut cover = CoverObject::default();
+        cover.id = Uuid::new_v4();
+        cover.relationships.push(manga_relationship(manga.id));
+        options.push(manga.clone()).unwrap();
+        options.push(vec![english.clone(), french.clone()]).unwrap();
+        options.push(cover.clone()).unwrap();
+
+        let index = LibraryIndex::build(&options).unwrap();
+        assert!(index.contains_manga(&manga.id));
+        let mut chapters = index.manga_chapters(&manga.id);
+        chapters.sort();
+        let mut expected = vec![english.id, french.id];
+        expected.sort();
+        assert_eq!(chapters, expected);
+        assert_eq!(index.manga_covers(&manga.id), vec![cover.id]);
+        assert_eq!(index.available_languages()[&manga.id].len(), 2);
+        let filter = ChapterListDataPullFilterParams {
+            manga_ids: vec![manga.id],
+            translated_languages: vec![Language::French],
+            ..Default::default()
+        };
+        assert_eq!(index.chapter_candidates(&filter), vec![french.id]);
+    }
+
+    #[test]
+    fn pushes_update_the_saved_index() {
+        let (dir, mut options) = library();
+        let manga = manga();
+        let english = chapter(manga.id, Language::English);
+        options.push(manga.clone()).unwrap();
+        options.push(english.clone()).unwrap();
+        assert!(options.save_index().unwrap());
+        assert!(!options.save_index().unwrap());
+
+        // Loaded back from the file, without rebuilding it
+        let mut reloaded = DirsOptions::new_from_data_dir(dir.path());
+        let loaded = LibraryIndex::load_or_build(&reloaded).unwrap();
+        assert!(!loaded.is_dirty());
+        assert_eq!(loaded.manga_chapters(&manga.id), vec![english.id]);
+
+        let french = chapter(manga.id, Language::French);
+        reloaded.push(french.clone()).unwrap();
+        reloaded
+            .with_index(|index| {
+                assert!(index.is_dirty());
+                assert_eq!(index.chapter(&french.id).unwrap().manga, Some(manga.id));
+                assert_eq!(index.manga_chapters(&manga.id).len(), 2);
+            })
+            .unwrap();
+        reloaded.update_index(|index| {
+            index.remove_chapter(english.id);
+        });
+        reloaded
+            .with_index(|index| assert_eq!(index.manga_chapters(&manga.id), vec![french.id]))
+            .unwrap();
+    }
+
+    #[test]
+    fn rewritten_files_make_the_index_stale() {
+        let (_dir, mut options) = library();
+        let manga = manga();
+        let mut english = chapter(manga.id, Language::English);
+        options.push(manga.clone()).unwrap();
+        options.push(english.clone()).unwrap();
+        options.save_index().unwrap();
+        assert!(!LibraryIndex::load_or_build(&options).unwrap().is_dirty());
+
+        // Rewritten behind the index back, which doesn't touch the chapters directory
+        sleep(Duration::from_millis(20));
+        english.attributes.translated_language = Language::Japanese;
+        options
+            .write_stored_file(&options.chapters_id_add(english.id).join("data"), &english)
+            .unwrap();
+
+        let rebuilt = LibraryIndex::load_or_build(&options).unwrap();
+        assert!(rebuilt.is_dirty());
+        assert_eq!(
+            rebuilt.chapter(&english.id).unwrap().translated_language,
+            Language::Japanese
+        );
+    }
+}
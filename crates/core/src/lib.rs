@@ -8,6 +8,7 @@ pub mod data_pulls;
 pub mod data_push;
 pub mod error;
 pub mod file_dirs;
+pub mod index;
 
 pub(crate) type ManagerCoreResult<T, E = error::Error> = Result<T, E>;
 
@@ -1,19 +1,19 @@
+pub mod import;
+pub mod pages;
 pub mod pull;
+pub mod verify;
+pub mod volumes;
 
 use std::{
-    collections::HashMap,
     fmt::Debug,
-    fs::File,
-    io::{self, BufRead, BufReader, Read, Seek},
-    path::Path,
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
 };
 
 use crate::utils::zstd_reader::Reader;
-use api_core::{
-    data_push::{chapter::image::ChapterImagePushEntry, Push},
-    DirsOptions,
-};
+use api_core::{data_push::Push, DirsOptions};
 use mangadex_api_schema_rust::v5::{ChapterObject, CoverObject, MangaObject};
+use import::ImportOptions;
 use pull::{
     chapter::ArchiveChapterPull, manga::ArchiveMangaPull, ArchiveAnyPull, ArchiveCoverPull,
 };
@@ -22,7 +22,12 @@ use serde::de::DeserializeOwned;
 use uuid::Uuid;
 use zstd::stream::raw::Decoder;
 
-use crate::{constants::CONTENTS_FILENAME, PackageContents, ThisResult};
+use crate::{
+    constants::{CONTENTS_FILENAME, PACKAGE_FORMAT_VERSION},
+    contents::options::PackageContentsOptions,
+    toc::{TableOfContents, TocEntry},
+    PackageContents, ThisResult,
+};
 
 type DecoderInner<'a, R> = Reader<R, Decoder<'a>>;
 
@@ -31,6 +36,7 @@ where
     R: Seek + BufRead,
 {
     contents: Option<PackageContents>,
+    toc: Option<TableOfContents>,
     tar_archive: Option<tar::Archive<DecoderInner<'a, R>>>,
 }
 
@@ -41,6 +47,7 @@ where
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Archive")
             .field("contents", &self.contents)
+            .field("toc", &self.toc)
             .field("decoder", &"zstd::Decoder<'_, (some stream)>")
             .finish()
     }
@@ -60,6 +67,62 @@ fn tar_archive_not_found_error() -> io::Error {
     )
 }
 
+fn entry_not_found_error() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        "the manga/chapter/cover is not found in the archive",
+    )
+}
+
+fn check_format_version(contents: &PackageContents) -> io::Result<()> {
+    let version = contents.get_format_version();
+    if version > PACKAGE_FORMAT_VERSION {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "the package format version {version} is newer than the supported version \
+                 {PACKAGE_FORMAT_VERSION}"
+            ),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn not_found_in_package(err: api_core::Error, what: String) -> api_core::Error {
+    let api_core::Error::Io(io_err) = err else {
+        return err;
+    };
+    if io_err.kind() == io::ErrorKind::NotFound {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("the {what} is not found in the package"),
+        )
+        .into()
+    } else {
+        io_err.into()
+    }
+}
+
+/// Wrap `reader` in a tar archive reading past the end-of-archive blocks,
+/// so the tar streams of a multi-part package are read as one.
+fn new_tar_archive<R: Read>(reader: R) -> tar::Archive<R> {
+    let mut archive = tar::Archive::new(reader);
+    archive.set_ignore_zeros(true);
+    archive
+}
+
+fn read_metadata<O: DeserializeOwned>(
+    entry: &mut dyn Read,
+    options: &PackageContentsOptions,
+) -> ThisResult<O> {
+    if options.zstd_compressed_metadata {
+        Ok(ciborium::from_reader(options.entry_decoder(entry)?)?)
+    } else {
+        Ok(ciborium::from_reader(entry)?)
+    }
+}
+
 impl<R> Seek for Archive<'_, R>
 where
     R: Seek + BufRead,
@@ -72,7 +135,7 @@ where
         let mut decoder = archive.into_inner();
         let res = decoder.reader_mut().seek(pos)?;
         decoder.reset()?;
-        self.tar_archive.replace(tar::Archive::new(decoder));
+        self.tar_archive.replace(new_tar_archive(decoder));
         Ok(res)
     }
 }
@@ -107,7 +170,51 @@ where
     {
         Ok(func(self.get_archive(rewind)?))
     }
+    /// Decode the entry frame pointed by `toc_entry` and pass the entry data to `read`.
+    ///
+    /// The archive is rewinded afterward.
+    fn read_toc_entry<O, F>(&mut self, toc_entry: TocEntry, read: F) -> ThisResult<O>
+    where
+        F: FnOnce(&mut dyn Read) -> ThisResult<O>,
+    {
+        let archive = self
+            .tar_archive
+            .take()
+            .ok_or_else(tar_archive_not_found_error)?;
+        let mut decoder = archive.into_inner();
+        let res = (|| -> ThisResult<O> {
+            decoder
+                .reader_mut()
+                .seek(SeekFrom::Start(toc_entry.offset))?;
+            decoder.reset()?;
+            decoder.set_single_frame();
+            let mut frame = tar::Archive::new(&mut decoder);
+            let mut entry = frame
+                .entries()?
+                .next()
+                .ok_or_else(entry_not_found_error)??;
+            read(&mut entry)
+        })();
+        let rewinded = decoder
+            .reader_mut()
+            .rewind()
+            .and_then(|_| decoder.reset());
+        self.tar_archive.replace(new_tar_archive(decoder));
+        rewinded?;
+        res
+    }
+    fn get_toc_entry<P: AsRef<Path>>(&self, path: P) -> Option<TocEntry> {
+        self.toc.as_ref()?.get(path).copied()
+    }
     fn seed_contents(&mut self) -> ThisResult<()> {
+        if let Some(toc_entry) = self.get_toc_entry(CONTENTS_FILENAME) {
+            let contents = self.read_toc_entry(toc_entry, |entry| -> ThisResult<PackageContents> {
+                Ok(ciborium::from_reader(entry)?)
+            })?;
+            check_format_version(&contents)?;
+            self.contents.replace(contents);
+            return Ok(());
+        }
         let contents = self.use_tar_archive(true, |archive| -> ThisResult<PackageContents> {
             let mut content_file = archive
                 .entries()?
@@ -122,13 +229,16 @@ where
                 .ok_or_else(archive_contents_not_found_error)?;
             Ok(ciborium::from_reader(&mut content_file)?)
         })??;
+        check_format_version(&contents)?;
         self.contents.replace(contents);
         Ok(())
     }
-    pub fn new(reader: R, decoder: Decoder<'_>) -> ThisResult<Archive<'_, R>> {
+    pub fn new(mut reader: R, decoder: Decoder<'_>) -> ThisResult<Archive<'_, R>> {
+        let toc = TableOfContents::read_from(&mut reader)?;
         let mut new_self = Archive {
-            tar_archive: Some(tar::Archive::new(Reader::new(reader, decoder))),
+            tar_archive: Some(new_tar_archive(Reader::new(reader, decoder))),
             contents: None,
+            toc,
         };
         new_self.seed_contents()?;
         Ok(new_self)
@@ -142,6 +252,12 @@ where
             .as_ref()
             .ok_or_else(archive_contents_not_found_error)
     }
+    /// The table of contents of the package.
+    ///
+    /// Packages written with the linear layout (before the table of contents) don't have one.
+    pub fn get_table_of_contents(&self) -> Option<&TableOfContents> {
+        self.toc.as_ref()
+    }
     pub fn manga_pull(
         &mut self,
         rewind: bool,
@@ -178,113 +294,47 @@ where
             package_contents,
         })
     }
-    fn pull_data<O, F>(&mut self, rewind: bool, mut find: F) -> ThisResult<O>
+    /// Read the metadata entry at `path`.
+    ///
+    /// Jumps straight to the entry if the package has a table of contents,
+    /// scans the archive entries otherwise.
+    fn pull_data<O>(&mut self, rewind: bool, path: &Path) -> ThisResult<O>
     where
         O: DeserializeOwned,
-        F: FnMut(&tar::Entry<DecoderInner<'a, R>>, &PackageContents) -> bool,
     {
-        let package_contents = self.get_package_contents().cloned()?;
+        let options = self.get_package_contents()?.get_options().into_owned();
+        if let Some(toc_entry) = self.get_toc_entry(path) {
+            return self.read_toc_entry(toc_entry, |entry| read_metadata(entry, &options));
+        }
         self.use_tar_archive(rewind, |archive| {
-            let options = package_contents.options.clone().unwrap_or_default();
-            let entry = archive
+            let mut entry = archive
                 .entries()?
                 .flatten()
-                .find(|entry| find(entry, &package_contents))
-                .ok_or(io::Error::new(
-                    io::ErrorKind::NotFound,
-                    "the manga/chapter/cover is not found in the archive",
-                ))?;
-            if options.zstd_compressed_metadata {
-                Ok(ciborium::from_reader(zstd::stream::Decoder::new(entry)?)?)
-            } else {
-                Ok(ciborium::from_reader(entry)?)
-            }
+                .find(|entry| entry.path().is_ok_and(|entry_path| entry_path == path))
+                .ok_or_else(entry_not_found_error)?;
+            read_metadata(&mut entry, &options)
         })?
     }
+    fn package_dirs_path<F>(&self, path: F) -> io::Result<PathBuf>
+    where
+        F: FnOnce(&DirsOptions) -> PathBuf,
+    {
+        Ok(path(&self.get_package_contents()?.get_options().get_dirs()))
+    }
     pub fn get_manga(&mut self, id: Uuid, rewind: bool) -> ThisResult<MangaObject> {
-        self.pull_data(rewind, |entry, package_contents| {
-            let dirs_options = package_contents
-                .options
-                .clone()
-                .unwrap_or_default()
-                .directories
-                .unwrap_or_default();
-            let Ok(path) = entry.path() else {
-                return false;
-            };
-            path.as_ref() == AsRef::<Path>::as_ref(&dirs_options.mangas_add(format!("{id}.cbor")))
-        })
-        .map_err(|err| {
-            let api_core::Error::Io(io_err) = err else {
-                return err;
-            };
-            if io_err.kind() == io::ErrorKind::NotFound {
-                io::Error::new(
-                    io::ErrorKind::NotFound,
-                    format!("the manga {id} is not found in the package"),
-                )
-                .into()
-            } else {
-                io_err.into()
-            }
-        })
+        let path = self.package_dirs_path(|dirs| dirs.mangas_add(format!("{id}.cbor")))?;
+        self.pull_data(rewind, &path)
+            .map_err(|err| not_found_in_package(err, format!("manga {id}")))
     }
     pub fn get_cover(&mut self, id: Uuid, rewind: bool) -> ThisResult<CoverObject> {
-        self.pull_data(rewind, |entry, package_contents| {
-            let dirs_options = package_contents
-                .options
-                .clone()
-                .unwrap_or_default()
-                .directories
-                .unwrap_or_default();
-            let Ok(path) = entry.path() else {
-                return false;
-            };
-            path.as_ref() == AsRef::<Path>::as_ref(&dirs_options.covers_add(format!("{id}.cbor")))
-        })
-        .map_err(|err| {
-            let api_core::Error::Io(io_err) = err else {
-                return err;
-            };
-            if io_err.kind() == io::ErrorKind::NotFound {
-                io::Error::new(
-                    io::ErrorKind::NotFound,
-                    format!("the cover {id} is not found in the package"),
-                )
-                .into()
-            } else {
-                io_err.into()
-            }
-        })
+        let path = self.package_dirs_path(|dirs| dirs.covers_add(format!("{id}.cbor")))?;
+        self.pull_data(rewind, &path)
+            .map_err(|err| not_found_in_package(err, format!("cover {id}")))
     }
     pub fn get_chapter(&mut self, id: Uuid, rewind: bool) -> ThisResult<ChapterObject> {
-        self.pull_data(rewind, |entry, package_contents| {
-            let dirs_options = package_contents
-                .options
-                .clone()
-                .unwrap_or_default()
-                .directories
-                .unwrap_or_default();
-            let Ok(path) = entry.path() else {
-                return false;
-            };
-            path.as_ref()
-                == AsRef::<Path>::as_ref(&dirs_options.chapters_add(format!("{id}/data.cbor")))
-        })
-        .map_err(|err| {
-            let api_core::Error::Io(io_err) = err else {
-                return err;
-            };
-            if io_err.kind() == io::ErrorKind::NotFound {
-                io::Error::new(
-                    io::ErrorKind::NotFound,
-                    format!("the chapter {id} is not found in the package"),
-                )
-                .into()
-            } else {
-                io_err.into()
-            }
-        })
+        let path = self.package_dirs_path(|dirs| dirs.chapters_add(format!("{id}/data.cbor")))?;
+        self.pull_data(rewind, &path)
+            .map_err(|err| not_found_in_package(err, format!("chapter {id}")))
     }
     pub fn any_pull(
         &mut self,
@@ -300,51 +350,7 @@ where
 impl<'a, R: BufRead + Seek> Push<Archive<'a, R>> for DirsOptions {
     type Error = api_core::Error;
     fn push(&mut self, mut data: Archive<'a, R>) -> Result<(), Self::Error> {
-        let pull = data.any_pull(true)?;
-        let mut cover_acc = HashMap::<String, CoverObject>::new();
-        let mut cover_image_acc = HashMap::<String, File>::new();
-        for entry in pull.flatten() {
-            match entry {
-                pull::any::PossibleEntryData::Manga(manga) => {
-                    self.push(*manga)?;
-                }
-                pull::any::PossibleEntryData::Chapter(chapter) => {
-                    self.push(*chapter)?;
-                }
-                pull::any::PossibleEntryData::Cover(cover) => {
-                    cover_acc.insert(cover.attributes.file_name.clone(), *cover);
-                }
-                pull::any::PossibleEntryData::CoverImage { filename, file } => {
-                    cover_image_acc.insert(filename, file);
-                }
-                pull::any::PossibleEntryData::ChapterImage {
-                    filename,
-                    file,
-                    chapter,
-                    mode,
-                } => {
-                    self.push(
-                        ChapterImagePushEntry::new(chapter, filename, BufReader::new(file))
-                            .mode(mode),
-                    )?;
-                }
-                pull::any::PossibleEntryData::Any { .. } => {}
-            }
-        }
-        let covers = cover_acc
-            .into_iter()
-            .fold(
-                HashMap::<String, (CoverObject, BufReader<File>)>::new(),
-                |mut acc, (filename, cover)| {
-                    if let Some(file) = cover_image_acc.remove(&filename) {
-                        acc.insert(filename, (cover, BufReader::new(file)));
-                    }
-                    acc
-                },
-            )
-            .into_values()
-            .collect::<Vec<_>>();
-        self.push(covers)?;
+        data.import(self, &ImportOptions::default())?;
         Ok(())
     }
 }
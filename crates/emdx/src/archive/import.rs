@@ -0,0 +1,747 @@
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    fs::File,
+    io::{BufRead, BufReader, Seek},
+};
+
+use api_core::{
+    data_pulls::Pull,
+    data_push::{
+        chapter::image::{ChapterImagePushEntry, Mode},
+        Push,
+    },
+    DirsOptions,
+};
+use mangadex_api_schema_rust::v5::{ChapterObject, CoverObject, MangaObject};
+use mangadex_api_types_rust::MangaDexDateTime;
+use uuid::Uuid;
+
+use super::{pull::any::PossibleEntryData, Archive};
+use crate::{PackageContents, Progress, ThisResult};
+
+/// What to do when an imported entry is already in the library
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ConflictPolicy {
+    /// Keep the library data
+    SkipExisting,
+    /// Replace the library data with the package data
+    #[default]
+    Overwrite,
+    /// Keep the data with the highest `attributes.version`
+    /// (or the latest `attributes.updated_at` if the versions are equal)
+    KeepNewer,
+    /// Push the package data with [`Push::verify_and_push`],
+    /// which merges the relationships with the library data
+    Merge,
+}
+
+/// An entry of the package
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ImportEntry {
+    Manga(Uuid),
+    Cover(Uuid),
+    Chapter(Uuid),
+    ChapterImage {
+        id: Uuid,
+        mode: Mode,
+        filename: String,
+    },
+}
+
+/// How a conflict has been resolved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConflictResolution {
+    KeptExisting,
+    Overwritten,
+    Merged,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ImportConflict {
+    pub entry: ImportEntry,
+    pub resolution: ConflictResolution,
+}
+
+/// The result of [`Archive::import`]
+#[derive(Debug, Clone, Default)]
+pub struct ImportReport {
+    /// The entries that wasn't in the library
+    pub imported: Vec<ImportEntry>,
+    /// The entries excluded by the selection
+    pub skipped: Vec<ImportEntry>,
+    /// The entries that was already in the library
+    pub conflicted: Vec<ImportConflict>,
+}
+
+/// Which part of the package to import and how to handle the conflicts.
+///
+/// If no manga or chapter is selected, the whole package is imported.
+#[derive(Debug, Clone, Default)]
+pub struct ImportOptions {
+    mangas: Vec<Uuid>,
+    chapters: Vec<Uuid>,
+    mode: Option<Mode>,
+    policy: ConflictPolicy,
+}
+
+#[derive(Debug, Default)]
+struct SelectedEntries {
+    mangas: HashSet<Uuid>,
+    covers: HashSet<Uuid>,
+    chapters: HashSet<Uuid>,
+}
+
+impl ImportOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Import these mangas with their covers and chapters
+    pub fn mangas(self, mangas: Vec<Uuid>) -> Self {
+        Self { mangas, ..self }
+    }
+    /// Import these chapters with their manga and its covers
+    pub fn chapters(self, chapters: Vec<Uuid>) -> Self {
+        Self { chapters, ..self }
+    }
+    /// Only import the chapter images of this mode
+    pub fn mode(self, mode: Option<Mode>) -> Self {
+        Self { mode, ..self }
+    }
+    pub fn policy(self, policy: ConflictPolicy) -> Self {
+        Self { policy, ..self }
+    }
+    fn select(&self, contents: &PackageContents) -> SelectedEntries {
+        let everything = self.mangas.is_empty() && self.chapters.is_empty();
+        let mut selected = SelectedEntries::default();
+        for (manga_id, manga) in &contents.data {
+            let whole_manga = everything || self.mangas.contains(manga_id);
+            let chapters = manga
+                .chapters
+                .keys()
+                .filter(|id| whole_manga || self.chapters.contains(id))
+                .copied()
+                .collect::<Vec<_>>();
+            if whole_manga || !chapters.is_empty() {
+                selected.mangas.insert(*manga_id);
+                selected.covers.extend(manga.covers.iter().copied());
+                selected.chapters.extend(chapters);
+            }
+        }
+        selected
+    }
+}
+
+pub(crate) fn is_newer(
+    (version, updated_at): (u32, Option<&MangaDexDateTime>),
+    (current_version, current_updated_at): (u32, Option<&MangaDexDateTime>),
+) -> bool {
+    match version.cmp(&current_version) {
+        Ordering::Equal => {
+            updated_at.map(|d| d.as_ref()) > current_updated_at.map(|d| d.as_ref())
+        }
+        ordering => ordering.is_gt(),
+    }
+}
+
+struct Importer<'a> {
+    dirs: &'a mut DirsOptions,
+    policy: ConflictPolicy,
+    report: ImportReport,
+}
+
+impl Importer<'_> {
+    /// Returns `None` if the entry isn't in the library.
+    fn resolve<F>(&self, exists: bool, package_is_newer: F) -> Option<ConflictResolution>
+    where
+        F: FnOnce() -> bool,
+    {
+        if !exists {
+            return None;
+        }
+        Some(match self.policy {
+            ConflictPolicy::SkipExisting => ConflictResolution::KeptExisting,
+            ConflictPolicy::Overwrite => ConflictResolution::Overwritten,
+            ConflictPolicy::KeepNewer if package_is_newer() => ConflictResolution::Overwritten,
+            ConflictPolicy::KeepNewer => ConflictResolution::KeptExisting,
+            ConflictPolicy::Merge => ConflictResolution::Merged,
+        })
+    }
+    fn write<P>(
+        &mut self,
+        entry: ImportEntry,
+        resolution: Option<ConflictResolution>,
+        data: P,
+    ) -> ThisResult<()>
+    where
+        DirsOptions: Push<P, Error = api_core::Error>,
+    {
+        let Some(resolution) = resolution else {
+            self.dirs.push(data)?;
+            self.report.imported.push(entry);
+            return Ok(());
+        };
+        match resolution {
+            ConflictResolution::KeptExisting => {}
+            ConflictResolution::Overwritten => self.dirs.push(data)?,
+            ConflictResolution::Merged => self.dirs.verify_and_push(data)?,
+        }
+        self.report
+            .conflicted
+            .push(ImportConflict { entry, resolution });
+        Ok(())
+    }
+    fn import_manga(&mut self, manga: MangaObject) -> ThisResult<()> {
+        let resolution = self.resolve(self.dirs.manga_file(manga.id).is_some(), || {
+            Pull::<MangaObject, Uuid>::pull(&*self.dirs, manga.id)
+                .ok()
+                .is_none_or(|current| {
+                    is_newer(
+                        (manga.attributes.version, manga.attributes.updated_at.as_ref()),
+                        (current.attributes.version, current.attributes.updated_at.as_ref()),
+                    )
+                })
+        });
+        self.write(ImportEntry::Manga(manga.id), resolution, manga)
+    }
+    fn import_chapter(&mut self, chapter: ChapterObject) -> ThisResult<()> {
+        let resolution = self.resolve(self.dirs.chapter_file(chapter.id).is_some(), || {
+            Pull::<ChapterObject, Uuid>::pull(&*self.dirs, chapter.id)
+                .ok()
+                .is_none_or(|current| {
+                    is_newer(
+                        (chapter.attributes.version, chapter.attributes.updated_at.as_ref()),
+                        (current.attributes.version, current.attributes.updated_at.as_ref()),
+                    )
+                })
+        });
+        self.write(ImportEntry::Chapter(chapter.id), resolution, chapter)
+    }
+    fn import_cover(&mut self, cover: CoverObject, image: File) -> ThisResult<()> {
+        let resolution = self.resolve(self.dirs.cover_file(cover.id).is_some(), || {
+            Pull::<CoverObject, Uuid>::pull(&*self.dirs, cover.id)
+                .ok()
+                .is_none_or(|current| {
+                    is_newer(
+                        (cover.attributes.version, cover.attributes.updated_at.as_ref()),
+                        (current.attributes.version, current.attributes.updated_at.as_ref()),
+                    )
+                })
+        });
+        self.write(
+            ImportEntry::Cover(cover.id),
+            resolution,
+            (cover, BufReader::new(image)),
+        )
+    }
+    /// Chapter images with the same filename are considered to be the same image,
+    /// so they are only replaced with [`ConflictPolicy::Overwrite`].
+    fn import_chapter_image(
+        &mut self,
+        id: Uuid,
+        mode: Mode,
+        filename: String,
+        image: File,
+    ) -> ThisResult<()> {
+        let exists = self
+            .dirs
+            .chapter_images_dir(id, mode)
+            .join(&filename)
+            .exists();
+        let resolution = self.resolve(exists, || false).map(|resolution| {
+            if resolution == ConflictResolution::Overwritten {
+                resolution
+            } else {
+                ConflictResolution::KeptExisting
+            }
+        });
+        let data =
+            ChapterImagePushEntry::new(id, filename.clone(), BufReader::new(image)).mode(mode);
+        self.write(
+            ImportEntry::ChapterImage { id, mode, filename },
+            resolution,
+            data,
+        )
+    }
+}
+
+impl<R> Archive<'_, R>
+where
+    R: BufRead + Seek,
+{
+    /// Import the selected entries of the package into `dirs`.
+    ///
+    /// A delta package is refused if the library lacks its base package.
+    pub fn import(
+        &mut self,
+        dirs: &mut DirsOptions,
+        options: &ImportOptions,
+    ) -> ThisResult<ImportReport> {
+        self.import_with_progress(dirs, options, |_| {})
+    }
+    /// [`Self::import`], calling `progress` after every package entry.
+    ///
+    /// The total is only known if the package has a manifest.
+    pub fn import_with_progress<F>(
+        &mut self,
+        dirs: &mut DirsOptions,
+        options: &ImportOptions,
+        mut progress: F,
+    ) -> ThisResult<ImportReport>
+    where
+        F: FnMut(Progress),
+    {
+        let contents = self.get_package_contents()?;
+        // every entry with `contents.cbor`
+        let total = contents
+            .manifest
+            .as_ref()
+            .map(|manifest| manifest.entries.len() + 1);
+        if let Some(delta) = contents.delta.as_ref() {
+            delta.check_library(dirs)?;
+        }
+        let selected = options.select(contents);
+        let mut importer = Importer {
+            dirs,
+            policy: options.policy,
+            report: Default::default(),
+        };
+        let mut covers = HashMap::<String, CoverObject>::new();
+        let mut cover_images = HashMap::<String, File>::new();
+        for (done, entry) in (1..).zip(self.any_pull(true)?) {
+            match entry? {
+                PossibleEntryData::Manga(manga) => {
+                    if selected.mangas.contains(&manga.id) {
+                        importer.import_manga(*manga)?;
+                    } else {
+                        importer.report.skipped.push(ImportEntry::Manga(manga.id));
+                    }
+                }
+                PossibleEntryData::Chapter(chapter) => {
+                    if selected.chapters.contains(&chapter.id) {
+                        importer.import_chapter(*chapter)?;
+                    } else {
+                        importer.report.skipped.push(ImportEntry::Chapter(chapter.id));
+                    }
+                }
+                PossibleEntryData::Cover(cover) => {
+                    if selected.covers.contains(&cover.id) {
+                        covers.insert(cover.attributes.file_name.clone(), *cover);
+                    } else {
+                        importer.report.skipped.push(ImportEntry::Cover(cover.id));
+                    }
+                }
+                PossibleEntryData::CoverImage { filename, file } => {
+                    cover_images.insert(filename, file);
+                }
+                PossibleEntryData::ChapterImage {
+                    filename,
+                    file,
+                    chapter,
+                    mode,
+                } => {
+                    if selected.chapters.contains(&chapter)
+                        && options.mode.is_none_or(|selected_mode| selected_mode == mode)
+                    {
+                        importer.import_chapter_image(chapter, mode, filename, file)?;
+                    } else {
+                        importer.report.skipped.push(ImportEntry::ChapterImage {
+                            id: chapter,
+                            mode,
+                            filename,
+                        });
+                    }
+                }
+                PossibleEntryData::Any { .. } => {}
+            }
+            progress(Progress { done, total });
+        }
+        for (filename, cover) in covers {
+            match cover_images.remove(&filename) {
+                Some(image) => importer.import_cover(cover, image)?,
+                // a cover without its image can't be pushed
+                None => importer.report.skipped.push(ImportEntry::Cover(cover.id)),
+            }
+        }
+        Ok(importer.report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, fs, io::Cursor, path::Path};
+
+    use api_core::{
+        data_pulls::Pull,
+        data_push::{
+            chapter::image::{ChapterImagePushEntry, Mode},
+            Push,
+        },
+        DirsOptions,
+    };
+    use mangadex_api_schema_rust::v5::{
+        AuthorAttributes, ChapterObject, CoverObject, MangaAttributes, MangaObject,
+        RelatedAttributes, Relationship,
+    };
+    use mangadex_api_types_rust::{MangaDexDateTime, RelationshipType};
+    use serde::{
+        de::{value::StrDeserializer, IntoDeserializer},
+        Deserialize,
+    };
+    use tempfile::TempDir;
+    use uuid::Uuid;
+
+    use super::{
+        ConflictPolicy, ConflictResolution, ImportConflict, ImportEntry, ImportOptions,
+        ImportReport,
+    };
+    use crate::{
+        test_utils::chapter_contents, Archive, PChapterObject, PMangaObject, PackageBuilder,
+        PackageContents,
+    };
+
+    fn relationship(id: Uuid, type_: RelationshipType) -> Relationship {
+        let mut relationship = Relationship::default();
+        relationship.id = id;
+        relationship.type_ = type_;
+        relationship
+    }
+
+    fn date(date: &str) -> MangaDexDateTime {
+        let deserializer: StrDeserializer<'_, serde::de::value::Error> = date.into_deserializer();
+        MangaDexDateTime::deserialize(deserializer).unwrap()
+    }
+
+    fn init_library(path: &Path) -> DirsOptions {
+        let dirs = DirsOptions::new_from_data_dir(path);
+        dirs.init_dirs().unwrap();
+        dirs
+    }
+
+    fn chapter_image(dirs: &DirsOptions, chapter: Uuid, filename: &str) -> Vec<u8> {
+        fs::read(dirs.chapter_images_dir(chapter, Mode::Data).join(filename)).unwrap()
+    }
+
+    /// A library with a copy of every entry of [`Self::package`],
+    /// except the second image of the chapter and the other chapter.
+    ///
+    /// The package has a newer manga (by version), an older chapter,
+    /// a newer cover (by `updated_at`, with the same version)
+    /// and another first image of the chapter.
+    struct Fixture {
+        _dir: TempDir,
+        dirs: DirsOptions,
+        package: Vec<u8>,
+        manga: Uuid,
+        author: Uuid,
+        cover: Uuid,
+        chapter: Uuid,
+        other_chapter: Uuid,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let (manga_id, author, cover_id) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+            let (chapter_id, other_chapter_id) = (Uuid::new_v4(), Uuid::new_v4());
+
+            let mut manga = MangaObject::default();
+            manga.id = manga_id;
+            manga.attributes.version = 1;
+            manga
+                .relationships
+                .push(relationship(cover_id, RelationshipType::CoverArt));
+            let mut manga_relationship = relationship(manga_id, RelationshipType::Manga);
+            manga_relationship.attributes =
+                Some(RelatedAttributes::Manga(MangaAttributes::default()));
+            let mut cover = CoverObject::default();
+            cover.id = cover_id;
+            cover.attributes.file_name = "cover.png".into();
+            cover.attributes.version = 1;
+            cover.attributes.updated_at = Some(date("2024-01-01T00:00:00+00:00"));
+            cover.relationships.push(manga_relationship.clone());
+            let mut chapter = ChapterObject::default();
+            chapter.id = chapter_id;
+            chapter.attributes.version = 2;
+            chapter.relationships.push(manga_relationship.clone());
+
+            let dir = TempDir::new().unwrap();
+            let mut dirs = init_library(dir.path());
+            dirs.push(manga.clone()).unwrap();
+            dirs.push((cover.clone(), &b"library cover"[..])).unwrap();
+            dirs.push(chapter.clone()).unwrap();
+            dirs.push(ChapterImagePushEntry::new(
+                chapter_id,
+                "1.png".into(),
+                &b"library page 1"[..],
+            ))
+            .unwrap();
+
+            manga.attributes.version = 2;
+            // only the expanded relationships are merged
+            let mut author_relationship = relationship(author, RelationshipType::Author);
+            author_relationship.attributes =
+                Some(RelatedAttributes::Author(AuthorAttributes::default()));
+            manga.relationships.push(author_relationship);
+            cover.attributes.updated_at = Some(date("2024-02-01T00:00:00+00:00"));
+            chapter.attributes.version = 1;
+            let mut other_chapter = ChapterObject::default();
+            other_chapter.id = other_chapter_id;
+            other_chapter.relationships.push(manga_relationship);
+
+            let package_dir = TempDir::new().unwrap();
+            let mut package_dirs = init_library(package_dir.path());
+            package_dirs.push(manga).unwrap();
+            package_dirs.push((cover, &b"package cover"[..])).unwrap();
+            package_dirs.push(chapter).unwrap();
+            package_dirs.push(other_chapter).unwrap();
+            let images = [
+                (chapter_id, "1.png", &b"package page 1"[..]),
+                (chapter_id, "2.png", &b"package page 2"[..]),
+                (other_chapter_id, "1.png", &b"other page 1"[..]),
+            ];
+            for (chapter, filename, data) in images {
+                package_dirs
+                    .push(ChapterImagePushEntry::new(chapter, filename.into(), data))
+                    .unwrap();
+            }
+            let mut contents = chapter_contents(
+                manga_id,
+                chapter_id,
+                PChapterObject {
+                    data: vec!["1.png".into(), "2.png".into()],
+                    data_saver: Vec::new(),
+                },
+            );
+            let manga_data: &mut PMangaObject = contents.data.get_mut(&manga_id).unwrap();
+            manga_data.covers.push(cover_id);
+            manga_data.chapters.insert(
+                other_chapter_id,
+                PChapterObject {
+                    data: vec!["1.png".into()],
+                    data_saver: Vec::new(),
+                },
+            );
+            let mut package = Vec::new();
+            PackageBuilder::new(package_dirs)
+                .set_content(contents)
+                .build(&mut package)
+                .unwrap();
+
+            Self {
+                _dir: dir,
+                dirs,
+                package,
+                manga: manga_id,
+                author,
+                cover: cover_id,
+                chapter: chapter_id,
+                other_chapter: other_chapter_id,
+            }
+        }
+        /// Import the chapter of the package with `policy`.
+        fn import(&mut self, policy: ConflictPolicy) -> ImportReport {
+            let options = ImportOptions::new()
+                .chapters(vec![self.chapter])
+                .policy(policy);
+            Archive::from_reader(Cursor::new(self.package.clone()))
+                .unwrap()
+                .import(&mut self.dirs, &options)
+                .unwrap()
+        }
+        fn image(&self, filename: &str) -> ImportEntry {
+            ImportEntry::ChapterImage {
+                id: self.chapter,
+                mode: Mode::Data,
+                filename: filename.into(),
+            }
+        }
+        /// The conflicts of the manga, cover, chapter and first image of the chapter.
+        fn conflicts(
+            &self,
+            manga: ConflictResolution,
+            cover: ConflictResolution,
+            chapter: ConflictResolution,
+        ) -> HashSet<ImportConflict> {
+            HashSet::from([
+                ImportConflict {
+                    entry: ImportEntry::Manga(self.manga),
+                    resolution: manga,
+                },
+                ImportConflict {
+                    entry: ImportEntry::Cover(self.cover),
+                    resolution: cover,
+                },
+                ImportConflict {
+                    entry: ImportEntry::Chapter(self.chapter),
+                    resolution: chapter,
+                },
+                ImportConflict {
+                    entry: self.image("1.png"),
+                    resolution: ConflictResolution::KeptExisting,
+                },
+            ])
+        }
+        fn manga(&self) -> MangaObject {
+            Pull::<MangaObject, Uuid>::pull(&self.dirs, self.manga).unwrap()
+        }
+        fn chapter(&self) -> ChapterObject {
+            Pull::<ChapterObject, Uuid>::pull(&self.dirs, self.chapter).unwrap()
+        }
+        fn cover_image(&self) -> Vec<u8> {
+            fs::read(self.dirs.cover_images_add("cover.png")).unwrap()
+        }
+    }
+
+    /// Only the second image of the chapter is new, the other chapter isn't selected.
+    fn assert_imported_and_skipped(fixture: &Fixture, report: &ImportReport) {
+        assert_eq!(report.imported, [fixture.image("2.png")]);
+        assert_eq!(
+            report.skipped.iter().cloned().collect::<HashSet<_>>(),
+            HashSet::from([
+                ImportEntry::Chapter(fixture.other_chapter),
+                ImportEntry::ChapterImage {
+                    id: fixture.other_chapter,
+                    mode: Mode::Data,
+                    filename: "1.png".into(),
+                },
+            ])
+        );
+        assert_eq!(
+            chapter_image(&fixture.dirs, fixture.chapter, "2.png"),
+            b"package page 2"
+        );
+        assert!(fixture.dirs.chapter_file(fixture.other_chapter).is_none());
+    }
+
+    #[test]
+    fn skip_existing_keeps_the_library_data() {
+        let mut fixture = Fixture::new();
+        let report = fixture.import(ConflictPolicy::SkipExisting);
+
+        assert_imported_and_skipped(&fixture, &report);
+        assert_eq!(
+            report.conflicted.into_iter().collect::<HashSet<_>>(),
+            fixture.conflicts(
+                ConflictResolution::KeptExisting,
+                ConflictResolution::KeptExisting,
+                ConflictResolution::KeptExisting,
+            )
+        );
+        assert_eq!(fixture.manga().attributes.version, 1);
+        assert_eq!(fixture.chapter().attributes.version, 2);
+        assert_eq!(fixture.cover_image(), b"library cover");
+        assert_eq!(
+            chapter_image(&fixture.dirs, fixture.chapter, "1.png"),
+            b"library page 1"
+        );
+    }
+
+    #[test]
+    fn keep_newer_compares_the_versions_then_the_update_dates() {
+        let mut fixture = Fixture::new();
+        let report = fixture.import(ConflictPolicy::KeepNewer);
+
+        assert_imported_and_skipped(&fixture, &report);
+        assert_eq!(
+            report.conflicted.into_iter().collect::<HashSet<_>>(),
+            fixture.conflicts(
+                ConflictResolution::Overwritten,
+                ConflictResolution::Overwritten,
+                ConflictResolution::KeptExisting,
+            )
+        );
+        assert_eq!(fixture.manga().attributes.version, 2);
+        assert_eq!(fixture.chapter().attributes.version, 2);
+        // same version, but updated after the library cover
+        assert_eq!(fixture.cover_image(), b"package cover");
+        assert_eq!(
+            chapter_image(&fixture.dirs, fixture.chapter, "1.png"),
+            b"library page 1"
+        );
+    }
+
+    #[test]
+    fn merge_fills_the_missing_relationships_of_the_library_data() {
+        let mut fixture = Fixture::new();
+        assert!(fixture
+            .manga()
+            .find_first_relationships(RelationshipType::Author)
+            .is_none());
+        let report = fixture.import(ConflictPolicy::Merge);
+
+        assert_imported_and_skipped(&fixture, &report);
+        assert_eq!(
+            report.conflicted.into_iter().collect::<HashSet<_>>(),
+            fixture.conflicts(
+                ConflictResolution::Merged,
+                ConflictResolution::Merged,
+                ConflictResolution::Merged,
+            )
+        );
+        let manga = fixture.manga();
+        assert_eq!(manga.attributes.version, 1);
+        assert_eq!(
+            manga
+                .find_first_relationships(RelationshipType::Author)
+                .map(|author| author.id),
+            Some(fixture.author)
+        );
+        assert_eq!(fixture.chapter().attributes.version, 2);
+        assert_eq!(
+            chapter_image(&fixture.dirs, fixture.chapter, "1.png"),
+            b"library page 1"
+        );
+    }
+
+    #[test]
+    fn chapter_images_with_the_same_filename_are_only_overwritten() {
+        let mut fixture = Fixture::new();
+        let report = fixture.import(ConflictPolicy::Overwrite);
+
+        assert_imported_and_skipped(&fixture, &report);
+        assert!(report.conflicted.contains(&ImportConflict {
+            entry: fixture.image("1.png"),
+            resolution: ConflictResolution::Overwritten,
+        }));
+        assert_eq!(
+            chapter_image(&fixture.dirs, fixture.chapter, "1.png"),
+            b"package page 1"
+        );
+    }
+
+    #[test]
+    fn select_chapters_with_their_manga() {
+        let (manga, other_manga) = (Uuid::new_v4(), Uuid::new_v4());
+        let (cover, chapter, other_chapter) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let mut contents = PackageContents::default();
+        contents.data.insert(
+            manga,
+            PMangaObject {
+                covers: vec![cover],
+                chapters: [
+                    (chapter, PChapterObject::default()),
+                    (other_chapter, PChapterObject::default()),
+                ]
+                .into(),
+            },
+        );
+        contents.data.insert(other_manga, PMangaObject::default());
+
+        let everything = ImportOptions::new().select(&contents);
+        assert_eq!(everything.mangas.len(), 2);
+        assert_eq!(everything.chapters.len(), 2);
+
+        let selected = ImportOptions::new()
+            .chapters(vec![chapter])
+            .select(&contents);
+        assert_eq!(selected.mangas, HashSet::from([manga]));
+        assert_eq!(selected.covers, HashSet::from([cover]));
+        assert_eq!(selected.chapters, HashSet::from([chapter]));
+
+        let selected = ImportOptions::new()
+            .mangas(vec![other_manga])
+            .select(&contents);
+        assert_eq!(selected.mangas, HashSet::from([other_manga]));
+        assert!(selected.chapters.is_empty());
+    }
+}
@@ -0,0 +1,236 @@
+use std::{
+    io::{self, BufRead, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    vec::IntoIter,
+};
+
+use api_core::data_push::chapter::image::Mode;
+use uuid::Uuid;
+
+use super::{
+    entry_not_found_error, new_tar_archive, tar_archive_not_found_error, Archive, DecoderInner,
+};
+use crate::{contents::options::PackageContentsOptions, ThisResult};
+
+/// A chapter page read from a package.
+///
+/// The image is decoded while it is read.
+pub struct ChapterPage<'p> {
+    pub filename: String,
+    reader: Box<dyn Read + 'p>,
+}
+
+impl Read for ChapterPage<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+/// The pages of a chapter, in the [`PChapterObject`](crate::PChapterObject) order.
+///
+/// Made by [`Archive::read_chapter`].
+/// Each page borrows the package reader, so they are read one at a time:
+///
+/// ```no_run
+/// use std::{fs::File, io::Read};
+///
+/// use api_core::data_push::chapter::image::Mode;
+/// use emdx::Archive;
+/// use uuid::Uuid;
+///
+/// fn main() -> anyhow::Result<()> {
+///     let mut archive = Archive::from_reader(File::open("your_package.emdx")?)?;
+///     let mut pages = archive.read_chapter(Uuid::new_v4(), Mode::Data)?;
+///     while let Some(page) = pages.next_page() {
+///         let mut page = page?;
+///         let mut image = Vec::new();
+///         page.read_to_end(&mut image)?;
+///         println!("{} is {} bytes", page.filename, image.len());
+///     }
+///     pages.finish()?;
+///     Ok(())
+/// }
+/// ```
+pub struct ChapterPages<'p, 'a, R>
+where
+    R: Seek + BufRead,
+{
+    archive: &'p mut Archive<'a, R>,
+    decoder: Option<DecoderInner<'a, R>>,
+    pages: IntoIter<(String, PathBuf)>,
+    options: PackageContentsOptions,
+}
+
+impl<R> ChapterPages<'_, '_, R>
+where
+    R: Seek + BufRead,
+{
+    /// Open the next page.
+    ///
+    /// Jumps straight to the page if the package has a table of contents,
+    /// scans the package from the start otherwise.
+    pub fn next_page(&mut self) -> Option<ThisResult<ChapterPage<'_>>> {
+        let (filename, path) = self.pages.next()?;
+        Some(self.open_page(filename, &path))
+    }
+    fn open_page(&mut self, filename: String, path: &Path) -> ThisResult<ChapterPage<'_>> {
+        let decoder = self
+            .decoder
+            .as_mut()
+            .ok_or_else(tar_archive_not_found_error)?;
+        let size = if let Some(toc_entry) = self.archive.get_toc_entry(path) {
+            decoder
+                .reader_mut()
+                .seek(SeekFrom::Start(toc_entry.offset))?;
+            decoder.reset()?;
+            decoder.set_single_frame();
+            let mut frame = tar::Archive::new(&mut *decoder);
+            let entry = frame
+                .entries()?
+                .next()
+                .ok_or_else(entry_not_found_error)??;
+            entry.size()
+        } else {
+            decoder.reader_mut().rewind()?;
+            decoder.reset()?;
+            let mut archive = new_tar_archive(&mut *decoder);
+            let entry = archive
+                .entries()?
+                .flatten()
+                .find(|entry| entry.path().is_ok_and(|entry_path| entry_path == path))
+                .ok_or_else(entry_not_found_error)?;
+            entry.size()
+        };
+        // the tar headers are consumed, the decoder now stands at the entry data
+        let data = decoder.take(size);
+        let reader: Box<dyn Read + '_> = if self.options.zstd_compressed_images {
+            Box::new(self.options.entry_decoder(data)?)
+        } else {
+            Box::new(data)
+        };
+        Ok(ChapterPage { filename, reader })
+    }
+    /// Rewind the package reader and give it back to the archive.
+    ///
+    /// Also done when the pages are dropped, but a failed rewind can only be reported here:
+    /// the archive can't be read anymore after it.
+    pub fn finish(mut self) -> ThisResult<()> {
+        self.give_back()
+    }
+    fn give_back(&mut self) -> ThisResult<()> {
+        if let Some(mut decoder) = self.decoder.take() {
+            decoder.reader_mut().rewind()?;
+            decoder.reset()?;
+            self.archive.tar_archive.replace(new_tar_archive(decoder));
+        }
+        Ok(())
+    }
+}
+
+impl<R> Drop for ChapterPages<'_, '_, R>
+where
+    R: Seek + BufRead,
+{
+    fn drop(&mut self) {
+        // the error is only reported by [`ChapterPages::finish`],
+        // a failed rewind leaves the archive without its reader, so its next reads fail
+        let _ = self.give_back();
+    }
+}
+
+impl<'a, R> Archive<'a, R>
+where
+    R: Seek + BufRead,
+{
+    /// Read the `mode` images of the chapter `id`.
+    pub fn read_chapter(&mut self, id: Uuid, mode: Mode) -> ThisResult<ChapterPages<'_, 'a, R>> {
+        let contents = self.get_package_contents()?;
+        let images = contents
+            .data
+            .values()
+            .find_map(|manga| manga.chapters.get(&id))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("the chapter {id} is not found in the package"),
+                )
+            })?;
+        let options = contents.get_options().into_owned();
+        let images_dir = options.get_dirs().chapter_images_dir(id, mode);
+        let filenames = match mode {
+            Mode::Data => &images.data,
+            Mode::DataSaver => &images.data_saver,
+        };
+        let pages = filenames
+            .iter()
+            .map(|filename| (filename.clone(), images_dir.join(filename)))
+            .collect::<Vec<_>>();
+        let decoder = self
+            .tar_archive
+            .take()
+            .ok_or_else(tar_archive_not_found_error)?
+            .into_inner();
+        Ok(ChapterPages {
+            archive: self,
+            decoder: Some(decoder),
+            pages: pages.into_iter(),
+            options,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read};
+
+    use api_core::{data_push::chapter::image::Mode, DirsOptions};
+    use uuid::Uuid;
+
+    use crate::{
+        test_utils::{chapter_contents, linear, package_with},
+        Archive, PChapterObject,
+    };
+
+    fn read_pages(package: Vec<u8>, chapter: Uuid) -> Vec<(String, String)> {
+        let mut archive = Archive::from_reader(Cursor::new(package)).unwrap();
+        let mut pages = archive.read_chapter(chapter, Mode::Data).unwrap();
+        let mut read = Vec::new();
+        while let Some(page) = pages.next_page() {
+            let mut page = page.unwrap();
+            let mut text = String::new();
+            page.read_to_string(&mut text).unwrap();
+            read.push((page.filename.clone(), text));
+        }
+        pages.finish().unwrap();
+        read
+    }
+
+    #[test]
+    fn pages_follow_the_chapter_order() {
+        let chapter = Uuid::new_v4();
+        let contents = chapter_contents(
+            Uuid::new_v4(),
+            chapter,
+            PChapterObject {
+                data: vec!["1.png".into(), "2.png".into()],
+                data_saver: Vec::new(),
+            },
+        );
+        let images_dir = DirsOptions::default().chapter_images_dir(chapter, Mode::Data);
+        let package = package_with(
+            &contents,
+            [
+                (images_dir.join("2.png"), "second page"),
+                (images_dir.join("1.png"), "first page"),
+            ],
+        );
+        let linear = linear(&package);
+
+        let expected = vec![
+            ("1.png".to_string(), "first page".to_string()),
+            ("2.png".to_string(), "second page".to_string()),
+        ];
+        assert_eq!(read_pages(package, chapter), expected);
+        assert_eq!(read_pages(linear, chapter), expected);
+    }
+}
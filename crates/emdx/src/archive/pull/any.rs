@@ -12,7 +12,6 @@ use serde::de::DeserializeOwned;
 use tar::{Entries, Entry};
 use tempfile::tempfile;
 use uuid::Uuid;
-use zstd::Decoder;
 
 use crate::{contents::options::PackageContentsOptions, PackageContents, ThisResult};
 
@@ -220,7 +219,7 @@ where
     {
         let options = self.get_package_contents_options();
         if options.zstd_compressed_metadata {
-            Ok(ciborium::from_reader(Decoder::new(entry)?)?)
+            Ok(ciborium::from_reader(options.entry_decoder(entry)?)?)
         } else {
             Ok(ciborium::from_reader(entry)?)
         }
@@ -231,7 +230,7 @@ where
             let mut temp_buf = BufWriter::new(&mut temp);
             let options = self.get_package_contents_options();
             if options.zstd_compressed_images {
-                io::copy(&mut Decoder::new(&mut entry)?, &mut temp_buf)?;
+                io::copy(&mut options.entry_decoder(&mut entry)?, &mut temp_buf)?;
             } else {
                 io::copy(&mut entry, &mut temp_buf)?;
             }
@@ -5,7 +5,6 @@ use std::{
 
 use mangadex_api_schema_rust::v5::ChapterObject;
 use tar::Entry;
-use zstd::Decoder;
 
 use crate::{PackageContents, ThisResult};
 
@@ -40,7 +39,7 @@ where
             })
         {
             if options.zstd_compressed_metadata {
-                Ok(ciborium::from_reader(Decoder::new(entry)?)?)
+                Ok(ciborium::from_reader(options.entry_decoder(entry)?)?)
             } else {
                 Ok(ciborium::from_reader(entry)?)
             }
@@ -0,0 +1,115 @@
+use std::{
+    collections::HashSet,
+    io::{self, BufRead, Seek},
+    path::{Path, PathBuf},
+};
+
+use super::Archive;
+use crate::{constants::CONTENTS_FILENAME, contents::manifest::ManifestEntry, ThisResult};
+
+/// An entry that doesn't match its manifest record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorruptedEntry {
+    pub path: PathBuf,
+    pub expected: ManifestEntry,
+    pub found: ManifestEntry,
+}
+
+/// The result of [`Archive::verify`].
+#[derive(Debug, Clone, Default)]
+pub struct VerifyReport {
+    /// The entries listed in the manifest but not in the package
+    pub missing: Vec<PathBuf>,
+    /// The entries in the package but not listed in the manifest
+    pub extra: Vec<PathBuf>,
+    pub corrupted: Vec<CorruptedEntry>,
+}
+
+impl VerifyReport {
+    pub fn is_valid(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty() && self.corrupted.is_empty()
+    }
+}
+
+fn manifest_not_found_error() -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, "this package has no manifest")
+}
+
+impl<R> Archive<'_, R>
+where
+    R: BufRead + Seek,
+{
+    /// Stream the whole package and check every entry against the package manifest.
+    pub fn verify(&mut self) -> ThisResult<VerifyReport> {
+        let manifest = self
+            .get_package_contents()?
+            .manifest
+            .clone()
+            .ok_or_else(manifest_not_found_error)?;
+        let mut report = VerifyReport::default();
+        let mut seen = HashSet::new();
+        for entry in self.get_archive(true)?.entries()? {
+            let mut entry = entry?;
+            let path = entry.path()?.into_owned();
+            if path == AsRef::<Path>::as_ref(CONTENTS_FILENAME) {
+                continue;
+            }
+            let found = ManifestEntry::from_reader(&mut entry)?;
+            match manifest.get(&path) {
+                Some(expected) if *expected != found => report.corrupted.push(CorruptedEntry {
+                    path: path.clone(),
+                    expected: *expected,
+                    found,
+                }),
+                Some(_) => {}
+                None => report.extra.push(path.clone()),
+            }
+            seen.insert(path);
+        }
+        report.missing = manifest
+            .entries
+            .into_keys()
+            .filter(|path| !seen.contains(path))
+            .collect();
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Cursor, path::PathBuf};
+
+    use crate::{
+        contents::manifest::{ManifestEntry, PackageManifest},
+        test_utils::{linear, package_with},
+        Archive, PackageContents,
+    };
+
+    #[test]
+    fn verify_reports_missing_extra_and_corrupted_entries() {
+        let mut manifest = PackageManifest::new();
+        let entry = |data: &[u8]| ManifestEntry::from_reader(data).unwrap();
+        manifest.insert("valid.cbor", entry(b"valid"));
+        manifest.insert("corrupted.cbor", entry(b"the original data"));
+        manifest.insert("missing.cbor", entry(b"missing"));
+        let contents = PackageContents {
+            manifest: Some(manifest),
+            ..Default::default()
+        };
+        let entries: [(&str, &[u8]); 3] = [
+            ("valid.cbor", b"valid"),
+            ("corrupted.cbor", b"some other data"),
+            ("extra.cbor", b"extra"),
+        ];
+        let package = linear(&package_with(&contents, entries));
+
+        let mut archive = Archive::from_reader(Cursor::new(package)).unwrap();
+        let report = archive.verify().unwrap();
+        assert!(!report.is_valid());
+        assert_eq!(report.missing, vec![PathBuf::from("missing.cbor")]);
+        assert_eq!(report.extra, vec![PathBuf::from("extra.cbor")]);
+        assert_eq!(report.corrupted.len(), 1);
+        assert_eq!(report.corrupted[0].path, PathBuf::from("corrupted.cbor"));
+        assert_eq!(report.corrupted[0].found, entry(b"some other data"));
+    }
+}
@@ -0,0 +1,172 @@
+use std::{
+    fs::File,
+    io::{self, BufReader},
+    path::Path,
+};
+
+use zstd::stream::raw::Decoder;
+
+use super::{new_tar_archive, Archive, Reader};
+use crate::{
+    toc::{TableOfContents, TocEntry},
+    volume::{find_volumes, MultiPartReader},
+    PackageContents, ThisResult,
+};
+
+/// Add the entries of a part to the contents of the previous ones.
+///
+/// The images of a chapter cut across parts are appended in the parts order.
+fn merge_part_contents(contents: &mut PackageContents, mut part: PackageContents) {
+    if let (Some(manifest), Some(part_manifest)) =
+        (contents.manifest.as_mut(), part.manifest.take())
+    {
+        manifest.entries.extend(part_manifest.entries);
+    }
+    contents.merge(part);
+}
+
+fn part_error(path: &Path, index: u32) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("{} is not the part {index} of the package", path.display()),
+    )
+}
+
+impl Archive<'static, BufReader<MultiPartReader>> {
+    /// Open the parts of a package, in order, as one package.
+    ///
+    /// The part contents and tables of contents are merged,
+    /// so the package can be pulled like a single file one.
+    /// A package that isn't split is accepted as a single part.
+    pub fn from_parts<I, P>(parts: I) -> ThisResult<Self>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let mut files = Vec::new();
+        let mut toc = TableOfContents::default();
+        let mut contents: Option<PackageContents> = None;
+        let mut start = 0;
+        let mut last = false;
+        for (index, path) in (1..).zip(parts) {
+            let path = path.as_ref();
+            let part = Archive::from_reader(File::open(path)?)?;
+            let part_contents = part.get_package_contents()?.clone();
+            last = match part_contents.volume {
+                Some(volume) if volume.index == index => volume.last,
+                None if index == 1 => true,
+                _ => return Err(part_error(path, index).into()),
+            };
+            if let Some(part_toc) = part.get_table_of_contents() {
+                for (entry_path, entry) in &part_toc.entries {
+                    let entry = TocEntry {
+                        offset: entry.offset + start,
+                        ..*entry
+                    };
+                    toc.insert(entry_path, entry);
+                }
+            }
+            match contents.as_mut() {
+                Some(contents) => merge_part_contents(contents, part_contents),
+                None => contents = Some(part_contents),
+            }
+            let file = File::open(path)?;
+            start += file.metadata()?.len();
+            files.push(file);
+        }
+        let mut contents = contents.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "no package part is given")
+        })?;
+        if !last {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "the last part of the package is missing",
+            )
+            .into());
+        }
+        contents.volume = None;
+        let reader = BufReader::with_capacity(
+            zstd::zstd_safe::CCtx::in_size(),
+            MultiPartReader::new(files)?,
+        );
+        Ok(Archive {
+            contents: Some(contents),
+            toc: (!toc.is_empty()).then_some(toc),
+            tar_archive: Some(new_tar_archive(Reader::new(reader, Decoder::new()?))),
+        })
+    }
+    /// Open every `name.part{index}.tar.zstd` part as one package.
+    pub fn open_volumes<P: AsRef<Path>>(name: P) -> ThisResult<Self> {
+        Self::from_parts(find_volumes(name)?)
+    }
+    /// Open the package file at `path`,
+    /// or every part of a split package named `path` if there is no file there.
+    pub fn open<P: AsRef<Path>>(path: P) -> ThisResult<Self> {
+        let path = path.as_ref();
+        if path.exists() {
+            Self::from_parts([path])
+        } else {
+            Self::open_volumes(path)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+
+    use api_core::{data_push::chapter::image::Mode, DirsOptions};
+    use tempfile::NamedTempFile;
+    use uuid::Uuid;
+
+    use crate::{
+        test_utils::{chapter_contents, package_with},
+        volume::PackageVolume,
+        Archive, PChapterObject,
+    };
+
+    /// A part holding the `pages` of `chapter`.
+    fn part(manga: Uuid, chapter: Uuid, pages: &[&str], volume: PackageVolume) -> NamedTempFile {
+        let mut contents = chapter_contents(
+            manga,
+            chapter,
+            PChapterObject {
+                data: pages.iter().map(|page| page.to_string()).collect(),
+                data_saver: Vec::new(),
+            },
+        );
+        contents.volume = Some(volume);
+        let images_dir = DirsOptions::default().chapter_images_dir(chapter, Mode::Data);
+        let package = package_with(
+            &contents,
+            pages.iter().map(|page| (images_dir.join(page), page)),
+        );
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(&package).unwrap();
+        file
+    }
+
+    #[test]
+    fn a_chapter_cut_across_parts_is_read_back_whole() {
+        let (manga, chapter) = (Uuid::new_v4(), Uuid::new_v4());
+        let volume = |index, last| PackageVolume { index, last };
+        let first = part(manga, chapter, &["1.png", "2.png"], volume(1, false));
+        let second = part(manga, chapter, &["3.png"], volume(2, true));
+
+        let mut archive = Archive::from_parts([first.path(), second.path()]).unwrap();
+        let images = &archive.get_package_contents().unwrap().data[&manga].chapters[&chapter];
+        assert_eq!(images.data, ["1.png", "2.png", "3.png"]);
+
+        let mut pages = archive.read_chapter(chapter, Mode::Data).unwrap();
+        let mut read = Vec::new();
+        while let Some(page) = pages.next_page() {
+            let mut text = String::new();
+            page.unwrap().read_to_string(&mut text).unwrap();
+            read.push(text);
+        }
+        assert_eq!(read, ["1.png", "2.png", "3.png"]);
+
+        assert!(Archive::from_parts([first.path()]).is_err());
+        assert!(Archive::from_parts([second.path(), first.path()]).is_err());
+    }
+}
@@ -1,9 +1,17 @@
+mod image_profile;
 mod inner;
+mod pool;
+mod reencode;
+mod stream;
+
+pub use image_profile::{ImageProfile, ImageTargetFormat};
+pub use reencode::Reencoder;
+pub use stream::{StreamBuilder, StreamWriter};
 
 use std::{
     io::{self, Write},
+    num::NonZeroUsize,
     ops::Deref,
-    path::PathBuf,
 };
 
 use api_core::{
@@ -14,22 +22,28 @@ use api_core::{
     data_push::chapter::image::Mode as ChapterImagesMode,
     DirsOptions,
 };
+use image::ImageFormat;
 use inner::BuilderInner;
 use mangadex_api_schema_rust::v5::{ChapterObject, CoverObject, MangaObject};
 use mangadex_api_types_rust::RelationshipType;
 
+use serde::Serialize;
 use tar::HeaderMode;
 use uuid::Uuid;
 
-use crate::{PMangaObject, PackageContents, ThisResult};
+use crate::{contents::options::ZstdDictionary, PMangaObject, PackageContents, Progress, ThisResult};
 
 #[derive(Debug, Clone)]
 pub struct Builder {
     initial_dir_options: DirsOptions,
     contents: PackageContents,
+    base: Option<PackageContents>,
     compression_level: i32,
-    compress_image_to_jpeg: bool,
+    image_profile: Option<ImageProfile>,
     header_mode: HeaderMode,
+    workers: Option<NonZeroUsize>,
+    zstd_workers: u32,
+    long_distance_matching: bool,
 }
 
 impl Default for Builder {
@@ -37,9 +51,13 @@ impl Default for Builder {
         Self {
             initial_dir_options: Default::default(),
             contents: Default::default(),
+            base: None,
             compression_level: Default::default(),
-            compress_image_to_jpeg: Default::default(),
+            image_profile: None,
             header_mode: HeaderMode::Complete,
+            workers: None,
+            zstd_workers: 0,
+            long_distance_matching: false,
         }
     }
 }
@@ -88,18 +106,62 @@ impl Builder {
             ..Default::default()
         }
     }
+    /// Transcode the images with the default JPEG [`ImageProfile`].
     pub fn set_compress_image_to_jpeg(mut self, compress_image_to_jpeg: bool) -> Self {
-        self.compress_image_to_jpeg = compress_image_to_jpeg;
+        self.image_profile = compress_image_to_jpeg.then(ImageProfile::default);
+        self
+    }
+    /// Transcode the images with `profile`.
+    ///
+    /// The filenames in the package contents are rewritten with the new extension.
+    pub fn set_image_profile(mut self, profile: Option<ImageProfile>) -> Self {
+        self.image_profile = profile;
         self
     }
+    pub fn get_image_profile(&self) -> Option<&ImageProfile> {
+        self.image_profile.as_ref()
+    }
     pub fn set_tar_header_mode(mut self, mode: HeaderMode) -> Self {
         self.header_mode = mode;
         self
     }
+    /// Package the data of `dirs` instead of the directories given to [`Self::new`].
+    pub fn set_dir_options(mut self, dirs: DirsOptions) -> Self {
+        self.initial_dir_options = dirs;
+        self
+    }
+    pub fn get_dir_options(&self) -> &DirsOptions {
+        &self.initial_dir_options
+    }
     pub fn set_content(mut self, content: PackageContents) -> Self {
         self.contents = content;
         self
     }
+    /// Build a delta package against `base`, the contents of a package built before
+    /// (see [`Archive::get_package_contents`](crate::Archive::get_package_contents)).
+    ///
+    /// Only the entries that aren't in the base manifest with the same checksum are written,
+    /// so the builder takes the base options: the zstd compressed entries,
+    /// the dictionary and the compression level, if the base has it.
+    /// The package contents only list these entries
+    /// and the [`PackageDelta`](crate::contents::delta::PackageDelta) refers to the base.
+    ///
+    /// The build fails if `base` has no manifest
+    /// or if the entries are then encoded differently from the base ones
+    /// (see [`PackageContentsOptions::same_entry_encoding`](crate::contents::options::PackageContentsOptions::same_entry_encoding)).
+    pub fn set_base(mut self, base: Option<PackageContents>) -> Self {
+        if let Some(base) = base.as_ref() {
+            if let Some(compression_level) = base.get_options().compression_level {
+                self.compression_level = compression_level;
+            }
+            self.contents.options = base.options.clone();
+        }
+        self.base = base;
+        self
+    }
+    pub fn get_base(&self) -> Option<&PackageContents> {
+        self.base.as_ref()
+    }
     pub fn add_manga(&mut self, id: Uuid) -> ThisResult<()> {
         let manga_data: MangaObject = self.initial_dir_options.pull(id)?;
         let cover_id = {
@@ -109,21 +171,19 @@ impl Builder {
                     RelationshipType::CoverArt,
                 ]))?
                 .id;
-            if let Some(data) = self
-                .initial_dir_options
-                .pull_all_covers()
-                .ok()
-                .and_then(|pull| pull.flatten().find(|data| data.id == manga_data_cover_id))
+            if Pull::<CoverObject, _>::pull(&self.initial_dir_options, manga_data_cover_id)
+                .is_ok()
             {
-                data.id
+                manga_data_cover_id
             } else {
+                let filter = CoverListDataPullFilterParams {
+                    manga_ids: vec![manga_data.id],
+                    ..Default::default()
+                };
                 self.initial_dir_options
-                    .pull_all_covers()?
+                    .pull_indexed_covers(&filter)?
                     .flatten()
-                    .to_filtered(CoverListDataPullFilterParams {
-                        manga_ids: vec![manga_data.id],
-                        ..Default::default()
-                    })
+                    .to_filtered(filter)
                     .map(|e| e.id)
                     .collect::<Vec<_>>()
                     .random()
@@ -330,58 +390,280 @@ impl Builder {
             .get_or_insert_with(Default::default)
             .zstd_compressed_metadata = compressed_metadata;
     }
+    /// Set how many threads read, convert and compress the entries while building.
+    ///
+    /// The entries are still written in the same order, whatever the number of workers.
+    /// Defaults to the available parallelism.
+    pub fn set_workers(&mut self, workers: NonZeroUsize) {
+        self.workers = Some(workers);
+    }
+    pub fn get_workers(&self) -> NonZeroUsize {
+        self.workers
+            .or_else(|| std::thread::available_parallelism().ok())
+            .unwrap_or(NonZeroUsize::MIN)
+    }
+    /// Set how many zstd threads compress each package frame, `0` (the default) disables it.
+    pub fn set_zstd_workers(&mut self, zstd_workers: u32) {
+        self.zstd_workers = zstd_workers;
+    }
+    pub fn get_zstd_workers(&self) -> u32 {
+        self.zstd_workers
+    }
+    /// Enable the zstd long distance matching for the package frames.
+    pub fn set_long_distance_matching(&mut self, long_distance_matching: bool) {
+        self.long_distance_matching = long_distance_matching;
+    }
+    pub fn get_long_distance_matching(&self) -> bool {
+        self.long_distance_matching
+    }
     pub fn build<W: Write>(self, writer: W) -> ThisResult<PackageContents> {
-        BuilderInner::new(self, writer)?.build()
+        self.build_with_progress(writer, |_| {})
     }
-    fn get_to_use_paths(&self) -> Vec<PathBuf> {
-        let mut paths = Vec::new();
+    /// [`Self::build`], calling `progress` after every appended job
+    /// (a manga, a cover with its image, a chapter or a chapter image).
+    pub fn build_with_progress<W, P>(self, writer: W, progress: P) -> ThisResult<PackageContents>
+    where
+        W: Write,
+        P: FnMut(Progress),
+    {
+        let mut writer = Some(writer);
+        let open_part = |_: u32| {
+            writer
+                .take()
+                .ok_or_else(|| io::Error::other("the package has only one part"))
+        };
+        BuilderInner::new(self)?
+            .build(None, open_part, progress)?
+            .pop()
+            .ok_or_else(|| io::Error::other("the package has not been written").into())
+    }
+    /// Build the package in parts of at most `max_part_size` bytes.
+    ///
+    /// `open_part` is called with the index of every new part, starting from `1`,
+    /// see [`volume_path`](crate::volume::volume_path) for the usual part names.
+    /// Every part has the `contents.cbor` of its own entries.
+    /// A chapter is only cut across parts if it's bigger than a part.
+    ///
+    /// Returns the contents of every part.
+    pub fn build_parts<W, F>(
+        self,
+        max_part_size: u64,
+        open_part: F,
+    ) -> ThisResult<Vec<PackageContents>>
+    where
+        W: Write,
+        F: FnMut(u32) -> io::Result<W>,
+    {
+        BuilderInner::new(self)?.build(Some(max_part_size), open_part, |_| {})
+    }
+    /// The metadata entries of the package, serialized like the builder writes them.
+    fn metadata_samples(&self) -> ThisResult<Vec<Vec<u8>>> {
+        fn sample<C: Serialize>(content: &C) -> ThisResult<Vec<u8>> {
+            let mut sample = Vec::new();
+            ciborium::into_writer(content, &mut sample)?;
+            Ok(sample)
+        }
+        let dirs = &self.initial_dir_options;
+        let mut samples = Vec::new();
         for (manga_id, manga_data) in &self.contents.data {
-            paths.push(
-                self.initial_dir_options
-                    .mangas_add(format!("{manga_id}.json")),
-            );
+            samples.push(sample(&Pull::<MangaObject, _>::pull(dirs, *manga_id)?)?);
             for cover_id in &manga_data.covers {
-                paths.push(
-                    self.initial_dir_options
-                        .covers_add(format!("{cover_id}.json")),
-                );
-                if let Ok(cover_data) =
-                    Pull::<CoverObject, Uuid>::pull(&self.initial_dir_options, *cover_id)
+                let mut cover: CoverObject = dirs.pull(*cover_id)?;
+                // The transcoded cover images are renamed in the written cover
+                if let Some(profile) = self.image_profile.as_ref()
+                    && ImageFormat::from_path(&cover.attributes.file_name)
+                        .is_ok_and(|format| format != ImageFormat::Gif)
                 {
-                    paths.push(
-                        self.initial_dir_options
-                            .cover_images_add(cover_data.attributes.file_name),
-                    );
+                    cover.attributes.file_name = profile.rename(&cover.attributes.file_name);
                 }
+                samples.push(sample(&cover)?);
             }
-            for (chapter_id, chapter_data) in &manga_data.chapters {
-                paths.push(
-                    self.initial_dir_options
-                        .chapters_id_add(*chapter_id)
-                        .join("data.json"),
-                );
-                for filename in &chapter_data.data {
-                    paths.push(
-                        self.initial_dir_options
-                            .chapters_id_data_add(*chapter_id)
-                            .join(filename),
-                    );
-                }
-                for filename in &chapter_data.data_saver {
-                    paths.push(
-                        self.initial_dir_options
-                            .chapters_id_data_saver_add(*chapter_id)
-                            .join(filename),
-                    );
-                }
+            for chapter_id in manga_data.chapters.keys() {
+                samples.push(sample(&Pull::<ChapterObject, _>::pull(dirs, *chapter_id)?)?);
             }
         }
-        paths
+        Ok(samples)
     }
+    /// Train a zstd dictionary of at most `max_size` bytes
+    /// from the metadata entries of the package.
     pub fn create_dict(&self, max_size: usize) -> io::Result<Vec<u8>> {
-        zstd::dict::from_files(self.get_to_use_paths(), max_size)
+        let samples = self.metadata_samples().map_err(io::Error::other)?;
+        zstd::dict::from_samples(&samples, max_size)
+    }
+    /// Set the dictionary used to compress the metadata and images entries.
+    ///
+    /// It is stored in the package contents options, so the [`Archive`](crate::Archive)
+    /// loads it automatically.
+    /// It is only used if [`Self::zstd_compressed_metadata`]
+    /// or [`Self::zstd_compressed_images`] is enabled.
+    pub fn set_dictionary(&mut self, dictionary: Option<Vec<u8>>) {
+        self.contents
+            .options
+            .get_or_insert_with(Default::default)
+            .dictionary = dictionary.map(ZstdDictionary::from);
+    }
+    /// Train a dictionary from the package metadata with [`Self::create_dict`]
+    /// and use it with [`Self::set_dictionary`].
+    pub fn train_dictionary(&mut self, max_size: usize) -> io::Result<()> {
+        let dictionary = self.create_dict(max_size)?;
+        self.set_dictionary(Some(dictionary));
+        Ok(())
+    }
+    pub fn get_dictionary(&self) -> Option<&[u8]> {
+        self.contents.options.as_ref()?.dictionary.as_deref()
     }
     pub fn get_package_contents(&self) -> &PackageContents {
         &self.contents
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read};
+
+    use api_core::data_push::{chapter::image::ChapterImagePushEntry, Push};
+    use mangadex_api_schema_rust::v5::{ChapterObject, Relationship};
+    use mangadex_api_types_rust::RelationshipType;
+    use uuid::Uuid;
+
+    use super::Builder;
+    use crate::{test_utils::library_with_chapter, Archive, PChapterObject, PackageContents};
+
+    /// The path and data of every entry of the package.
+    fn entries(package: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let decoded = zstd::decode_all(package).unwrap();
+        let mut tar = tar::Archive::new(decoded.as_slice());
+        tar.entries()
+            .unwrap()
+            .map(|entry| {
+                let mut entry = entry.unwrap();
+                let path = entry.path().unwrap().to_string_lossy().into_owned();
+                let mut data = Vec::new();
+                entry.read_to_end(&mut data).unwrap();
+                (path, data)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn zstd_workers_and_long_distance_matching_build() {
+        let images: [(&str, &[u8]); 2] = [("1.png", &[1; 4096]), ("2.png", &[2; 4096])];
+        let (_dir, dirs, contents) = library_with_chapter(images);
+        let build = |zstd_workers, long_distance_matching| {
+            let mut builder = Builder::new(dirs.clone()).set_content(contents.clone());
+            builder.set_zstd_workers(zstd_workers);
+            builder.set_long_distance_matching(long_distance_matching);
+            let mut package = Vec::new();
+            builder.build(&mut package).unwrap();
+            package
+        };
+        let plain = build(0, false);
+        let package = build(2, true);
+
+        assert_eq!(entries(&package), entries(&plain));
+        let archive = Archive::from_reader(Cursor::new(package)).unwrap();
+        assert!(archive.get_table_of_contents().is_some());
+        let chapters = |contents: &PackageContents| {
+            contents
+                .data
+                .values()
+                .flat_map(|manga_data| manga_data.chapters.values())
+                .map(|images| images.data.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            chapters(archive.get_package_contents().unwrap()),
+            chapters(&contents)
+        );
+    }
+
+    #[test]
+    fn the_dictionary_is_trained_on_the_written_metadata() {
+        let (_dir, mut dirs, mut contents) = library_with_chapter([("1.png", &[1; 64][..])]);
+        let (manga, manga_data) = contents.data.iter_mut().next().unwrap();
+        let mut manga_relationship = Relationship::default();
+        manga_relationship.id = *manga;
+        manga_relationship.type_ = RelationshipType::Manga;
+        for number in 0..64 {
+            let mut chapter = ChapterObject::default();
+            chapter.id = Uuid::new_v4();
+            chapter.attributes.chapter = Some(number.to_string());
+            chapter.attributes.title = Some(format!("The chapter number {number}"));
+            chapter.relationships.push(manga_relationship.clone());
+            dirs.push(chapter.clone()).unwrap();
+            manga_data
+                .chapters
+                .insert(chapter.id, PChapterObject::default());
+        }
+        let builder = Builder::new(dirs).set_content(contents);
+
+        let mut package = Vec::new();
+        builder.clone().build(&mut package).unwrap();
+        let mut written = entries(&package)
+            .into_iter()
+            .filter(|(path, _)| !path.ends_with(".png") && !path.ends_with("contents.cbor"))
+            .map(|(_, data)| data)
+            .collect::<Vec<_>>();
+        let mut samples = builder.metadata_samples().unwrap();
+        written.sort();
+        samples.sort();
+        assert_eq!(samples, written);
+
+        let mut builder = builder;
+        builder.train_dictionary(4096).unwrap();
+        builder.zstd_compressed_metadata(true);
+        let mut package = Vec::new();
+        builder.build(&mut package).unwrap();
+        let archive = Archive::from_reader(Cursor::new(package)).unwrap();
+        assert!(archive
+            .get_package_contents()
+            .unwrap()
+            .options
+            .as_ref()
+            .and_then(|options| options.dictionary.as_ref())
+            .is_some());
+    }
+
+    #[test]
+    fn a_delta_takes_the_compressed_metadata_of_its_base() {
+        let images: [(&str, &[u8]); 2] = [("1.png", &[1; 64]), ("2.png", &[2; 64])];
+        let (_dir, mut dirs, contents) = library_with_chapter(images);
+        let mut builder = Builder::new(dirs.clone()).set_content(contents.clone());
+        builder.zstd_compressed_metadata(true);
+        builder.set_compression_level(5);
+        let mut package = Vec::new();
+        let base = builder.build(&mut package).unwrap();
+        assert_eq!(base.get_options().compression_level, Some(5));
+
+        let chapter = contents
+            .data
+            .values()
+            .flat_map(|manga_data| manga_data.chapters.keys())
+            .next()
+            .copied()
+            .unwrap();
+        dirs.push(ChapterImagePushEntry::new(chapter, "2.png".into(), &[3; 64][..]))
+            .unwrap();
+        let delta_builder = Builder::new(dirs).set_content(contents).set_base(Some(base));
+        let mut delta = Vec::new();
+        let delta_contents = delta_builder.clone().build(&mut delta).unwrap();
+
+        let options = delta_contents.get_options();
+        assert!(options.zstd_compressed_metadata);
+        assert_eq!(options.compression_level, Some(5));
+        let written = entries(&delta)
+            .into_iter()
+            .map(|(path, _)| path)
+            .filter(|path| !path.ends_with("contents.cbor"))
+            .collect::<Vec<_>>();
+        assert_eq!(written.len(), 1);
+        assert!(written[0].ends_with("2.png"));
+
+        let mut other_level = delta_builder.clone();
+        other_level.set_compression_level(9);
+        assert!(other_level.build(&mut Vec::new()).is_err());
+        let mut other_dictionary = delta_builder;
+        other_dictionary.set_dictionary(Some(br#"{"id":"","type":"chapter"}"#.to_vec()));
+        assert!(other_dictionary.build(&mut Vec::new()).is_err());
+    }
+}
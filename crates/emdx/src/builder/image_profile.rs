@@ -0,0 +1,237 @@
+use std::io::{Seek, Write};
+
+use image::{
+    codecs::{jpeg::JpegEncoder, png::PngEncoder, webp::WebPEncoder},
+    imageops::FilterType,
+    DynamicImage, ImageResult,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ImageTargetFormat {
+    #[default]
+    Jpeg,
+    Png,
+    WebPLossless,
+}
+
+impl ImageTargetFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageTargetFormat::Jpeg => "jpg",
+            ImageTargetFormat::Png => "png",
+            ImageTargetFormat::WebPLossless => "webp",
+        }
+    }
+}
+
+/// How the [`Builder`](super::Builder) transcodes the chapter and cover images.
+///
+/// GIFs are always kept as is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageProfile {
+    format: ImageTargetFormat,
+    quality: u8,
+    max_width: Option<u32>,
+    max_height: Option<u32>,
+    grayscale: bool,
+}
+
+impl Default for ImageProfile {
+    fn default() -> Self {
+        Self {
+            format: Default::default(),
+            quality: 75,
+            max_width: None,
+            max_height: None,
+            grayscale: false,
+        }
+    }
+}
+
+impl ImageProfile {
+    pub fn new(format: ImageTargetFormat) -> Self {
+        Self {
+            format,
+            ..Default::default()
+        }
+    }
+    /// The JPEG quality, from `1` to `100`.
+    pub fn quality(self, quality: u8) -> Self {
+        Self {
+            quality: quality.clamp(1, 100),
+            ..self
+        }
+    }
+    /// Shrink the images bigger than `max_width` or `max_height`, keeping their aspect ratio.
+    pub fn max_size(self, max_width: Option<u32>, max_height: Option<u32>) -> Self {
+        Self {
+            max_width,
+            max_height,
+            ..self
+        }
+    }
+    /// Convert the images to grayscale, for the e-ink readers.
+    pub fn grayscale(self, grayscale: bool) -> Self {
+        Self { grayscale, ..self }
+    }
+    pub fn get_format(&self) -> ImageTargetFormat {
+        self.format
+    }
+    pub fn get_quality(&self) -> u8 {
+        self.quality
+    }
+    pub fn get_max_size(&self) -> (Option<u32>, Option<u32>) {
+        (self.max_width, self.max_height)
+    }
+    pub fn is_grayscale(&self) -> bool {
+        self.grayscale
+    }
+    fn exceeds_max_size(&self, image: &DynamicImage) -> bool {
+        self.max_width.is_some_and(|width| image.width() > width)
+            || self.max_height.is_some_and(|height| image.height() > height)
+    }
+    /// Resize and convert `image` for the profile format.
+    pub fn apply(&self, image: DynamicImage) -> DynamicImage {
+        let image = if self.exceeds_max_size(&image) {
+            image.resize(
+                self.max_width.unwrap_or(u32::MAX),
+                self.max_height.unwrap_or(u32::MAX),
+                FilterType::Lanczos3,
+            )
+        } else {
+            image
+        };
+        // every encoder takes 8 bits images, but JPEG has no alpha channel
+        let alpha = image.color().has_alpha() && self.format != ImageTargetFormat::Jpeg;
+        match (self.grayscale, alpha) {
+            (true, true) => DynamicImage::ImageLumaA8(image.to_luma_alpha8()),
+            (true, false) => DynamicImage::ImageLuma8(image.to_luma8()),
+            (false, true) => DynamicImage::ImageRgba8(image.to_rgba8()),
+            (false, false) => DynamicImage::ImageRgb8(image.to_rgb8()),
+        }
+    }
+    /// Encode an image made by [`Self::apply`] into `writer`.
+    pub fn encode<W: Write + Seek>(&self, image: &DynamicImage, writer: W) -> ImageResult<()> {
+        match self.format {
+            ImageTargetFormat::Jpeg => {
+                image.write_with_encoder(JpegEncoder::new_with_quality(writer, self.quality))
+            }
+            ImageTargetFormat::Png => image.write_with_encoder(PngEncoder::new(writer)),
+            ImageTargetFormat::WebPLossless => {
+                image.write_with_encoder(WebPEncoder::new_lossless(writer))
+            }
+        }
+    }
+    /// `filename` with the profile format extension appended.
+    ///
+    /// The original extension is kept in the stem, so the hash of the original image
+    /// (in the MangaDex@Home filenames) isn't taken for the transcoded image one.
+    pub fn rename(&self, filename: &str) -> String {
+        format!("{filename}.{}", self.format.extension())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read};
+
+    use api_core::data_pulls::chapter::images::verify::{check_image_integrity, ImageIntegrity};
+    use image::{ColorType, DynamicImage, ImageFormat};
+    use sha2::{Digest, Sha256};
+
+    use super::{ImageProfile, ImageTargetFormat};
+    use crate::{builder::Builder, test_utils::library_with_chapter};
+
+    #[test]
+    fn grayscale_resized_jpeg() {
+        let profile = ImageProfile::new(ImageTargetFormat::Jpeg)
+            .quality(90)
+            .max_size(Some(100), None)
+            .grayscale(true);
+        let image = profile.apply(DynamicImage::new_rgba8(400, 200));
+        assert_eq!((image.width(), image.height()), (100, 50));
+        assert_eq!(image.color(), ColorType::L8);
+
+        let mut encoded = Cursor::new(Vec::new());
+        profile.encode(&image, &mut encoded).unwrap();
+        assert_eq!(
+            image::guess_format(encoded.get_ref()).unwrap(),
+            ImageFormat::Jpeg
+        );
+        let hash = "0c7c6ee5".repeat(8);
+        assert_eq!(
+            profile.rename(&format!("1-{hash}.png")),
+            format!("1-{hash}.png.jpg")
+        );
+    }
+
+    #[test]
+    fn the_contents_have_the_transcoded_filenames() {
+        let mut png = Cursor::new(Vec::new());
+        DynamicImage::new_rgb8(8, 8)
+            .write_to(&mut png, ImageFormat::Png)
+            .unwrap();
+        let png = png.into_inner();
+        let hash = |data: &[u8]| {
+            Sha256::digest(data)
+                .iter()
+                .map(|b| format!("{b:02x}"))
+                .collect::<String>()
+        };
+        let page = format!("1-{}.png", hash(&png));
+        let broken = format!("2-{}.png", hash(b"not an image"));
+        // a broken image is packaged as it is
+        let images: [(&str, &[u8]); 2] = [(&page, &png), (&broken, b"not an image")];
+        let (_dir, dirs, contents) = library_with_chapter(images);
+        let (chapter, _) = contents
+            .data
+            .values()
+            .next()
+            .unwrap()
+            .chapters
+            .iter()
+            .next()
+            .unwrap();
+        let chapter = *chapter;
+        let mut package = Vec::new();
+        let written = Builder::new(dirs)
+            .set_content(contents)
+            .set_image_profile(Some(ImageProfile::new(ImageTargetFormat::Jpeg)))
+            .build(&mut package)
+            .unwrap();
+
+        let filenames = written.data.values().next().unwrap().chapters[&chapter]
+            .data
+            .clone();
+        let transcoded = format!("{page}.jpg");
+        assert_eq!(filenames, [transcoded.clone(), broken.clone()]);
+        let decoded = zstd::decode_all(package.as_slice()).unwrap();
+        let mut tar = tar::Archive::new(decoded.as_slice());
+        let mut images = Vec::new();
+        for entry in tar.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let path = entry.path().unwrap().into_owned();
+            if path
+                .parent()
+                .and_then(|dir| dir.parent())
+                .and_then(|dir| dir.file_name())
+                == Some(chapter.to_string().as_ref())
+            {
+                let mut data = Vec::new();
+                entry.read_to_end(&mut data).unwrap();
+                let filename = path.file_name().unwrap().to_string_lossy().into_owned();
+                let integrity = check_image_integrity(&filename, data.as_slice()).unwrap();
+                images.push((filename, image::guess_format(&data).ok(), integrity));
+            }
+        }
+        images.sort_by(|a, b| a.0.cmp(&b.0));
+        // the transcoded page no longer has the hash of its content, but it isn't corrupted
+        assert_eq!(
+            images,
+            [
+                (transcoded, Some(ImageFormat::Jpeg), ImageIntegrity::Unknown),
+                (broken, None, ImageIntegrity::Valid),
+            ]
+        );
+    }
+}
@@ -1,21 +1,30 @@
 use std::{
-    fs::{create_dir_all, File, OpenOptions},
-    io::{self, BufReader, BufWriter, Seek, Write},
-    path::Path,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Seek, Write},
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
 };
 
-use api_core::{data_pulls::Pull, DirsOptions};
+use api_core::{data_pulls::Pull, data_push::chapter::image::Mode as ChapterImagesMode, DirsOptions};
 use image::{ImageFormat, ImageResult};
 use mangadex_api_schema_rust::v5::{ChapterObject, CoverObject, MangaObject};
 use serde::Serialize;
 use tar::{Builder as TarBuilder, Header, HeaderMode};
-use tempfile::{tempdir, TempDir};
+use tempfile::tempfile;
 use uuid::Uuid;
-use zstd::{stream::AutoFinishEncoder, Encoder};
+use zstd::stream::AutoFinishEncoder;
 
 use crate::{
     constants::{CHAPTER_CONTENT_FILE, CONTENTS_FILENAME},
-    PChapterObject, PackageContents,
+    contents::{
+        delta::PackageDelta,
+        manifest::{ManifestEntry, PackageManifest},
+        options::PackageContentsOptions,
+    },
+    toc::TableOfContents,
+    utils::frame_writer::FrameWriter,
+    volume::PackageVolume,
+    PackageContents, Progress,
 };
 
 enum BuilderInnerWriter<'a, W: Write> {
@@ -24,9 +33,15 @@ enum BuilderInnerWriter<'a, W: Write> {
 }
 
 impl<W: Write> BuilderInnerWriter<'_, W> {
-    fn encoder(writer: W, compression_level: i32) -> io::Result<Self> {
+    fn encoder(
+        writer: W,
+        options: &PackageContentsOptions,
+        compression_level: i32,
+    ) -> io::Result<Self> {
         Ok(Self::Encoder(
-            Encoder::new(writer, compression_level)?.auto_finish(),
+            options
+                .entry_encoder(writer, compression_level)?
+                .auto_finish(),
         ))
     }
 }
@@ -47,364 +62,649 @@ impl<W: Write> Write for BuilderInnerWriter<'_, W> {
     }
 }
 
-use super::{Builder, ThisResult};
-pub struct BuilderInner<'a, W>
-where
-    W: Write,
-{
-    package_content: PackageContents,
-    workdir: TempDir,
-    tar: TarBuilder<AutoFinishEncoder<'a, W>>,
-    dir_options: DirsOptions,
-    default_dir_options: DirsOptions,
-    compression_level: i32,
-    compress_image_to_jpeg: bool,
-    header_mode: HeaderMode,
+use super::{pool::ordered_map, Builder, ImageProfile, ThisResult};
+
+/// A part of the package that can be prepared independently from the others.
+#[derive(Debug, Clone)]
+enum Job {
+    Manga(Uuid),
+    Cover {
+        manga: Uuid,
+        id: Uuid,
+    },
+    Chapter {
+        manga: Uuid,
+        id: Uuid,
+    },
+    ChapterImage {
+        manga: Uuid,
+        chapter: Uuid,
+        mode: ChapterImagesMode,
+        filename: String,
+    },
 }
 
-impl<W> BuilderInner<'_, W>
-where
-    W: Write,
-{
-    pub fn new(builder: Builder, writer: W) -> io::Result<Self> {
-        let workdir = tempdir()?;
-        let mut tar =
-            TarBuilder::new(Encoder::new(writer, builder.compression_level)?.auto_finish());
-        tar.mode(builder.header_mode);
+impl Job {
+    /// `true` for the last job of a manga or a chapter.
+    ///
+    /// The jobs between two group ends are kept in the same package part when possible.
+    fn ends_group(&self) -> bool {
+        matches!(self, Job::Manga(_) | Job::Chapter { .. })
+    }
+}
+
+/// A package entry ready to be appended.
+pub(super) struct PreparedEntry {
+    path: PathBuf,
+    file: File,
+    manifest_entry: ManifestEntry,
+}
+
+impl PreparedEntry {
+    pub(super) fn new(path: PathBuf, mut file: File) -> io::Result<Self> {
+        file.rewind()?;
+        let manifest_entry = ManifestEntry::from_reader(&mut file)?;
+        file.rewind()?;
         Ok(Self {
-            compression_level: builder.compression_level,
-            workdir,
-            tar,
-            dir_options: builder.initial_dir_options,
-            package_content: builder.contents,
-            default_dir_options: Default::default(),
-            compress_image_to_jpeg: builder.compress_image_to_jpeg,
-            header_mode: builder.header_mode,
+            path,
+            file,
+            manifest_entry,
         })
     }
-    fn append_file<P: AsRef<Path>>(&mut self, path: P, file: &mut File) -> io::Result<()> {
-        let mode = self.header_mode;
-        let metadata = file.metadata()?;
-        let mut header = Header::new_gnu();
-        header.set_metadata_in_mode(&metadata, mode);
-        let file_buffer = BufReader::new(file);
-        self.tar.append_data(&mut header, path, file_buffer)?;
-        Ok(())
+    /// An upper bound of the entry size in the package,
+    /// its table of contents and manifest records included.
+    fn size_bound(&self) -> u64 {
+        // the tar header, the GNU long name header and its data block
+        let tar_size = 3 * 512 + self.manifest_entry.size.next_multiple_of(512);
+        zstd::zstd_safe::compress_bound(tar_size as usize) as u64 + 512
+    }
+    /// `true` if `manifest` has this entry with the same checksum.
+    fn is_in(&self, manifest: &PackageManifest) -> bool {
+        manifest.get(&self.path) == Some(&self.manifest_entry)
     }
-    fn create_workdir_file<P: AsRef<Path>>(&self, path: P) -> io::Result<File> {
-        OpenOptions::new()
-            .read(true)
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(self.workdir.path().join(path))
+}
+
+struct PreparedJob {
+    entries: Vec<PreparedEntry>,
+    /// The chapter image filename, if it changed with the JPEG conversion
+    new_filename: Option<String>,
+}
+
+impl PreparedJob {
+    fn size_bound(&self) -> u64 {
+        self.entries.iter().map(PreparedEntry::size_bound).sum()
     }
+}
+
+impl From<PreparedEntry> for PreparedJob {
+    fn from(value: PreparedEntry) -> Self {
+        Self {
+            entries: vec![value],
+            new_filename: None,
+        }
+    }
+}
+
+/// Reads, converts and compresses the package entries.
+///
+/// It is shared between the builder workers.
+pub(super) struct EntryPreparer {
+    pub(super) dir_options: DirsOptions,
+    pub(super) default_dir_options: DirsOptions,
+    pub(super) options: PackageContentsOptions,
+    pub(super) compression_level: i32,
+    pub(super) image_profile: Option<ImageProfile>,
+}
+
+impl EntryPreparer {
     fn write_cbor_to_file<'b, C: Serialize>(
         &self,
         file: &'b mut File,
         content: &C,
     ) -> ThisResult<()> {
-        let writer: BuilderInnerWriter<'b, &'b mut File> = if !self
-            .package_content
-            .options
-            .as_ref()
-            .map(|e| e.zstd_compressed_metadata)
-            .unwrap_or_default()
-        {
+        let options = &self.options;
+        let writer: BuilderInnerWriter<'b, &'b mut File> = if !options.zstd_compressed_metadata {
             BuilderInnerWriter::Default(file)
         } else {
-            BuilderInnerWriter::encoder(file, self.compression_level)?
+            BuilderInnerWriter::encoder(file, options, self.compression_level)?
         };
         let mut file_buf_writer = BufWriter::new(writer);
         ciborium::into_writer(content, &mut file_buf_writer)?;
         file_buf_writer.flush()?;
         Ok(())
     }
-    fn wctf<C: Serialize>(&self, file: &mut File, content: &C) -> ThisResult<()> {
-        let mut file_buf_writer = BufWriter::new(file);
-        ciborium::into_writer(content, &mut file_buf_writer)?;
-        file_buf_writer.flush()?;
-        Ok(())
+    pub(super) fn metadata_entry<C: Serialize>(
+        &self,
+        path: PathBuf,
+        content: &C,
+    ) -> ThisResult<PreparedEntry> {
+        let mut file = tempfile()?;
+        self.write_cbor_to_file(&mut file, content)?;
+        Ok(PreparedEntry::new(path, file)?)
     }
-    fn build_contents(&mut self) -> ThisResult<()> {
-        let mut contents_file = self.create_workdir_file(CONTENTS_FILENAME)?;
-        self.wctf(&mut contents_file, &self.package_content)?;
-        contents_file.rewind()?;
-        self.append_file(CONTENTS_FILENAME, &mut contents_file)?;
-        Ok(())
+    fn pull_metadata_entry<D: Serialize>(
+        &self,
+        id: Uuid,
+        path: PathBuf,
+    ) -> ThisResult<PreparedEntry>
+    where
+        DirsOptions: Pull<D, Uuid, Error: Into<api_core::Error>>,
+    {
+        let data: D = self.dir_options.pull(id).map_err(|e| e.into())?;
+        self.metadata_entry(path, &data)
     }
-    fn create_dir_all<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
-        create_dir_all(self.workdir.path().join(path))
+    fn transcode_image<R>(
+        &self,
+        profile: &ImageProfile,
+        filename: &str,
+        reader: R,
+    ) -> ImageResult<(String, File)>
+    where
+        R: BufRead + Seek,
+    {
+        let format = ImageFormat::from_path(filename)?;
+        if format == ImageFormat::Gif {
+            return Err(image::ImageError::IoError(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Gif can't be transcoded",
+            )));
+        }
+        let image = profile.apply(image::load(reader, format)?);
+        let mut file_output_path = tempfile()?;
+        {
+            let mut file_out_buf = BufWriter::new(&mut file_output_path);
+            profile.encode(&image, &mut file_out_buf)?;
+            file_out_buf.flush()?;
+        }
+        file_output_path.rewind()?;
+        Ok((profile.rename(filename), file_output_path))
     }
-    fn get_package_content_chapter_images(&self, chapter: Uuid) -> ThisResult<PChapterObject> {
-        self.package_content
-            .data
-            .iter()
-            .find_map(|(_, manga_data)| {
-                manga_data.chapters.iter().find_map(|(chapter_id, images)| {
-                    if *chapter_id == chapter {
-                        Some(images.clone())
-                    } else {
-                        None
-                    }
-                })
-            })
-            .ok_or(api_core::Error::Io(io::Error::from(
-                io::ErrorKind::NotFound,
-            )))
+    /// Transcode and compress the image at `source` if it's enabled.
+    ///
+    /// `filename` is updated if the image is transcoded.
+    fn image_file(&self, source: &Path, filename: &mut String) -> io::Result<File> {
+        self.encode_image(File::open(source)?, filename)
     }
-    fn append_image_file<P: AsRef<Path>>(
-        &mut self,
-        path: P,
-        mut file: &mut File,
-    ) -> io::Result<()> {
-        if self
-            .package_content
-            .options
-            .as_ref()
-            .map(|e| e.zstd_compressed_images)
-            .unwrap_or_default()
+    /// Transcode and compress the image `file` named `filename` if it's enabled.
+    ///
+    /// `filename` is updated if the image is transcoded.
+    pub(super) fn encode_image(&self, mut file: File, filename: &mut String) -> io::Result<File> {
+        let mut file = if let Some(profile) = self.image_profile.as_ref()
+            && let Ok((new_filename, transcoded)) =
+                self.transcode_image(profile, filename, BufReader::new(&mut file))
         {
-            let mut temp = tempfile::tempfile()?;
-            {
-                let mut reader = BufReader::new(&mut file);
-                let mut writer =
-                    BufWriter::new(Encoder::new(&mut temp, self.compression_level)?.auto_finish());
-
-                io::copy(&mut reader, &mut writer)?;
-                writer.flush()?;
-            }
-            file.rewind()?;
-            temp.rewind()?;
-            self.append_file(path, &mut temp)?;
+            *filename = new_filename;
+            transcoded
         } else {
-            self.append_file(path, file)?;
+            file.rewind()?;
+            file
+        };
+        if !self.options.zstd_compressed_images {
+            return Ok(file);
         }
-        Ok(())
+        let mut temp = tempfile()?;
+        {
+            let mut reader = BufReader::new(&mut file);
+            let mut writer = BufWriter::new(
+                self.options
+                    .entry_encoder(&mut temp, self.compression_level)?
+                    .auto_finish(),
+            );
+            io::copy(&mut reader, &mut writer)?;
+            writer.flush()?;
+        }
+        Ok(temp)
     }
-    fn convert_image_to_jpeg<P>(&self, path: P) -> ImageResult<(String, File)>
-    where
-        P: AsRef<Path>,
-    {
-        let current_format = ImageFormat::from_path(&path)?;
-        let filename = path
-            .as_ref()
-            .file_name()
-            .and_then(|e| e.to_str())
-            .map(String::from)
-            .ok_or(io::Error::new(
-                io::ErrorKind::Unsupported,
-                "Filename transport not found",
-            ))?;
-        let image = image::open(path)?;
-        if current_format != ImageFormat::Gif {
-            let new_filename = format!("{filename}.{}", ImageFormat::Jpeg.extensions_str()[0]);
-            let mut file_output_path = self.create_workdir_file(&new_filename)?;
-            {
-                let mut file_out_buf = BufWriter::new(&mut file_output_path);
-                image.write_to(&mut file_out_buf, ImageFormat::Jpeg)?;
-                file_out_buf.flush()?;
+    fn prepare_cover(&self, id: Uuid) -> ThisResult<PreparedJob> {
+        let mut cover: CoverObject = self.dir_options.pull(id)?;
+        let filename = &mut cover.attributes.file_name;
+        let source = self.dir_options.cover_images_add(&*filename);
+        let image = self.image_file(&source, filename)?;
+        let image = PreparedEntry::new(
+            self.default_dir_options.cover_images_add(&*filename),
+            image,
+        )?;
+        let metadata =
+            self.metadata_entry(self.default_dir_options.covers_add(format!("{id}.cbor")), &cover)?;
+        Ok(PreparedJob {
+            entries: vec![image, metadata],
+            new_filename: None,
+        })
+    }
+    fn prepare(&self, job: &Job) -> ThisResult<PreparedJob> {
+        match job {
+            Job::Manga(id) => Ok(self
+                .pull_metadata_entry::<MangaObject>(
+                    *id,
+                    self.default_dir_options.mangas_add(format!("{id}.cbor")),
+                )?
+                .into()),
+            Job::Cover { id, .. } => self.prepare_cover(*id),
+            Job::Chapter { id, .. } => Ok(self
+                .pull_metadata_entry::<ChapterObject>(
+                    *id,
+                    self.default_dir_options
+                        .chapters_add(format!("{id}/{CHAPTER_CONTENT_FILE}")),
+                )?
+                .into()),
+            Job::ChapterImage {
+                chapter,
+                mode,
+                filename,
+                ..
+            } => {
+                let source = self
+                    .dir_options
+                    .chapter_images_dir(*chapter, *mode)
+                    .join(filename);
+                let mut new_filename = filename.clone();
+                let image = self.image_file(&source, &mut new_filename)?;
+                let path = self
+                    .default_dir_options
+                    .chapter_images_dir(*chapter, *mode)
+                    .join(&new_filename);
+                Ok(PreparedJob {
+                    entries: vec![PreparedEntry::new(path, image)?],
+                    new_filename: (new_filename != *filename).then_some(new_filename),
+                })
             }
-            file_output_path.rewind()?;
-            Ok((new_filename, file_output_path))
-        } else {
-            Err(image::ImageError::IoError(io::Error::new(
-                io::ErrorKind::Unsupported,
-                "Gif can't be transformed into JPEG",
-            )))
         }
     }
-    fn append_chapter_images_data(
-        &mut self,
-        (id, images): (Uuid, &mut PChapterObject),
-    ) -> io::Result<()> {
-        let data = images
-            .data
-            .iter_mut()
-            .map(|image| {
-                let path = self.dir_options.chapters_id_data_add(id).join(&*image);
-                (image, path)
-            })
-            .collect::<Vec<_>>();
+}
 
-        for (filename, path) in data {
-            if self.compress_image_to_jpeg {
-                if let Ok((new_filename, mut file)) = self.convert_image_to_jpeg(&path) {
-                    *filename = new_filename;
-                    self.append_image_file(
-                        self.default_dir_options
-                            .chapters_id_data_add(id)
-                            .join(filename),
-                        &mut file,
-                    )?;
-                } else {
-                    self.append_image_file(
-                        self.default_dir_options
-                            .chapters_id_data_add(id)
-                            .join(filename),
-                        &mut File::open(&path)?,
-                    )?;
-                }
-            } else {
-                self.append_image_file(
-                    self.default_dir_options
-                        .chapters_id_data_add(id)
-                        .join(filename),
-                    &mut File::open(&path)?,
-                )?;
+/// What every package part is written with.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct PartSettings {
+    pub(super) compression_level: i32,
+    pub(super) zstd_workers: u32,
+    pub(super) long_distance_matching: bool,
+    pub(super) header_mode: HeaderMode,
+}
+
+/// Appends the prepared entries to the package, one frame per entry.
+pub(super) struct EntryWriter<W>
+where
+    W: Write,
+{
+    tar: TarBuilder<FrameWriter<W>>,
+    toc: TableOfContents,
+    manifest: PackageManifest,
+    header_mode: HeaderMode,
+}
+
+impl<W> EntryWriter<W>
+where
+    W: Write,
+{
+    pub(super) fn new(writer: W, settings: PartSettings) -> Self {
+        let mut frame_writer = FrameWriter::new(writer, settings.compression_level);
+        frame_writer.set_workers(settings.zstd_workers);
+        frame_writer.set_long_distance_matching(settings.long_distance_matching);
+        let mut tar = TarBuilder::new(frame_writer);
+        tar.mode(settings.header_mode);
+        Self {
+            tar,
+            toc: Default::default(),
+            manifest: PackageManifest::new(),
+            header_mode: settings.header_mode,
+        }
+    }
+    /// Append `entry` and return the size of its frame.
+    pub(super) fn append(&mut self, entry: PreparedEntry) -> io::Result<u64> {
+        let PreparedEntry {
+            path,
+            file,
+            manifest_entry,
+        } = entry;
+        let metadata = file.metadata()?;
+        let mut header = Header::new_gnu();
+        header.set_metadata_in_mode(&metadata, self.header_mode);
+        self.tar
+            .append_data(&mut header, &path, BufReader::new(file))?;
+        let mut written = 0;
+        if let Some(entry) = self.tar.get_mut().end_frame()? {
+            written = entry.compressed_size;
+            self.toc.insert(&path, entry);
+        }
+        self.manifest.insert(path, manifest_entry);
+        Ok(written)
+    }
+    fn finish(self) -> io::Result<W> {
+        self.tar.into_inner()?.finish(&self.toc)
+    }
+    /// Append the package `contents.cbor`, with the manifest of the appended entries,
+    /// and finish the package.
+    pub(super) fn finish_with_contents(
+        mut self,
+        contents: &mut PackageContents,
+    ) -> ThisResult<W> {
+        contents.manifest = Some(self.manifest.clone());
+        let mut contents_file = tempfile()?;
+        {
+            let mut file_buf_writer = BufWriter::new(&mut contents_file);
+            ciborium::into_writer(&*contents, &mut file_buf_writer)?;
+            file_buf_writer.flush()?;
+        }
+        self.append(PreparedEntry::new(CONTENTS_FILENAME.into(), contents_file)?)?;
+        Ok(self.finish()?)
+    }
+}
+
+/// A package part being written, with the contents of its entries.
+struct Volume<W>
+where
+    W: Write,
+{
+    writer: EntryWriter<W>,
+    contents: PackageContents,
+    size: u64,
+}
+
+impl<W> Volume<W>
+where
+    W: Write,
+{
+    fn append(&mut self, job: Job, prepared: PreparedJob) -> io::Result<()> {
+        for entry in prepared.entries {
+            self.size += self.writer.append(entry)?;
+        }
+        let data = &mut self.contents.data;
+        match job {
+            Job::Manga(id) => {
+                data.entry(id).or_default();
+            }
+            Job::Cover { manga, id } => data.entry(manga).or_default().covers.push(id),
+            Job::Chapter { manga, id } => {
+                data.entry(manga)
+                    .or_default()
+                    .chapters
+                    .entry(id)
+                    .or_default();
+            }
+            Job::ChapterImage {
+                manga,
+                chapter,
+                mode,
+                filename,
+            } => {
+                let images = data
+                    .entry(manga)
+                    .or_default()
+                    .chapters
+                    .entry(chapter)
+                    .or_default();
+                let filenames = match mode {
+                    ChapterImagesMode::Data => &mut images.data,
+                    ChapterImagesMode::DataSaver => &mut images.data_saver,
+                };
+                filenames.push(prepared.new_filename.unwrap_or(filename));
             }
         }
         Ok(())
     }
-    fn append_chapter_images_data_saver(
-        &mut self,
-        (id, images): (Uuid, &mut PChapterObject),
-    ) -> io::Result<()> {
-        let datas = images
-            .data_saver
-            .iter_mut()
-            .map(|image| {
-                let path = self
-                    .dir_options
-                    .chapters_id_data_saver_add(id)
-                    .join(&*image);
-                (image, path)
-            })
-            .collect::<Vec<_>>();
-        for (filename, path) in datas {
-            if self.compress_image_to_jpeg {
-                if let Ok((new_filename, mut file)) = self.convert_image_to_jpeg(&path) {
-                    *filename = new_filename;
-                    self.append_image_file(
-                        self.default_dir_options
-                            .chapters_id_data_saver_add(id)
-                            .join(filename),
-                        &mut file,
-                    )?;
-                } else {
-                    self.append_image_file(
-                        self.default_dir_options
-                            .chapters_id_data_saver_add(id)
-                            .join(filename),
-                        &mut File::open(&path)?,
-                    )?;
+    /// `true` if `size` more bytes would make the part bigger than `budget`.
+    ///
+    /// An empty part is never full, so the jobs bigger than a part are still written.
+    fn is_full(&self, size: u64, budget: Option<u64>) -> bool {
+        budget.is_some_and(|budget| self.size > 0 && self.size + size > budget)
+    }
+}
+
+pub struct BuilderInner {
+    package_content: PackageContents,
+    /// The base package manifest and the delta info of a delta package
+    base: Option<(PackageManifest, PackageDelta)>,
+    settings: PartSettings,
+    preparer: EntryPreparer,
+    workers: NonZeroUsize,
+}
+
+impl BuilderInner {
+    pub fn new(mut builder: Builder) -> io::Result<Self> {
+        let workers = builder.get_workers();
+        if let Some(options) = builder.contents.options.as_mut() {
+            options.set_entry_compression_level(builder.compression_level);
+        }
+        let base = match builder.base {
+            Some(base) => {
+                if !builder
+                    .contents
+                    .get_options()
+                    .same_entry_encoding(&base.get_options())
+                {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "the package entries aren't encoded like its base ones, \
+                        the delta would rewrite all of them",
+                    ));
                 }
-            } else {
-                self.append_image_file(
-                    self.default_dir_options
-                        .chapters_id_data_saver_add(id)
-                        .join(filename),
-                    &mut File::open(&path)?,
-                )?;
+                let delta = PackageDelta::new(&base)?;
+                base.manifest.map(|manifest| (manifest, delta))
+            }
+            None => None,
+        };
+        Ok(Self {
+            base,
+            settings: PartSettings {
+                compression_level: builder.compression_level,
+                zstd_workers: builder.zstd_workers,
+                long_distance_matching: builder.long_distance_matching,
+                header_mode: builder.header_mode,
+            },
+            preparer: EntryPreparer {
+                options: builder.contents.get_options().into_owned(),
+                dir_options: builder.initial_dir_options,
+                default_dir_options: Default::default(),
+                compression_level: builder.compression_level,
+                image_profile: builder.image_profile,
+            },
+            package_content: builder.contents,
+            workers,
+        })
+    }
+    /// Every job of the package, in the order of their entries.
+    ///
+    /// The mangas and chapters are sorted by id so the output doesn't depend on the map order.
+    fn jobs(&self) -> Vec<Job> {
+        let mut mangas = self.package_content.data.iter().collect::<Vec<_>>();
+        mangas.sort_by_key(|(id, _)| **id);
+        let mut jobs = Vec::new();
+        for (manga_id, manga_data) in mangas {
+            jobs.extend(manga_data.covers.iter().map(|id| Job::Cover {
+                manga: *manga_id,
+                id: *id,
+            }));
+            jobs.push(Job::Manga(*manga_id));
+            let mut chapters = manga_data.chapters.iter().collect::<Vec<_>>();
+            chapters.sort_by_key(|(id, _)| **id);
+            for (chapter_id, images) in chapters {
+                let modes = [
+                    (ChapterImagesMode::Data, &images.data),
+                    (ChapterImagesMode::DataSaver, &images.data_saver),
+                ];
+                for (mode, filenames) in modes {
+                    jobs.extend(filenames.iter().map(|filename| Job::ChapterImage {
+                        manga: *manga_id,
+                        chapter: *chapter_id,
+                        mode,
+                        filename: filename.clone(),
+                    }));
+                }
+                jobs.push(Job::Chapter {
+                    manga: *manga_id,
+                    id: *chapter_id,
+                });
             }
         }
-        Ok(())
+        jobs
     }
-    fn pull_and_write_to_cbor<D: Serialize>(&mut self, id: Uuid, file: &mut File) -> ThisResult<D>
+    /// The space kept in every part for its `contents.cbor`.
+    fn part_overhead(&self) -> u64 {
+        let dictionary = self
+            .package_content
+            .get_options()
+            .dictionary
+            .as_ref()
+            .map(|dictionary| dictionary.len() as u64)
+            .unwrap_or_default();
+        4096 + dictionary
+    }
+    fn open_volume<W, F>(&self, index: usize, open_part: &mut F) -> io::Result<Volume<W>>
     where
-        DirsOptions: Pull<D, Uuid, Error: Into<api_core::Error>>,
+        W: Write,
+        F: FnMut(u32) -> io::Result<W>,
     {
-        let data: D = self.dir_options.pull(id).map_err(|e| e.into())?;
-        self.write_cbor_to_file(file, &data)?;
-        Ok(data)
-    }
-    fn append_chapter_images(
-        &mut self,
-        (id, images): (Uuid, &mut PChapterObject),
-    ) -> io::Result<()> {
-        self.append_chapter_images_data((id, images))?;
-
-        self.append_chapter_images_data_saver((id, images))?;
-        Ok(())
+        let writer = open_part(index as u32)?;
+        Ok(Volume {
+            writer: EntryWriter::new(writer, self.settings),
+            contents: Default::default(),
+            size: 0,
+        })
     }
-    fn build_chapter(&mut self, id: Uuid) -> ThisResult<()> {
-        self.create_dir_all("chapters")?;
-        let mut content_files = self.create_workdir_file(format!("chapters/{id}.cbor"))?;
-        self.pull_and_write_to_cbor::<ChapterObject>(id, &mut content_files)?;
-        content_files.rewind()?;
-        let mut images = self.get_package_content_chapter_images(id)?;
-        self.append_chapter_images((id, &mut images))?;
-        self.append_file(
-            self.default_dir_options
-                .chapters_id_add(id)
-                .join(CHAPTER_CONTENT_FILE),
-            &mut content_files,
-        )?;
-        Ok(())
+    /// Write the part `contents.cbor` and finish the part.
+    fn finish_volume<W: Write>(
+        &self,
+        volume: Volume<W>,
+        info: Option<PackageVolume>,
+    ) -> ThisResult<PackageContents> {
+        let Volume {
+            writer,
+            mut contents,
+            ..
+        } = volume;
+        contents.options = self.package_content.options.clone();
+        contents.volume = info;
+        contents.delta = self.base.as_ref().map(|(_, delta)| delta.clone());
+        writer.finish_with_contents(&mut contents)?.flush()?;
+        Ok(contents)
     }
-    fn append_cover_image(&mut self, cover: &mut CoverObject) -> io::Result<()> {
-        let filename = &mut cover.attributes.file_name;
-        let image_path = self.dir_options.cover_images_add(&*filename);
-        if self.compress_image_to_jpeg {
-            if let Ok((new_filename, mut file)) = self.convert_image_to_jpeg(&image_path) {
-                *filename = new_filename;
-                self.append_image_file(
-                    self.default_dir_options.cover_images_add(filename),
-                    &mut file,
-                )?;
-            } else {
-                self.append_image_file(
-                    self.default_dir_options.cover_images_add(filename),
-                    &mut File::open(image_path)?,
-                )?;
+    /// Append the jobs of a manga or a chapter,
+    /// in a new part if they don't fit in the current one.
+    ///
+    /// A group is only cut across parts if it's bigger than a part.
+    fn append_group<W, F>(
+        &self,
+        group: &mut Vec<(Job, PreparedJob)>,
+        budget: Option<u64>,
+        volume: &mut Option<Volume<W>>,
+        parts: &mut Vec<PackageContents>,
+        open_part: &mut F,
+    ) -> ThisResult<()>
+    where
+        W: Write,
+        F: FnMut(u32) -> io::Result<W>,
+    {
+        let group_size: u64 = group
+            .iter()
+            .map(|(_, prepared)| prepared.size_bound())
+            .sum();
+        if budget.is_some_and(|budget| group_size <= budget)
+            && volume
+                .as_ref()
+                .is_some_and(|current| current.is_full(group_size, budget))
+        {
+            self.close_volume(volume, parts)?;
+        }
+        for (job, prepared) in group.drain(..) {
+            if volume
+                .as_ref()
+                .is_some_and(|current| current.is_full(prepared.size_bound(), budget))
+            {
+                self.close_volume(volume, parts)?;
             }
-        } else {
-            self.append_image_file(
-                self.default_dir_options.cover_images_add(filename),
-                &mut File::open(image_path)?,
-            )?;
+            let current = match volume.take() {
+                Some(current) => current,
+                None => self.open_volume(parts.len() + 1, open_part)?,
+            };
+            volume.insert(current).append(job, prepared)?;
         }
         Ok(())
     }
-    fn build_cover(&mut self, id: Uuid) -> ThisResult<()> {
-        self.create_dir_all("covers")?;
-        let mut content_files = self.create_workdir_file(format!("covers/{id}.cbor"))?;
-        //println!("pulling cover content");
-        let mut cover: CoverObject = self.dir_options.pull(id)?;
-        //println!("writing cover image");
-        self.append_cover_image(&mut cover)?;
-        self.write_cbor_to_file(&mut content_files, &cover)?;
-        content_files.rewind()?;
-        //println!("{:#?}", content_files.metadata()?);
-        //println!("writing cover data");
-        self.append_file(
-            self.default_dir_options.covers_add(format!("{id}.cbor")),
-            &mut content_files,
-        )?;
+    fn close_volume<W: Write>(
+        &self,
+        volume: &mut Option<Volume<W>>,
+        parts: &mut Vec<PackageContents>,
+    ) -> ThisResult<()> {
+        if let Some(current) = volume.take() {
+            let info = PackageVolume {
+                index: parts.len() as u32 + 1,
+                last: false,
+            };
+            parts.push(self.finish_volume(current, Some(info))?);
+        }
         Ok(())
     }
-    fn build_manga(&mut self, id: Uuid) -> ThisResult<()> {
-        self.create_dir_all("mangas")?;
-        let mut content_files = self.create_workdir_file(format!("mangas/{id}.cbor"))?;
-        self.pull_and_write_to_cbor::<MangaObject>(id, &mut content_files)?;
-        content_files.rewind()?;
-        self.append_file(
-            self.default_dir_options.mangas_add(format!("{id}.cbor")),
-            &mut content_files,
+    /// Build the package, in parts of at most `max_part_size` bytes if it's set.
+    ///
+    /// `open_part` is called with the index of every new part, starting from `1`,
+    /// and `progress` after every job.
+    pub fn build<W, F, P>(
+        self,
+        max_part_size: Option<u64>,
+        mut open_part: F,
+        mut progress: P,
+    ) -> ThisResult<Vec<PackageContents>>
+    where
+        W: Write,
+        F: FnMut(u32) -> io::Result<W>,
+        P: FnMut(Progress),
+    {
+        let budget = max_part_size
+            .map(|max_part_size| {
+                max_part_size
+                    .checked_sub(self.part_overhead())
+                    .filter(|budget| *budget > 0)
+                    .ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            "the maximum part size is too small",
+                        )
+                    })
+            })
+            .transpose()?;
+        let jobs = self.jobs();
+        let total = Some(jobs.len());
+        let mut done = 0;
+        let preparer = &self.preparer;
+        let mut parts = Vec::new();
+        let mut volume = None;
+        let mut group = Vec::new();
+        ordered_map(
+            self.workers,
+            &jobs,
+            |job| preparer.prepare(job),
+            |job, mut prepared| {
+                // a delta package only has the entries that aren't in its base
+                if let Some((base_manifest, _)) = self.base.as_ref() {
+                    prepared.entries.retain(|entry| !entry.is_in(base_manifest));
+                }
+                if !prepared.entries.is_empty() {
+                    group.push((job.clone(), prepared));
+                }
+                if job.ends_group() {
+                    self.append_group(
+                        &mut group,
+                        budget,
+                        &mut volume,
+                        &mut parts,
+                        &mut open_part,
+                    )?;
+                }
+                done += 1;
+                progress(Progress { done, total });
+                Ok(())
+            },
         )?;
-        Ok(())
-    }
-
-    pub fn build(mut self) -> ThisResult<PackageContents> {
-        for (manga_id, manga_data) in self.package_content.data.clone() {
-            // println!("writing {manga_id}");
-            for cover_id in &manga_data.covers {
-                //println!("writing cover {cover_id}");
-                self.build_cover(*cover_id)?;
-                //println!("builded cover");
-            }
-            //println!("writing {manga_id} manga data");
-            self.build_manga(manga_id)?;
-            //println!("writing {manga_id}");
-            for chapter in manga_data.chapters.keys() {
-                //println!("writing {chapter} chapter");
-                self.build_chapter(*chapter)?;
-                //println!("writed {chapter} chapter");
-            }
-        }
-        //println!("writing contents");
-        self.build_contents()?;
-        //println!("writed");
-        Ok(self.package_content)
+        // an empty package still has its `contents.cbor`
+        let last = match volume {
+            Some(last) => last,
+            None => self.open_volume(parts.len() + 1, &mut open_part)?,
+        };
+        let info = budget.map(|_| PackageVolume {
+            index: parts.len() as u32 + 1,
+            last: true,
+        });
+        parts.push(self.finish_volume(last, info)?);
+        Ok(parts)
     }
 }
@@ -0,0 +1,159 @@
+use std::{
+    collections::BTreeMap,
+    io,
+    num::NonZeroUsize,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Condvar, Mutex, PoisonError,
+    },
+    thread,
+};
+
+use crate::ThisResult;
+
+#[derive(Default)]
+struct Progress {
+    consumed: usize,
+    cancelled: bool,
+}
+
+/// Shared between the workers and the consumer.
+struct Window {
+    progress: Mutex<Progress>,
+    condvar: Condvar,
+    size: usize,
+}
+
+impl Window {
+    /// Wait until the job `index` is close enough to the consumer.
+    ///
+    /// Returns `false` if the consumer has stopped.
+    fn wait_for(&self, index: usize) -> bool {
+        let mut progress = self
+            .progress
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        while !progress.cancelled && index >= progress.consumed + self.size {
+            progress = self
+                .condvar
+                .wait(progress)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        !progress.cancelled
+    }
+    fn update<F: FnOnce(&mut Progress)>(&self, update: F) {
+        update(
+            &mut self
+                .progress
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner),
+        );
+        self.condvar.notify_all();
+    }
+}
+
+fn stopped_worker_error() -> io::Error {
+    io::Error::other("a package builder worker has stopped")
+}
+
+/// Run `prepare` on every job with `workers` threads
+/// and pass the results to `consume` in the `jobs` order.
+///
+/// The workers don't go further than a few jobs ahead of `consume`,
+/// so the prepared results don't pile up.
+pub(super) fn ordered_map<J, T, P, C>(
+    workers: NonZeroUsize,
+    jobs: &[J],
+    prepare: P,
+    mut consume: C,
+) -> ThisResult<()>
+where
+    J: Sync,
+    T: Send,
+    P: Fn(&J) -> ThisResult<T> + Sync,
+    C: FnMut(&J, T) -> ThisResult<()>,
+{
+    if workers.get() == 1 {
+        for job in jobs {
+            consume(job, prepare(job)?)?;
+        }
+        return Ok(());
+    }
+    let next = AtomicUsize::new(0);
+    let window = Window {
+        progress: Default::default(),
+        condvar: Condvar::new(),
+        size: workers.get() * 2,
+    };
+    thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel::<(usize, ThisResult<T>)>();
+        for _ in 0..workers.get() {
+            let sender = sender.clone();
+            let (next, window, prepare) = (&next, &window, &prepare);
+            scope.spawn(move || {
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(job) = jobs.get(index) else {
+                        break;
+                    };
+                    if !window.wait_for(index) {
+                        break;
+                    }
+                    // a panicking job would leave the consumer waiting for it forever
+                    let prepared = panic::catch_unwind(AssertUnwindSafe(|| prepare(job)))
+                        .unwrap_or_else(|_| Err(stopped_worker_error().into()));
+                    if sender.send((index, prepared)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+        let mut pending = BTreeMap::new();
+        let res: ThisResult<()> = jobs.iter().enumerate().try_for_each(|(index, job)| {
+            let prepared = loop {
+                if let Some(prepared) = pending.remove(&index) {
+                    break prepared;
+                }
+                let (done, prepared) = receiver.recv().map_err(|_| stopped_worker_error())?;
+                pending.insert(done, prepared);
+            };
+            consume(job, prepared?)?;
+            window.update(|progress| progress.consumed = index + 1);
+            Ok(())
+        });
+        if res.is_err() {
+            window.update(|progress| progress.cancelled = true);
+        }
+        res
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use super::ordered_map;
+
+    #[test]
+    fn results_are_consumed_in_the_jobs_order() {
+        let jobs = (0..32u64).collect::<Vec<_>>();
+        let mut consumed = Vec::new();
+        ordered_map(
+            NonZeroUsize::new(4).unwrap(),
+            &jobs,
+            |job| {
+                // make the first jobs the slowest ones
+                std::thread::sleep(std::time::Duration::from_millis(32 - job));
+                Ok(job * 2)
+            },
+            |_, doubled| {
+                consumed.push(doubled);
+                Ok(())
+            },
+        )
+        .unwrap();
+        assert_eq!(consumed, jobs.iter().map(|job| job * 2).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,422 @@
+use std::{
+    collections::{btree_map::Entry, BTreeMap, HashMap, HashSet},
+    fs::File,
+    io::{self, BufRead, Seek, Write},
+    path::{Path, PathBuf},
+    slice,
+};
+
+use api_core::{data_push::chapter::image::Mode as ChapterImagesMode, DirsOptions};
+use mangadex_api_schema_rust::v5::{ChapterObject, CoverObject, MangaObject};
+use mangadex_api_types_rust::MangaDexDateTime;
+use tar::HeaderMode;
+use uuid::Uuid;
+
+use super::{
+    inner::{EntryPreparer, EntryWriter, PartSettings, PreparedEntry},
+    ImageProfile,
+};
+use crate::{
+    archive::{import::is_newer, pull::any::PossibleEntryData},
+    constants::{CHAPTER_CONTENT_FILE, CONTENTS_FILENAME},
+    contents::{delta::PackageDelta, options::PackageContentsOptions},
+    Archive, PackageContents, ThisResult,
+};
+
+/// Writes a new package from the entries of existing [`Archive`]s, in a single pass,
+/// without extracting them into a data directory.
+///
+/// The entries are re-encoded with other [`PackageContentsOptions`],
+/// filtered with a [`PackageContents`] and their images can be transcoded.
+#[derive(Debug, Clone)]
+pub struct Reencoder {
+    options: PackageContentsOptions,
+    contents: Option<PackageContents>,
+    compression_level: i32,
+    image_profile: Option<ImageProfile>,
+    header_mode: HeaderMode,
+    zstd_workers: u32,
+    long_distance_matching: bool,
+}
+
+impl Default for Reencoder {
+    fn default() -> Self {
+        Self {
+            options: Default::default(),
+            contents: None,
+            compression_level: Default::default(),
+            image_profile: None,
+            header_mode: HeaderMode::Complete,
+            zstd_workers: 0,
+            long_distance_matching: false,
+        }
+    }
+}
+
+/// The newest metadata found in the merged packages, by id.
+#[derive(Default)]
+struct NewestMetadata {
+    mangas: BTreeMap<Uuid, MangaObject>,
+    chapters: BTreeMap<Uuid, ChapterObject>,
+    covers: BTreeMap<Uuid, CoverObject>,
+}
+
+fn insert_newer<T, F>(objects: &mut BTreeMap<Uuid, T>, id: Uuid, object: T, version: F)
+where
+    F: Fn(&T) -> (u32, Option<&MangaDexDateTime>),
+{
+    match objects.entry(id) {
+        Entry::Vacant(entry) => {
+            entry.insert(object);
+        }
+        Entry::Occupied(mut entry) => {
+            if is_newer(version(&object), version(entry.get())) {
+                entry.insert(object);
+            }
+        }
+    }
+}
+
+impl NewestMetadata {
+    fn insert_manga(&mut self, manga: MangaObject) {
+        insert_newer(&mut self.mangas, manga.id, manga, |manga| {
+            (manga.attributes.version, manga.attributes.updated_at.as_ref())
+        });
+    }
+    fn insert_chapter(&mut self, chapter: ChapterObject) {
+        insert_newer(&mut self.chapters, chapter.id, chapter, |chapter| {
+            (chapter.attributes.version, chapter.attributes.updated_at.as_ref())
+        });
+    }
+    fn insert_cover(&mut self, cover: CoverObject) {
+        insert_newer(&mut self.covers, cover.id, cover, |cover| {
+            (cover.attributes.version, cover.attributes.updated_at.as_ref())
+        });
+    }
+}
+
+/// The image filenames of `chapter` in `contents`.
+fn chapter_images_mut(
+    contents: &mut PackageContents,
+    chapter: Uuid,
+    mode: ChapterImagesMode,
+) -> Option<&mut Vec<String>> {
+    let images = contents
+        .data
+        .values_mut()
+        .find_map(|manga_data| manga_data.chapters.get_mut(&chapter))?;
+    Some(match mode {
+        ChapterImagesMode::Data => &mut images.data,
+        ChapterImagesMode::DataSaver => &mut images.data_saver,
+    })
+}
+
+/// The delta of the merged package.
+///
+/// Only a single delta package stays a delta,
+/// the other delta packages must be merged with their base.
+fn merged_delta<R>(archives: &[Archive<'_, R>]) -> ThisResult<Option<PackageDelta>>
+where
+    R: BufRead + Seek,
+{
+    if let [archive] = archives {
+        return Ok(archive.get_package_contents()?.delta.clone());
+    }
+    for (index, archive) in archives.iter().enumerate() {
+        if let Some(delta) = archive.get_package_contents()?.delta.as_ref() {
+            let previous = archives[..index]
+                .iter()
+                .map(|previous| previous.get_package_contents())
+                .collect::<io::Result<Vec<_>>>()?;
+            delta.check_packages(previous)?;
+        }
+    }
+    Ok(None)
+}
+
+impl Reencoder {
+    /// Re-encode the package entries with `options`.
+    pub fn new(options: PackageContentsOptions) -> Self {
+        Self {
+            options,
+            ..Default::default()
+        }
+    }
+    /// Only keep the mangas, covers, chapters and chapter images listed in `content`.
+    ///
+    /// Every entry of the package is kept by default.
+    pub fn set_content(mut self, content: Option<PackageContents>) -> Self {
+        self.contents = content;
+        self
+    }
+    /// Transcode the images with `profile`.
+    ///
+    /// The filenames in the package contents are rewritten with the new extension.
+    pub fn set_image_profile(mut self, profile: Option<ImageProfile>) -> Self {
+        self.image_profile = profile;
+        self
+    }
+    pub fn get_image_profile(&self) -> Option<&ImageProfile> {
+        self.image_profile.as_ref()
+    }
+    pub fn set_tar_header_mode(mut self, mode: HeaderMode) -> Self {
+        self.header_mode = mode;
+        self
+    }
+    pub fn get_options(&self) -> &PackageContentsOptions {
+        &self.options
+    }
+    pub fn set_compression_level(&mut self, compression_level: i32) {
+        self.compression_level = compression_level;
+    }
+    pub fn get_compression_level(&self) -> i32 {
+        self.compression_level
+    }
+    /// Set how many zstd threads compress each package frame, `0` (the default) disables it.
+    pub fn set_zstd_workers(&mut self, zstd_workers: u32) {
+        self.zstd_workers = zstd_workers;
+    }
+    pub fn get_zstd_workers(&self) -> u32 {
+        self.zstd_workers
+    }
+    /// Enable the zstd long distance matching for the package frames.
+    pub fn set_long_distance_matching(&mut self, long_distance_matching: bool) {
+        self.long_distance_matching = long_distance_matching;
+    }
+    pub fn get_long_distance_matching(&self) -> bool {
+        self.long_distance_matching
+    }
+    /// Stream the `archive` entries into a new package written to `writer`.
+    ///
+    /// Returns the new package contents.
+    pub fn reencode<R, W>(
+        self,
+        archive: &mut Archive<'_, R>,
+        writer: W,
+    ) -> ThisResult<PackageContents>
+    where
+        R: BufRead + Seek,
+        W: Write,
+    {
+        self.merge(slice::from_mut(archive), writer)
+    }
+    /// Stream the entries of every archive into a new package written to `writer`.
+    ///
+    /// An entry found in several packages is only written once, from the last package,
+    /// and the manga, chapter and cover metadata with the highest `attributes.version`
+    /// (or the latest `attributes.updated_at`) is kept.
+    /// The metadata is written after the images, once every package has been read.
+    ///
+    /// A delta package is applied onto its base, which must be given before it.
+    /// A single delta package is re-encoded as a delta package.
+    ///
+    /// Without a content set with [`Self::set_content`], everything in the packages is kept.
+    /// Returns the new package contents.
+    pub fn merge<R, W>(
+        mut self,
+        archives: &mut [Archive<'_, R>],
+        writer: W,
+    ) -> ThisResult<PackageContents>
+    where
+        R: BufRead + Seek,
+        W: Write,
+    {
+        let mut contents = match self.contents {
+            Some(contents) => contents,
+            None => {
+                let mut merged = PackageContents::default();
+                for archive in archives.iter() {
+                    merged.merge(archive.get_package_contents()?.clone());
+                }
+                merged
+            }
+        };
+        self.options
+            .set_entry_compression_level(self.compression_level);
+        contents.options = Some(self.options.clone());
+        contents.volume = None;
+        contents.delta = merged_delta(archives)?;
+        let dirs = DirsOptions::default();
+        let preparer = EntryPreparer {
+            dir_options: Default::default(),
+            default_dir_options: Default::default(),
+            options: self.options,
+            compression_level: self.compression_level,
+            image_profile: self.image_profile,
+        };
+        let mut writer = EntryWriter::new(
+            writer,
+            PartSettings {
+                compression_level: self.compression_level,
+                zstd_workers: self.zstd_workers,
+                long_distance_matching: self.long_distance_matching,
+                header_mode: self.header_mode,
+            },
+        );
+        let mut metadata = NewestMetadata::default();
+        let mut cover_images = HashMap::<String, File>::new();
+        let mut written = HashSet::<PathBuf>::new();
+        // the last packages first, so their entries replace the ones of the previous packages
+        for archive in archives.iter_mut().rev() {
+            for entry in archive.any_pull(true)? {
+                match entry? {
+                    PossibleEntryData::Manga(manga) => metadata.insert_manga(*manga),
+                    PossibleEntryData::Chapter(chapter) => metadata.insert_chapter(*chapter),
+                    PossibleEntryData::Cover(cover) => metadata.insert_cover(*cover),
+                    PossibleEntryData::CoverImage { filename, file } => {
+                        cover_images.entry(filename).or_insert(file);
+                    }
+                    PossibleEntryData::ChapterImage {
+                        filename,
+                        file,
+                        chapter,
+                        mode,
+                    } => {
+                        let images_dir = dirs.chapter_images_dir(chapter, mode);
+                        if !written.insert(images_dir.join(&filename)) {
+                            continue;
+                        }
+                        let Some(filenames) = chapter_images_mut(&mut contents, chapter, mode)
+                        else {
+                            continue;
+                        };
+                        let Some(index) = filenames.iter().position(|name| *name == filename)
+                        else {
+                            continue;
+                        };
+                        let mut new_filename = filename;
+                        let image = preparer.encode_image(file, &mut new_filename)?;
+                        writer.append(PreparedEntry::new(images_dir.join(&new_filename), image)?)?;
+                        filenames[index] = new_filename;
+                    }
+                    PossibleEntryData::Any { tar_path, file } => {
+                        if tar_path != AsRef::<Path>::as_ref(CONTENTS_FILENAME)
+                            && written.insert(tar_path.clone())
+                        {
+                            writer.append(PreparedEntry::new(tar_path, file)?)?;
+                        }
+                    }
+                }
+            }
+        }
+        for (id, manga) in metadata.mangas {
+            if contents.data.contains_key(&id) {
+                let path = dirs.mangas_add(format!("{id}.cbor"));
+                writer.append(preparer.metadata_entry(path, &manga)?)?;
+            }
+        }
+        for (id, chapter) in metadata.chapters {
+            if contents
+                .data
+                .values()
+                .any(|manga_data| manga_data.chapters.contains_key(&id))
+            {
+                let path = dirs.chapters_add(format!("{id}/{CHAPTER_CONTENT_FILE}"));
+                writer.append(preparer.metadata_entry(path, &chapter)?)?;
+            }
+        }
+        for (id, mut cover) in metadata.covers {
+            if !contents
+                .data
+                .values()
+                .any(|manga_data| manga_data.covers.contains(&id))
+            {
+                continue;
+            }
+            let filename = &mut cover.attributes.file_name;
+            if let Some(file) = cover_images.remove(&*filename) {
+                let image = preparer.encode_image(file, filename)?;
+                writer.append(PreparedEntry::new(dirs.cover_images_add(&*filename), image)?)?;
+            }
+            let path = dirs.covers_add(format!("{id}.cbor"));
+            writer.append(preparer.metadata_entry(path, &cover)?)?;
+        }
+        writer.finish_with_contents(&mut contents)?.flush()?;
+        Ok(contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read};
+
+    use api_core::{data_push::chapter::image::Mode, DirsOptions};
+    use uuid::Uuid;
+
+    use super::Reencoder;
+    use crate::{
+        contents::options::PackageContentsOptions,
+        test_utils::{chapter_contents, linear, package_with},
+        Archive, PChapterObject,
+    };
+
+    #[test]
+    fn reencode_with_compressed_images_and_without_data_saver() {
+        let chapter = Uuid::new_v4();
+        let contents = chapter_contents(
+            Uuid::new_v4(),
+            chapter,
+            PChapterObject {
+                data: vec!["1.png".into()],
+                data_saver: vec!["1.jpg".into()],
+            },
+        );
+        let dirs = DirsOptions::default();
+        let package = linear(&package_with(
+            &contents,
+            [
+                (
+                    dirs.chapter_images_dir(chapter, Mode::Data).join("1.png"),
+                    "page",
+                ),
+                (
+                    dirs.chapter_images_dir(chapter, Mode::DataSaver)
+                        .join("1.jpg"),
+                    "small page",
+                ),
+            ],
+        ));
+        let mut archive = Archive::from_reader(Cursor::new(package)).unwrap();
+
+        let mut filter = contents.clone();
+        for manga_data in filter.data.values_mut() {
+            for images in manga_data.chapters.values_mut() {
+                images.data_saver.clear();
+            }
+        }
+        let options = PackageContentsOptions {
+            zstd_compressed_images: true,
+            ..Default::default()
+        };
+        let mut reencoded = Vec::new();
+        let new_contents = Reencoder::new(options)
+            .set_content(Some(filter))
+            .reencode(&mut archive, &mut reencoded)
+            .unwrap();
+        assert!(new_contents.get_options().zstd_compressed_images);
+
+        let mut archive = Archive::from_reader(Cursor::new(reencoded)).unwrap();
+        assert!(archive.get_table_of_contents().is_some());
+        let images = archive
+            .get_package_contents()
+            .unwrap()
+            .data
+            .values()
+            .flat_map(|manga_data| manga_data.chapters.values())
+            .next()
+            .cloned()
+            .unwrap();
+        assert_eq!(images.data, ["1.png"]);
+        assert!(images.data_saver.is_empty());
+
+        let mut pages = archive.read_chapter(chapter, Mode::Data).unwrap();
+        let mut page = String::new();
+        pages
+            .next_page()
+            .unwrap()
+            .unwrap()
+            .read_to_string(&mut page)
+            .unwrap();
+        assert_eq!(page, "page");
+    }
+}
@@ -0,0 +1,284 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, Read, Seek, Write},
+};
+
+use api_core::data_push::chapter::image::Mode as ChapterImagesMode;
+use mangadex_api_schema_rust::v5::{ChapterObject, CoverObject, MangaObject};
+use mangadex_api_types_rust::RelationshipType;
+use tar::HeaderMode;
+use tempfile::tempfile;
+use uuid::Uuid;
+
+use super::{
+    inner::{EntryPreparer, EntryWriter, PartSettings, PreparedEntry},
+    ImageProfile,
+};
+use crate::{
+    constants::CHAPTER_CONTENT_FILE, contents::options::PackageContentsOptions, PChapterObject,
+    PackageContents, ThisResult,
+};
+
+/// Writes a package from metadata values and image readers,
+/// so any source (a download pipeline, another storage backend...) can be packaged
+/// without writing a data directory first.
+///
+/// The settings are the same as the [`Builder`](super::Builder) ones,
+/// [`Self::writer`] starts the package.
+#[derive(Debug, Clone)]
+pub struct StreamBuilder {
+    options: PackageContentsOptions,
+    compression_level: i32,
+    image_profile: Option<ImageProfile>,
+    header_mode: HeaderMode,
+    zstd_workers: u32,
+    long_distance_matching: bool,
+}
+
+impl Default for StreamBuilder {
+    fn default() -> Self {
+        Self {
+            options: Default::default(),
+            compression_level: Default::default(),
+            image_profile: None,
+            header_mode: HeaderMode::Complete,
+            zstd_workers: 0,
+            long_distance_matching: false,
+        }
+    }
+}
+
+impl StreamBuilder {
+    /// Encode the package entries with `options`.
+    pub fn new(options: PackageContentsOptions) -> Self {
+        Self {
+            options,
+            ..Default::default()
+        }
+    }
+    /// Transcode the images with `profile`.
+    ///
+    /// The filenames in the package contents are rewritten with the new extension.
+    pub fn set_image_profile(mut self, profile: Option<ImageProfile>) -> Self {
+        self.image_profile = profile;
+        self
+    }
+    pub fn get_image_profile(&self) -> Option<&ImageProfile> {
+        self.image_profile.as_ref()
+    }
+    pub fn set_tar_header_mode(mut self, mode: HeaderMode) -> Self {
+        self.header_mode = mode;
+        self
+    }
+    pub fn get_options(&self) -> &PackageContentsOptions {
+        &self.options
+    }
+    pub fn set_compression_level(&mut self, compression_level: i32) {
+        self.compression_level = compression_level;
+    }
+    pub fn get_compression_level(&self) -> i32 {
+        self.compression_level
+    }
+    /// Set how many zstd threads compress each package frame, `0` (the default) disables it.
+    pub fn set_zstd_workers(&mut self, zstd_workers: u32) {
+        self.zstd_workers = zstd_workers;
+    }
+    pub fn get_zstd_workers(&self) -> u32 {
+        self.zstd_workers
+    }
+    /// Enable the zstd long distance matching for the package frames.
+    pub fn set_long_distance_matching(&mut self, long_distance_matching: bool) {
+        self.long_distance_matching = long_distance_matching;
+    }
+    pub fn get_long_distance_matching(&self) -> bool {
+        self.long_distance_matching
+    }
+    /// Start the package written to `writer`.
+    pub fn writer<W: Write>(mut self, writer: W) -> StreamWriter<W> {
+        self.options
+            .set_entry_compression_level(self.compression_level);
+        StreamWriter {
+            writer: EntryWriter::new(
+                writer,
+                PartSettings {
+                    compression_level: self.compression_level,
+                    zstd_workers: self.zstd_workers,
+                    long_distance_matching: self.long_distance_matching,
+                    header_mode: self.header_mode,
+                },
+            ),
+            preparer: EntryPreparer {
+                dir_options: Default::default(),
+                default_dir_options: Default::default(),
+                options: self.options.clone(),
+                compression_level: self.compression_level,
+                image_profile: self.image_profile,
+            },
+            contents: PackageContents {
+                options: Some(self.options),
+                ..Default::default()
+            },
+            chapter_images: HashMap::new(),
+        }
+    }
+}
+
+/// Copy `image` into a temporary file, so it can be transcoded and compressed.
+fn image_file<R: Read>(mut image: R) -> io::Result<File> {
+    let mut file = tempfile()?;
+    io::copy(&mut image, &mut file)?;
+    file.rewind()?;
+    Ok(file)
+}
+
+fn missing_manga_error() -> api_core::Error {
+    api_core::Error::MissingRelationships(vec![RelationshipType::Manga])
+}
+
+/// A package being written by a [`StreamBuilder`].
+///
+/// Every entry is written as soon as it's added, in any order,
+/// but every chapter image must be added with its chapter before [`Self::finish`].
+pub struct StreamWriter<W>
+where
+    W: Write,
+{
+    writer: EntryWriter<W>,
+    preparer: EntryPreparer,
+    contents: PackageContents,
+    /// The images of every chapter, attached to their manga on finish
+    chapter_images: HashMap<Uuid, PChapterObject>,
+}
+
+impl<W> StreamWriter<W>
+where
+    W: Write,
+{
+    pub fn add_manga(&mut self, manga: &MangaObject) -> ThisResult<()> {
+        let path = self
+            .preparer
+            .default_dir_options
+            .mangas_add(format!("{}.cbor", manga.id));
+        self.writer
+            .append(self.preparer.metadata_entry(path, manga)?)?;
+        self.contents.data.entry(manga.id).or_default();
+        Ok(())
+    }
+    /// Add a cover with its `image`, which is transcoded if an [`ImageProfile`] is set.
+    pub fn add_cover<R: Read>(&mut self, cover: &CoverObject, image: R) -> ThisResult<()> {
+        let manga = cover
+            .find_first_relationships(RelationshipType::Manga)
+            .ok_or_else(missing_manga_error)?
+            .id;
+        let mut cover = cover.clone();
+        let dirs = &self.preparer.default_dir_options;
+        let filename = &mut cover.attributes.file_name;
+        let image = self.preparer.encode_image(image_file(image)?, filename)?;
+        self.writer
+            .append(PreparedEntry::new(dirs.cover_images_add(&*filename), image)?)?;
+        let path = dirs.covers_add(format!("{}.cbor", cover.id));
+        self.writer
+            .append(self.preparer.metadata_entry(path, &cover)?)?;
+        let covers = &mut self.contents.data.entry(manga).or_default().covers;
+        if !covers.contains(&cover.id) {
+            covers.push(cover.id);
+        }
+        Ok(())
+    }
+    pub fn add_chapter(&mut self, chapter: &ChapterObject) -> ThisResult<()> {
+        let manga = chapter
+            .find_first_relationships(RelationshipType::Manga)
+            .ok_or_else(missing_manga_error)?
+            .id;
+        let path = self
+            .preparer
+            .default_dir_options
+            .chapters_add(format!("{}/{CHAPTER_CONTENT_FILE}", chapter.id));
+        self.writer
+            .append(self.preparer.metadata_entry(path, chapter)?)?;
+        self.contents
+            .data
+            .entry(manga)
+            .or_default()
+            .chapters
+            .entry(chapter.id)
+            .or_default();
+        Ok(())
+    }
+    /// Add a chapter image, which is transcoded if an [`ImageProfile`] is set.
+    ///
+    /// Returns the image filename in the package.
+    pub fn add_chapter_image<R: Read>(
+        &mut self,
+        chapter: Uuid,
+        mode: ChapterImagesMode,
+        filename: &str,
+        image: R,
+    ) -> ThisResult<String> {
+        let mut filename = filename.to_string();
+        let image = self
+            .preparer
+            .encode_image(image_file(image)?, &mut filename)?;
+        let path = self
+            .preparer
+            .default_dir_options
+            .chapter_images_dir(chapter, mode)
+            .join(&filename);
+        self.writer.append(PreparedEntry::new(path, image)?)?;
+        let images = self.chapter_images.entry(chapter).or_default();
+        match mode {
+            ChapterImagesMode::Data => images.data.push(filename.clone()),
+            ChapterImagesMode::DataSaver => images.data_saver.push(filename.clone()),
+        }
+        Ok(filename)
+    }
+    /// The contents of the entries added so far, without the chapter images.
+    pub fn get_package_contents(&self) -> &PackageContents {
+        &self.contents
+    }
+    /// Write the package `contents.cbor` and finish the package.
+    ///
+    /// Fails if a chapter image has been added without its chapter.
+    /// Returns the package contents.
+    pub fn finish(mut self) -> ThisResult<PackageContents> {
+        for (chapter, images) in self.chapter_images {
+            let chapter_images = self
+                .contents
+                .data
+                .values_mut()
+                .find_map(|manga_data| manga_data.chapters.get_mut(&chapter))
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("the images of the chapter {chapter} are added without it"),
+                    )
+                })?;
+            *chapter_images = images;
+        }
+        self.writer
+            .finish_with_contents(&mut self.contents)?
+            .flush()?;
+        Ok(self.contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use api_core::data_push::chapter::image::Mode;
+    use uuid::Uuid;
+
+    use super::StreamBuilder;
+
+    #[test]
+    fn chapter_images_need_their_chapter() {
+        let mut package = Vec::new();
+        let mut writer = StreamBuilder::default().writer(&mut package);
+        let filename = writer
+            .add_chapter_image(Uuid::new_v4(), Mode::Data, "1.png", "page".as_bytes())
+            .unwrap();
+        assert_eq!(filename, "1.png");
+        assert!(writer.get_package_contents().data.is_empty());
+        assert!(writer.finish().is_err());
+    }
+}
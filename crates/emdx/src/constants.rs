@@ -1,3 +1,6 @@
+/// The current package layout version, recorded in the package manifest.
+pub const PACKAGE_FORMAT_VERSION: u32 = 1;
+
 pub const CONTENTS_FILENAME: &str = "contents.cbor";
 
 pub const CHAPTER_CONTENT_FILE: &str = "data.cbor";
@@ -1,3 +1,6 @@
+pub mod delta;
+pub mod diff;
+pub mod manifest;
 pub mod options;
 
 use std::{
@@ -12,10 +15,14 @@ use api_core::{
 };
 use mangadex_api_schema_rust::v5::ChapterObject;
 use mangadex_api_types_rust::RelationshipType;
+use delta::PackageDelta;
+use manifest::PackageManifest;
 use options::PackageContentsOptions;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::{utils::serialize_sorted_map, volume::PackageVolume};
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct PChapterObject {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
@@ -45,6 +52,7 @@ impl From<ChapterImagesData> for PChapterObject {
 #[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct PMangaObject {
     pub covers: Vec<Uuid>,
+    #[serde(serialize_with = "serialize_sorted_map")]
     pub chapters: HashMap<Uuid, PChapterObject>,
 }
 
@@ -52,6 +60,15 @@ pub struct PMangaObject {
 pub struct PackageContents {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub options: Option<PackageContentsOptions>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<PackageManifest>,
+    /// Set on the parts of a split package, see [`crate::volume`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume: Option<PackageVolume>,
+    /// Set on the delta packages, see [`PackageDelta`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delta: Option<PackageDelta>,
+    #[serde(serialize_with = "serialize_sorted_map")]
     pub data: HashMap<Uuid, PMangaObject>,
 }
 
@@ -59,6 +76,40 @@ impl PackageContents {
     pub fn get_options(&self) -> Cow<'_, PackageContentsOptions> {
         self.options.as_ref().map(Cow::Borrowed).unwrap_or_default()
     }
+    /// The layout version of the package, `0` for the packages written before the manifest.
+    pub fn get_format_version(&self) -> u32 {
+        self.manifest
+            .as_ref()
+            .map(|manifest| manifest.format_version)
+            .unwrap_or_default()
+    }
+    /// Add the mangas, covers, chapters and chapter images of `other`.
+    ///
+    /// Unlike [`Add`], the image lists of a chapter in both contents are merged.
+    pub fn merge(&mut self, other: PackageContents) {
+        for (manga_id, manga_data) in other.data {
+            let merged = self.data.entry(manga_id).or_default();
+            for cover in manga_data.covers {
+                if !merged.covers.contains(&cover) {
+                    merged.covers.push(cover);
+                }
+            }
+            for (chapter_id, images) in manga_data.chapters {
+                let merged_images = merged.chapters.entry(chapter_id).or_default();
+                let modes = [
+                    (&mut merged_images.data, images.data),
+                    (&mut merged_images.data_saver, images.data_saver),
+                ];
+                for (merged_filenames, filenames) in modes {
+                    for filename in filenames {
+                        if !merged_filenames.contains(&filename) {
+                            merged_filenames.push(filename);
+                        }
+                    }
+                }
+            }
+        }
+    }
 }
 
 impl TryFrom<&DirsOptions> for PackageContents {
@@ -105,6 +156,9 @@ impl TryFrom<&DirsOptions> for PackageContents {
         );
         Ok(Self {
             options: None,
+            manifest: None,
+            volume: None,
+            delta: None,
             data,
         })
     }
@@ -0,0 +1,155 @@
+use std::{collections::HashMap, io};
+
+use api_core::{data_push::chapter::image::Mode, DirsOptions};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{PMangaObject, PackageContents};
+use crate::utils::serialize_sorted_map;
+
+/// Set on a delta package, which only has the entries that are new or changed since its base.
+///
+/// See [`PackageBuilder::set_base`](crate::PackageBuilder::set_base).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageDelta {
+    /// The [`PackageManifest::digest`](super::manifest::PackageManifest::digest)
+    /// of the base package
+    pub base_manifest: [u8; 32],
+    /// The mangas, covers, chapters and chapter images of the base package
+    #[serde(serialize_with = "serialize_sorted_map")]
+    pub base: HashMap<Uuid, PMangaObject>,
+}
+
+fn base_missing_error(missing: String) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("the base of this delta package is missing: {missing} is not found"),
+    )
+}
+
+impl PackageDelta {
+    /// A delta against `base`, which must have a manifest.
+    pub fn new(base: &PackageContents) -> io::Result<Self> {
+        let manifest = base.manifest.as_ref().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the base package has no manifest",
+            )
+        })?;
+        Ok(Self {
+            base_manifest: manifest.digest(),
+            base: base.data.clone(),
+        })
+    }
+    pub fn get_base_manifest_hex(&self) -> String {
+        self.base_manifest
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+    /// `true` if `contents` are the contents of the base package.
+    pub fn is_base(&self, contents: &PackageContents) -> bool {
+        contents
+            .manifest
+            .as_ref()
+            .is_some_and(|manifest| manifest.digest() == self.base_manifest)
+    }
+    /// The first entry of the base package that isn't in `dirs`.
+    fn find_missing(&self, dirs: &DirsOptions) -> Option<String> {
+        for (manga_id, manga_data) in &self.base {
+            if dirs.manga_file(*manga_id).is_none() {
+                return Some(format!("the manga {manga_id}"));
+            }
+            for cover_id in &manga_data.covers {
+                if dirs.cover_file(*cover_id).is_none() {
+                    return Some(format!("the cover {cover_id}"));
+                }
+            }
+            for (chapter_id, images) in &manga_data.chapters {
+                if dirs.chapter_file(*chapter_id).is_none() {
+                    return Some(format!("the chapter {chapter_id}"));
+                }
+                let modes = [
+                    (Mode::Data, &images.data),
+                    (Mode::DataSaver, &images.data_saver),
+                ];
+                for (mode, filenames) in modes {
+                    let images_dir = dirs.chapter_images_dir(*chapter_id, mode);
+                    if let Some(filename) = filenames
+                        .iter()
+                        .find(|filename| !images_dir.join(filename).exists())
+                    {
+                        return Some(format!("the image {filename} of the chapter {chapter_id}"));
+                    }
+                }
+            }
+        }
+        None
+    }
+    /// Check that every manga, cover, chapter and chapter image of the base package
+    /// is in the library at `dirs`.
+    pub fn check_library(&self, dirs: &DirsOptions) -> io::Result<()> {
+        match self.find_missing(dirs) {
+            Some(missing) => Err(base_missing_error(missing)),
+            None => Ok(()),
+        }
+    }
+    /// Check that one of `packages` is the base package.
+    pub fn check_packages<'a, I>(&self, packages: I) -> io::Result<()>
+    where
+        I: IntoIterator<Item = &'a PackageContents>,
+    {
+        if packages.into_iter().any(|contents| self.is_base(contents)) {
+            Ok(())
+        } else {
+            Err(base_missing_error(format!(
+                "the package with the manifest {}",
+                self.get_base_manifest_hex()
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use api_core::DirsOptions;
+    use uuid::Uuid;
+
+    use super::PackageDelta;
+    use crate::{
+        contents::manifest::{ManifestEntry, PackageManifest},
+        PMangaObject, PackageContents,
+    };
+
+    fn contents(entries: &[(&str, &str)]) -> PackageContents {
+        let mut manifest = PackageManifest::new();
+        for (path, data) in entries {
+            manifest.insert(*path, ManifestEntry::from_reader(data.as_bytes()).unwrap());
+        }
+        let mut contents = PackageContents {
+            manifest: Some(manifest),
+            ..Default::default()
+        };
+        contents.data.insert(Uuid::new_v4(), PMangaObject::default());
+        contents
+    }
+
+    #[test]
+    fn a_delta_refers_to_its_base_entries() {
+        let base = contents(&[("a.cbor", "a"), ("b.cbor", "b")]);
+        let delta = PackageDelta::new(&base).unwrap();
+        assert!(delta.is_base(&base));
+
+        let mut rebuilt = contents(&[("b.cbor", "b"), ("a.cbor", "a")]);
+        rebuilt.manifest.as_mut().unwrap().created_at = None;
+        assert!(delta.check_packages([&rebuilt]).is_ok());
+
+        let changed = contents(&[("a.cbor", "a"), ("b.cbor", "changed")]);
+        assert!(delta.check_packages([&changed]).is_err());
+        assert!(PackageDelta::new(&PackageContents::default()).is_err());
+
+        let library = tempfile::tempdir().unwrap();
+        let dirs = DirsOptions::new_from_data_dir(library.path());
+        assert!(delta.check_library(&dirs).is_err());
+    }
+}
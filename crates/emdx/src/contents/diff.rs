@@ -0,0 +1,216 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::PathBuf,
+};
+
+use uuid::Uuid;
+
+use super::{PChapterObject, PackageContents};
+
+/// The image filenames added to or removed from a chapter image list.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImagesDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl ImagesDiff {
+    fn new(from: &[String], to: &[String]) -> Self {
+        Self {
+            added: to
+                .iter()
+                .filter(|name| !from.contains(name))
+                .cloned()
+                .collect(),
+            removed: from
+                .iter()
+                .filter(|name| !to.contains(name))
+                .cloned()
+                .collect(),
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// A chapter in both packages, with different images.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChapterDiff {
+    pub id: Uuid,
+    pub data: ImagesDiff,
+    pub data_saver: ImagesDiff,
+}
+
+/// What changes from a package to another, made by [`PackageContents::diff`].
+///
+/// Every list is sorted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PackageDiff {
+    pub added_mangas: Vec<Uuid>,
+    pub removed_mangas: Vec<Uuid>,
+    pub added_covers: Vec<Uuid>,
+    pub removed_covers: Vec<Uuid>,
+    pub added_chapters: Vec<Uuid>,
+    pub removed_chapters: Vec<Uuid>,
+    pub changed_chapters: Vec<ChapterDiff>,
+    /// The entries in both manifests with a different checksum, like an updated metadata
+    pub changed_entries: Vec<PathBuf>,
+}
+
+impl PackageDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_mangas.is_empty()
+            && self.removed_mangas.is_empty()
+            && self.added_covers.is_empty()
+            && self.removed_covers.is_empty()
+            && self.added_chapters.is_empty()
+            && self.removed_chapters.is_empty()
+            && self.changed_chapters.is_empty()
+            && self.changed_entries.is_empty()
+    }
+}
+
+/// The ids in `to` but not in `from`, and the ids in `from` but not in `to`.
+fn added_and_removed(from: &BTreeSet<Uuid>, to: &BTreeSet<Uuid>) -> (Vec<Uuid>, Vec<Uuid>) {
+    (
+        to.difference(from).copied().collect(),
+        from.difference(to).copied().collect(),
+    )
+}
+
+impl PackageContents {
+    fn covers(&self) -> BTreeSet<Uuid> {
+        self.data
+            .values()
+            .flat_map(|manga_data| manga_data.covers.iter().copied())
+            .collect()
+    }
+    fn chapters(&self) -> HashMap<Uuid, &PChapterObject> {
+        self.data
+            .values()
+            .flat_map(|manga_data| manga_data.chapters.iter())
+            .map(|(id, images)| (*id, images))
+            .collect()
+    }
+    /// Compare the mangas, covers, chapters and chapter images of `self` to `other`.
+    ///
+    /// The changed entries are only found if both contents have a manifest.
+    pub fn diff(&self, other: &PackageContents) -> PackageDiff {
+        let (added_mangas, removed_mangas) = added_and_removed(
+            &self.data.keys().copied().collect(),
+            &other.data.keys().copied().collect(),
+        );
+        let (added_covers, removed_covers) = added_and_removed(&self.covers(), &other.covers());
+        let (chapters, other_chapters) = (self.chapters(), other.chapters());
+        let (added_chapters, removed_chapters) = added_and_removed(
+            &chapters.keys().copied().collect(),
+            &other_chapters.keys().copied().collect(),
+        );
+        let mut changed_chapters = chapters
+            .iter()
+            .filter_map(|(id, images)| {
+                let other_images = other_chapters.get(id)?;
+                let data = ImagesDiff::new(&images.data, &other_images.data);
+                let data_saver = ImagesDiff::new(&images.data_saver, &other_images.data_saver);
+                (!data.is_empty() || !data_saver.is_empty()).then_some(ChapterDiff {
+                    id: *id,
+                    data,
+                    data_saver,
+                })
+            })
+            .collect::<Vec<_>>();
+        changed_chapters.sort_by_key(|chapter| chapter.id);
+        let mut changed_entries = match (self.manifest.as_ref(), other.manifest.as_ref()) {
+            (Some(manifest), Some(other_manifest)) => manifest
+                .entries
+                .iter()
+                .filter(|(path, entry)| {
+                    other_manifest
+                        .get(path)
+                        .is_some_and(|other_entry| other_entry != *entry)
+                })
+                .map(|(path, _)| path.clone())
+                .collect(),
+            _ => Vec::new(),
+        };
+        changed_entries.sort();
+        PackageDiff {
+            added_mangas,
+            removed_mangas,
+            added_covers,
+            removed_covers,
+            added_chapters,
+            removed_chapters,
+            changed_chapters,
+            changed_entries,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::{ChapterDiff, ImagesDiff};
+    use crate::{PChapterObject, PMangaObject, PackageContents};
+
+    fn contents(manga: Uuid, chapters: &[(Uuid, Vec<&str>)]) -> PackageContents {
+        let mut contents = PackageContents::default();
+        contents.data.insert(
+            manga,
+            PMangaObject {
+                covers: Vec::new(),
+                chapters: chapters
+                    .iter()
+                    .map(|(id, images)| {
+                        let images = PChapterObject {
+                            data: images.iter().map(|image| image.to_string()).collect(),
+                            data_saver: Vec::new(),
+                        };
+                        (*id, images)
+                    })
+                    .collect(),
+            },
+        );
+        contents
+    }
+
+    #[test]
+    fn diff_and_merge_weekly_updates() {
+        let (manga, first, second, third) = (
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+        );
+        let last_week = contents(manga, &[(first, vec!["1.png"]), (second, vec!["1.png"])]);
+        let this_week = contents(
+            manga,
+            &[(first, vec!["1.png", "2.png"]), (third, vec!["1.png"])],
+        );
+
+        let diff = last_week.diff(&this_week);
+        assert!(diff.added_mangas.is_empty() && diff.removed_mangas.is_empty());
+        assert_eq!(diff.added_chapters, [third]);
+        assert_eq!(diff.removed_chapters, [second]);
+        assert_eq!(
+            diff.changed_chapters,
+            [ChapterDiff {
+                id: first,
+                data: ImagesDiff {
+                    added: vec!["2.png".into()],
+                    removed: Vec::new(),
+                },
+                data_saver: Default::default(),
+            }]
+        );
+
+        let mut merged = last_week.clone();
+        merged.merge(this_week.clone());
+        assert_eq!(merged.data[&manga].chapters.len(), 3);
+        assert_eq!(merged.data[&manga].chapters[&first].data, ["1.png", "2.png"]);
+        assert!(last_week.diff(&last_week).is_empty());
+        assert_eq!(merged.diff(&this_week).removed_chapters, [second]);
+    }
+}
@@ -0,0 +1,94 @@
+use std::{
+    collections::HashMap,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{constants::PACKAGE_FORMAT_VERSION, utils::serialize_sorted_map};
+
+/// The byte length and checksum of a package entry, as stored in the tar archive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub size: u64,
+    pub sha256: [u8; 32],
+}
+
+impl ManifestEntry {
+    /// Read `reader` to the end and compute its entry.
+    pub fn from_reader<R: Read>(mut reader: R) -> io::Result<Self> {
+        let mut hasher = Sha256::new();
+        let size = io::copy(&mut reader, &mut hasher)?;
+        Ok(Self {
+            size,
+            sha256: hasher.finalize().into(),
+        })
+    }
+    pub fn get_sha256_hex(&self) -> String {
+        self.sha256.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+}
+
+/// What a package is made of, written by the [`PackageBuilder`](crate::PackageBuilder).
+///
+/// Every entry except `contents.cbor` itself is listed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackageManifest {
+    pub format_version: u32,
+    /// The package creation time, in seconds since the Unix epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub producer: Option<String>,
+    #[serde(serialize_with = "serialize_sorted_map")]
+    pub entries: HashMap<PathBuf, ManifestEntry>,
+}
+
+impl PackageManifest {
+    pub fn new() -> Self {
+        Self {
+            format_version: PACKAGE_FORMAT_VERSION,
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .map(|since| since.as_secs()),
+            producer: Some(concat!("emdx ", env!("CARGO_PKG_VERSION")).into()),
+            entries: Default::default(),
+        }
+    }
+    pub fn get<P: AsRef<Path>>(&self, path: P) -> Option<&ManifestEntry> {
+        self.entries.get(path.as_ref())
+    }
+    pub fn insert<P: Into<PathBuf>>(
+        &mut self,
+        path: P,
+        entry: ManifestEntry,
+    ) -> Option<ManifestEntry> {
+        self.entries.insert(path.into(), entry)
+    }
+    /// The checksum of the entries paths, sizes and checksums.
+    ///
+    /// Two packages with the same entries have the same digest,
+    /// whatever their creation time, producer or parts.
+    pub fn digest(&self) -> [u8; 32] {
+        let mut entries = self.entries.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|(path, _)| *path);
+        let mut hasher = Sha256::new();
+        for (path, entry) in entries {
+            hasher.update(path.to_string_lossy().as_bytes());
+            hasher.update([0]);
+            hasher.update(entry.size.to_le_bytes());
+            hasher.update(entry.sha256);
+        }
+        hasher.finalize().into()
+    }
+}
+
+impl Default for PackageManifest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
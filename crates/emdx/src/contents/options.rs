@@ -1,7 +1,75 @@
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    fmt,
+    io::{self, BufReader, Read, Write},
+    ops::Deref,
+};
 
 use api_core::DirsOptions;
-use serde::{Deserialize, Serialize};
+use serde::{
+    de::{SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use zstd::{Decoder, Encoder};
+
+/// A zstd dictionary, serialized as a byte string.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct ZstdDictionary(pub Vec<u8>);
+
+impl fmt::Debug for ZstdDictionary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ZstdDictionary")
+            .field(&format_args!("{} bytes", self.0.len()))
+            .finish()
+    }
+}
+
+impl Deref for ZstdDictionary {
+    type Target = [u8];
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for ZstdDictionary {
+    fn from(value: Vec<u8>) -> Self {
+        Self(value)
+    }
+}
+
+impl Serialize for ZstdDictionary {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+struct ZstdDictionaryVisitor;
+
+impl<'de> Visitor<'de> for ZstdDictionaryVisitor {
+    type Value = ZstdDictionary;
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a zstd dictionary byte string")
+    }
+    fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(ZstdDictionary(v.to_vec()))
+    }
+    fn visit_byte_buf<E: serde::de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(ZstdDictionary(v))
+    }
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        Ok(ZstdDictionary(bytes))
+    }
+}
+
+impl<'de> Deserialize<'de> for ZstdDictionary {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_byte_buf(ZstdDictionaryVisitor)
+    }
+}
 
 #[derive(Debug, Deserialize, Serialize, Clone, Default)]
 pub struct PackageContentsOptions {
@@ -9,6 +77,12 @@ pub struct PackageContentsOptions {
     pub directories: Option<DirsOptions>,
     pub zstd_compressed_images: bool,
     pub zstd_compressed_metadata: bool,
+    /// The dictionary used to compress the metadata and images entries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dictionary: Option<ZstdDictionary>,
+    /// The zstd level of the compressed entries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression_level: Option<i32>,
 }
 
 impl PackageContentsOptions {
@@ -18,6 +92,44 @@ impl PackageContentsOptions {
             .map(Cow::Borrowed)
             .unwrap_or_default()
     }
+    /// Record the `compression_level` of the entries, if they are zstd compressed.
+    pub fn set_entry_compression_level(&mut self, compression_level: i32) {
+        if self.zstd_compressed_images || self.zstd_compressed_metadata {
+            self.compression_level = Some(compression_level);
+        }
+    }
+    /// `true` if the entries are encoded the same way as with `other`,
+    /// so the same data gives the same [manifest](super::manifest::PackageManifest) checksums.
+    ///
+    /// The compression levels are only compared if they are both known.
+    pub fn same_entry_encoding(&self, other: &Self) -> bool {
+        let compressed = self.zstd_compressed_images || self.zstd_compressed_metadata;
+        self.zstd_compressed_images == other.zstd_compressed_images
+            && self.zstd_compressed_metadata == other.zstd_compressed_metadata
+            && self.dictionary == other.dictionary
+            && (!compressed
+                || self.compression_level.is_none()
+                || other.compression_level.is_none()
+                || self.compression_level == other.compression_level)
+    }
+    /// A decoder for a zstd compressed entry, using the package dictionary if there is one.
+    pub fn entry_decoder<R: Read>(&self, reader: R) -> io::Result<Decoder<'static, BufReader<R>>> {
+        match self.dictionary.as_deref() {
+            Some(dictionary) => Decoder::with_dictionary(BufReader::new(reader), dictionary),
+            None => Decoder::new(reader),
+        }
+    }
+    /// An encoder for a zstd compressed entry, using the package dictionary if there is one.
+    pub fn entry_encoder<W: Write>(
+        &self,
+        writer: W,
+        compression_level: i32,
+    ) -> io::Result<Encoder<'static, W>> {
+        match self.dictionary.as_deref() {
+            Some(dictionary) => Encoder::with_dictionary(writer, compression_level, dictionary),
+            None => Encoder::new(writer, compression_level),
+        }
+    }
 }
 
 impl From<DirsOptions> for PackageContentsOptions {
@@ -28,3 +140,41 @@ impl From<DirsOptions> for PackageContentsOptions {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+
+    use super::{PackageContentsOptions, ZstdDictionary};
+
+    #[test]
+    fn dictionary_round_trip() {
+        let data = br#"{"id":"chapter-1","type":"chapter","attributes":{"volume":"1"}}"#;
+        let options = PackageContentsOptions {
+            zstd_compressed_metadata: true,
+            // any byte string can be used as a raw content dictionary
+            dictionary: Some(ZstdDictionary::from(
+                br#"{"id":"","type":"chapter","attributes":{"volume":""}}"#.to_vec(),
+            )),
+            ..Default::default()
+        };
+        let mut serialized = Vec::new();
+        ciborium::into_writer(&options, &mut serialized).unwrap();
+        let options: PackageContentsOptions = ciborium::from_reader(serialized.as_slice()).unwrap();
+        assert!(options.dictionary.is_some());
+
+        let mut compressed = Vec::new();
+        {
+            let mut encoder = options.entry_encoder(&mut compressed, 3).unwrap();
+            encoder.write_all(data).unwrap();
+            encoder.finish().unwrap();
+        }
+        let mut decompressed = Vec::new();
+        options
+            .entry_decoder(compressed.as_slice())
+            .unwrap()
+            .read_to_end(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, data);
+    }
+}
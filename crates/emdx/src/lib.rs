@@ -4,9 +4,20 @@ pub mod archive;
 pub mod builder;
 pub mod constants;
 pub mod contents;
+pub mod library;
+pub mod progress;
+#[cfg(feature = "tokio")]
+pub mod tasks;
+#[cfg(test)]
+mod test_utils;
+pub mod toc;
+pub mod volume;
 
 pub use archive::Archive;
 pub use builder::Builder as PackageBuilder;
 pub use contents::{PChapterObject, PMangaObject, PackageContents};
+pub use library::ArchiveLibrary;
+pub use progress::Progress;
+pub use toc::TableOfContents;
 
 pub(crate) type ThisResult<T, E = api_core::Error> = Result<T, E>;
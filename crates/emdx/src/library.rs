@@ -0,0 +1,237 @@
+use std::{
+    io::{self, BufRead, BufReader, Read, Seek},
+    sync::{Mutex, MutexGuard},
+};
+
+use api_core::data_pulls::{
+    chapter::images::ChapterImagesData, manga::aggregate::IntoMangaAggreagate, Pull,
+};
+use mangadex_api_input_types::manga::aggregate::MangaAggregateParam;
+use mangadex_api_schema_rust::v5::{ChapterObject, CoverObject, MangaAggregate, MangaObject};
+use uuid::Uuid;
+
+use crate::{Archive, PackageContents, ThisResult};
+
+fn poisoned_archive_error() -> io::Error {
+    io::Error::other("the archive lock is poisoned")
+}
+
+/// A read-only library backed by a package.
+///
+/// It implements the same [`Pull`]s as [`DirsOptions`](api_core::DirsOptions)
+/// and its lazy `pull_all_*` methods yields the same items,
+/// so the same query code runs against a package and against a data directory:
+///
+/// ```no_run
+/// use std::fs::File;
+///
+/// use api_core::data_pulls::{manga::MangaListDataPullFilterParams, IntoFiltered};
+/// use emdx::ArchiveLibrary;
+///
+/// fn main() -> anyhow::Result<()> {
+///     let library = ArchiveLibrary::from_reader(File::open("your_package.emdx")?)?;
+///     for manga in library
+///         .pull_all_mangas()
+///         .flatten()
+///         .to_filtered(MangaListDataPullFilterParams {
+///             title: Some("Fuufu Ijou".into()),
+///             ..Default::default()
+///         })
+///     {
+///         println!("has manga {}", manga.id);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct ArchiveLibrary<'a, R>
+where
+    R: Seek + BufRead,
+{
+    contents: PackageContents,
+    archive: Mutex<Archive<'a, R>>,
+}
+
+impl<R> ArchiveLibrary<'static, BufReader<R>>
+where
+    R: Seek + Read,
+{
+    pub fn from_reader(reader: R) -> ThisResult<Self> {
+        Self::new(Archive::from_reader(reader)?)
+    }
+}
+
+impl<'a, R> ArchiveLibrary<'a, R>
+where
+    R: Seek + BufRead,
+{
+    pub fn new(archive: Archive<'a, R>) -> ThisResult<Self> {
+        Ok(Self {
+            contents: archive.get_package_contents()?.clone(),
+            archive: Mutex::new(archive),
+        })
+    }
+    pub fn into_inner(self) -> ThisResult<Archive<'a, R>> {
+        Ok(self
+            .archive
+            .into_inner()
+            .map_err(|_| poisoned_archive_error())?)
+    }
+    pub fn get_package_contents(&self) -> &PackageContents {
+        &self.contents
+    }
+    fn lock(&self) -> io::Result<MutexGuard<'_, Archive<'a, R>>> {
+        self.archive.lock().map_err(|_| poisoned_archive_error())
+    }
+    /// Pull every `ids` with `get`, one by one.
+    ///
+    /// The archive is only locked while an item is read,
+    /// so the library can still be used while iterating.
+    /// Without a table of contents, every item is found by scanning the package.
+    fn pull_all<T, I, G>(&self, ids: I, get: G) -> impl Iterator<Item = ThisResult<T>>
+    where
+        I: Iterator<Item = Uuid>,
+        G: Fn(&mut Archive<'a, R>, Uuid) -> ThisResult<T>,
+    {
+        ids.map(move |id| get(&mut *self.lock()?, id))
+    }
+    pub fn pull_all_mangas(&self) -> impl Iterator<Item = ThisResult<MangaObject>> {
+        self.pull_all(self.contents.data.keys().copied(), |archive, id| {
+            archive.get_manga(id, true)
+        })
+    }
+    pub fn pull_all_covers(&self) -> impl Iterator<Item = ThisResult<CoverObject>> {
+        self.pull_all(
+            self.contents
+                .data
+                .values()
+                .flat_map(|manga| manga.covers.iter().copied()),
+            |archive, id| archive.get_cover(id, true),
+        )
+    }
+    pub fn pull_all_chapter(&self) -> impl Iterator<Item = ThisResult<ChapterObject>> {
+        self.pull_all(
+            self.contents
+                .data
+                .values()
+                .flat_map(|manga| manga.chapters.keys().copied()),
+            |archive, id| archive.get_chapter(id, true),
+        )
+    }
+    /// The aggregate of the package chapters matching `params`.
+    pub fn aggregate(&self, params: MangaAggregateParam) -> ThisResult<MangaAggregate> {
+        Ok(self.pull_all_chapter().flatten().aggregate(params))
+    }
+}
+
+impl<R> Pull<MangaObject, Uuid> for ArchiveLibrary<'_, R>
+where
+    R: Seek + BufRead,
+{
+    type Error = api_core::Error;
+    fn pull(&self, id: Uuid) -> Result<MangaObject, Self::Error> {
+        self.lock()?.get_manga(id, true)
+    }
+}
+
+impl<R> Pull<CoverObject, Uuid> for ArchiveLibrary<'_, R>
+where
+    R: Seek + BufRead,
+{
+    type Error = api_core::Error;
+    fn pull(&self, id: Uuid) -> Result<CoverObject, Self::Error> {
+        self.lock()?.get_cover(id, true)
+    }
+}
+
+impl<R> Pull<ChapterObject, Uuid> for ArchiveLibrary<'_, R>
+where
+    R: Seek + BufRead,
+{
+    type Error = api_core::Error;
+    fn pull(&self, id: Uuid) -> Result<ChapterObject, Self::Error> {
+        self.lock()?.get_chapter(id, true)
+    }
+}
+
+impl<R> Pull<ChapterImagesData, Uuid> for ArchiveLibrary<'_, R>
+where
+    R: Seek + BufRead,
+{
+    type Error = api_core::Error;
+    fn pull(&self, id: Uuid) -> Result<ChapterImagesData, Self::Error> {
+        self.contents
+            .data
+            .values()
+            .find_map(|manga| manga.chapters.get(&id))
+            .cloned()
+            .map(ChapterImagesData::from)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("the chapter {id} is not found in the package"),
+                )
+                .into()
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use api_core::{
+        data_pulls::{chapter::ChapterListDataPullFilterParams, IntoFiltered, Pull},
+        data_push::Push,
+    };
+    use mangadex_api_schema_rust::v5::{ChapterObject, Relationship};
+    use mangadex_api_types_rust::{Language, RelationshipType};
+    use uuid::Uuid;
+
+    use super::ArchiveLibrary;
+    use crate::{
+        builder::Builder,
+        test_utils::{library_with_chapter, linear},
+        PChapterObject,
+    };
+
+    #[test]
+    fn pull_and_filter_the_chapters_of_both_layouts() {
+        let (_dir, mut dirs, mut contents) = library_with_chapter([("1.png", &[1; 64][..])]);
+        let (manga, manga_data) = contents.data.iter_mut().next().unwrap();
+        let mut manga_relationship = Relationship::default();
+        manga_relationship.id = *manga;
+        manga_relationship.type_ = RelationshipType::Manga;
+        let mut french = ChapterObject::default();
+        french.id = Uuid::new_v4();
+        french.attributes.translated_language = Language::French;
+        french.relationships.push(manga_relationship);
+        dirs.push(french.clone()).unwrap();
+        manga_data
+            .chapters
+            .insert(french.id, PChapterObject::default());
+        let mut package = Vec::new();
+        Builder::new(dirs)
+            .set_content(contents)
+            .build(&mut package)
+            .unwrap();
+
+        for package in [linear(&package), package] {
+            let library = ArchiveLibrary::from_reader(Cursor::new(package)).unwrap();
+            assert_eq!(library.pull_all_chapter().flatten().count(), 2);
+            let filtered = library
+                .pull_all_chapter()
+                .flatten()
+                .to_filtered(ChapterListDataPullFilterParams {
+                    translated_languages: vec![Language::French],
+                    ..Default::default()
+                })
+                .map(|chapter| {
+                    // the archive isn't locked between the items
+                    Pull::<ChapterObject, Uuid>::pull(&library, chapter.id).unwrap()
+                })
+                .map(|chapter| chapter.id)
+                .collect::<Vec<_>>();
+            assert_eq!(filtered, [french.id]);
+        }
+    }
+}
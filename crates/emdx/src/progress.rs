@@ -0,0 +1,8 @@
+/// How far a package build or import is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Progress {
+    /// The package entries (or the builder jobs) handled so far
+    pub done: usize,
+    /// How many there are, if it's known
+    pub total: Option<usize>,
+}
@@ -0,0 +1,122 @@
+//! Run the package builds and imports on the tokio blocking threads,
+//! so they don't block an async executor.
+//!
+//! Enabled with the `tokio` feature.
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+};
+
+use api_core::DirsOptions;
+use tokio::{sync::watch, task::JoinHandle};
+use tokio_stream::wrappers::WatchStream;
+
+use crate::{
+    archive::import::{ImportOptions, ImportReport},
+    Archive, PackageBuilder, PackageContents, Progress, ThisResult,
+};
+
+/// A package task running on a blocking thread, with its [`Progress`].
+#[derive(Debug)]
+pub struct PackageTask<T> {
+    handle: JoinHandle<ThisResult<T>>,
+    progress: watch::Receiver<Progress>,
+}
+
+impl<T> PackageTask<T>
+where
+    T: Send + 'static,
+{
+    /// Run `task` with [`tokio::task::spawn_blocking`].
+    ///
+    /// `task` reports its progress with the given sender.
+    /// It must be called from a tokio runtime.
+    pub fn spawn<F>(task: F) -> Self
+    where
+        F: FnOnce(watch::Sender<Progress>) -> ThisResult<T> + Send + 'static,
+    {
+        let (sender, progress) = watch::channel(Progress::default());
+        Self {
+            handle: tokio::task::spawn_blocking(move || task(sender)),
+            progress,
+        }
+    }
+    pub fn get_progress(&self) -> Progress {
+        *self.progress.borrow()
+    }
+    /// A receiver of the progress updates.
+    pub fn subscribe(&self) -> watch::Receiver<Progress> {
+        self.progress.clone()
+    }
+    /// A stream of the progress updates, which ends with the task.
+    ///
+    /// The updates are coalesced, so a slow reader only gets the latest ones.
+    pub fn progress_stream(&self) -> WatchStream<Progress> {
+        WatchStream::new(self.progress.clone())
+    }
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+    /// Wait for the task result.
+    pub async fn wait(self) -> ThisResult<T> {
+        self.handle.await.map_err(io::Error::other)?
+    }
+}
+
+/// Build the package into a new file at `path`, on a blocking thread.
+pub fn build_package(builder: PackageBuilder, path: PathBuf) -> PackageTask<PackageContents> {
+    PackageTask::spawn(move |progress| {
+        let mut writer = BufWriter::new(File::create(path)?);
+        let contents = builder.build_with_progress(&mut writer, |current| {
+            progress.send_replace(current);
+        })?;
+        writer.flush()?;
+        Ok(contents)
+    })
+}
+
+/// Import the package (or the parts of a split package) at `path` into `dirs`,
+/// on a blocking thread.
+///
+/// See [`Archive::open`] and [`Archive::import`].
+pub fn import_package(
+    path: PathBuf,
+    mut dirs: DirsOptions,
+    options: ImportOptions,
+) -> PackageTask<ImportReport> {
+    PackageTask::spawn(move |progress| {
+        let mut archive = Archive::open(path)?;
+        archive.import_with_progress(&mut dirs, &options, |current| {
+            progress.send_replace(current);
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::runtime::Builder as RuntimeBuilder;
+
+    use super::PackageTask;
+    use crate::Progress;
+
+    #[test]
+    fn the_progress_is_sent_while_the_task_runs() {
+        let runtime = RuntimeBuilder::new_current_thread().build().unwrap();
+        runtime.block_on(async {
+            let task = PackageTask::spawn(|progress| {
+                for done in 1..=3 {
+                    progress.send_replace(Progress {
+                        done,
+                        total: Some(3),
+                    });
+                }
+                Ok(3)
+            });
+            let progress = task.subscribe();
+            assert_eq!(task.wait().await.unwrap(), 3);
+            assert_eq!(progress.borrow().done, 3);
+        });
+    }
+}
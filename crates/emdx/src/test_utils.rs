@@ -0,0 +1,104 @@
+//! The package fixtures shared by the tests.
+
+use std::path::Path;
+
+use api_core::{
+    data_push::{chapter::image::ChapterImagePushEntry, Push},
+    DirsOptions,
+};
+use mangadex_api_schema_rust::v5::{ChapterObject, MangaObject, Relationship};
+use mangadex_api_types_rust::RelationshipType;
+use tar::{Builder as TarBuilder, Header};
+use tempfile::TempDir;
+use uuid::Uuid;
+
+use crate::{
+    constants::CONTENTS_FILENAME, toc::TableOfContents, utils::frame_writer::FrameWriter,
+    PChapterObject, PMangaObject, PackageContents,
+};
+
+/// Contents holding the `images` of a single chapter.
+pub(crate) fn chapter_contents(
+    manga: Uuid,
+    chapter: Uuid,
+    images: PChapterObject,
+) -> PackageContents {
+    let mut contents = PackageContents::default();
+    contents.data.insert(
+        manga,
+        PMangaObject {
+            covers: Vec::new(),
+            chapters: [(chapter, images)].into(),
+        },
+    );
+    contents
+}
+
+/// A package with the random access layout, holding `entries` then the `contents`.
+///
+/// Every entry is written in its own frame.
+pub(crate) fn package_with<I, P, D>(contents: &PackageContents, entries: I) -> Vec<u8>
+where
+    I: IntoIterator<Item = (P, D)>,
+    P: AsRef<Path>,
+    D: AsRef<[u8]>,
+{
+    let mut contents_data = Vec::new();
+    ciborium::into_writer(contents, &mut contents_data).unwrap();
+    let mut toc = TableOfContents::default();
+    let mut tar = TarBuilder::new(FrameWriter::new(Vec::new(), 3));
+    let mut append = |path: &Path, data: &[u8]| {
+        let mut header = Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        tar.append_data(&mut header, path, data).unwrap();
+        toc.insert(path, tar.get_mut().end_frame().unwrap().unwrap());
+    };
+    for (path, data) in entries {
+        append(path.as_ref(), data.as_ref());
+    }
+    append(CONTENTS_FILENAME.as_ref(), &contents_data);
+    tar.into_inner().unwrap().finish(&toc).unwrap()
+}
+
+/// The same package with the linear layout: a single frame and no table of contents.
+pub(crate) fn linear(package: &[u8]) -> Vec<u8> {
+    zstd::encode_all(zstd::decode_all(package).unwrap().as_slice(), 3).unwrap()
+}
+
+/// A library in a temporary directory, holding a manga with a single chapter and its `images`.
+///
+/// Returns the contents of a package with the whole chapter.
+/// The directory is removed when the returned [`TempDir`] is dropped.
+pub(crate) fn library_with_chapter<'a>(
+    images: impl IntoIterator<Item = (&'a str, &'a [u8])>,
+) -> (TempDir, DirsOptions, PackageContents) {
+    let dir = TempDir::new().unwrap();
+    let mut dirs = DirsOptions::new_from_data_dir(dir.path());
+    dirs.init_dirs().unwrap();
+    let mut manga = MangaObject::default();
+    manga.id = Uuid::new_v4();
+    let mut manga_relationship = Relationship::default();
+    manga_relationship.id = manga.id;
+    manga_relationship.type_ = RelationshipType::Manga;
+    let mut chapter = ChapterObject::default();
+    chapter.id = Uuid::new_v4();
+    chapter.relationships.push(manga_relationship);
+    dirs.push(manga.clone()).unwrap();
+    dirs.push(chapter.clone()).unwrap();
+    let mut filenames = Vec::new();
+    for (filename, data) in images {
+        let entry = ChapterImagePushEntry::new(chapter.id, filename.into(), data);
+        dirs.push(entry).unwrap();
+        filenames.push(filename.to_string());
+    }
+    let contents = chapter_contents(
+        manga.id,
+        chapter.id,
+        PChapterObject {
+            data: filenames,
+            data_saver: Vec::new(),
+        },
+    );
+    (dir, dirs, contents)
+}
@@ -0,0 +1,160 @@
+//! The table of contents of the random access package layout.
+//!
+//! In this layout, every tar entry is written as an independent zstd frame.
+//! The table of contents maps each entry path to its frame and is stored at the end of the package
+//! in a zstd skippable frame, so a regular zstd decoder still sees a plain `.tar.zstd` file.
+//!
+//! ```text
+//! [ frame: entry 1 ] ... [ frame: entry n ] [ frame: tar end blocks ]
+//! [ skippable frame header | cbor table of contents | toc length (u32 LE) | TOC_MAGIC ]
+//! ```
+
+use std::{
+    collections::HashMap,
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::serialize_sorted_map;
+
+/// The magic number of the zstd skippable frame holding the table of contents.
+pub const TOC_SKIPPABLE_FRAME_MAGIC: u32 = 0x184D2A5E;
+
+/// The magic bytes ending a package with a table of contents.
+pub const TOC_MAGIC: &[u8; 8] = b"EMDXTOC1";
+
+const SKIPPABLE_FRAME_HEADER_LEN: u64 = 8;
+
+const TOC_TRAILER_LEN: u64 = 4 + TOC_MAGIC.len() as u64;
+
+/// Where an entry frame is located in the package.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub struct TocEntry {
+    /// The offset of the compressed frame from the start of the package
+    pub offset: u64,
+    /// The size of the compressed frame
+    pub compressed_size: u64,
+    /// The size of the decompressed frame (the tar header(s) included)
+    pub size: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct TableOfContents {
+    #[serde(serialize_with = "serialize_sorted_map")]
+    pub entries: HashMap<PathBuf, TocEntry>,
+}
+
+impl TableOfContents {
+    pub fn get<P: AsRef<Path>>(&self, path: P) -> Option<&TocEntry> {
+        self.entries.get(path.as_ref())
+    }
+    pub fn insert<P: Into<PathBuf>>(&mut self, path: P, entry: TocEntry) -> Option<TocEntry> {
+        self.entries.insert(path.into(), entry)
+    }
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    /// Write the table of contents as a zstd skippable frame.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let mut toc = Vec::new();
+        ciborium::into_writer(self, &mut toc).map_err(io::Error::other)?;
+        let toc_len = u32::try_from(toc.len()).map_err(io::Error::other)?;
+        let frame_len = toc_len
+            .checked_add(TOC_TRAILER_LEN as u32)
+            .ok_or_else(|| io::Error::other("the table of contents is too big"))?;
+        writer.write_all(&TOC_SKIPPABLE_FRAME_MAGIC.to_le_bytes())?;
+        writer.write_all(&frame_len.to_le_bytes())?;
+        writer.write_all(&toc)?;
+        writer.write_all(&toc_len.to_le_bytes())?;
+        writer.write_all(TOC_MAGIC)?;
+        writer.flush()
+    }
+    /// Read the table of contents at the end of a package.
+    ///
+    /// Returns `None` for packages written with the linear layout.
+    /// The reader is rewinded to the start of the package afterward.
+    pub fn read_from<R: Read + Seek>(reader: &mut R) -> io::Result<Option<Self>> {
+        let toc = Self::read_trailer(reader);
+        reader.rewind()?;
+        toc
+    }
+    fn read_trailer<R: Read + Seek>(reader: &mut R) -> io::Result<Option<Self>> {
+        let len = reader.seek(SeekFrom::End(0))?;
+        if len < SKIPPABLE_FRAME_HEADER_LEN + TOC_TRAILER_LEN {
+            return Ok(None);
+        }
+        reader.seek(SeekFrom::End(-(TOC_TRAILER_LEN as i64)))?;
+        let mut trailer = [0u8; TOC_TRAILER_LEN as usize];
+        reader.read_exact(&mut trailer)?;
+        let (toc_len, magic) = trailer.split_at(4);
+        if magic != TOC_MAGIC {
+            return Ok(None);
+        }
+        let toc_len = u32::from_le_bytes([toc_len[0], toc_len[1], toc_len[2], toc_len[3]]) as u64;
+        if len < SKIPPABLE_FRAME_HEADER_LEN + TOC_TRAILER_LEN + toc_len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the table of contents length is bigger than the package",
+            ));
+        }
+        reader.seek(SeekFrom::End(-((TOC_TRAILER_LEN + toc_len) as i64)))?;
+        ciborium::from_reader(reader.take(toc_len))
+            .map(Some)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read};
+
+    use super::TableOfContents;
+    use crate::{test_utils::package_with, Archive, PackageContents};
+
+    #[test]
+    fn random_access_layout_round_trip() {
+        let package = package_with(
+            &PackageContents::default(),
+            [("some/file.txt", b"Hello, world!")],
+        );
+
+        // still a plain `.tar.zstd` for the linear readers
+        let decoded = zstd::decode_all(package.as_slice()).unwrap();
+        let mut linear = tar::Archive::new(decoded.as_slice());
+        assert_eq!(linear.entries().unwrap().count(), 2);
+
+        let mut reader = Cursor::new(package);
+        let read_toc = TableOfContents::read_from(&mut reader).unwrap().unwrap();
+        assert_eq!(read_toc.len(), 2);
+        let entry = *read_toc.get("some/file.txt").unwrap();
+        let frame = &reader.get_ref()[entry.offset as usize..][..entry.compressed_size as usize];
+        let mut frame_tar = tar::Archive::new(Cursor::new(zstd::decode_all(frame).unwrap()));
+        let mut text = String::new();
+        frame_tar
+            .entries()
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        assert_eq!(text, "Hello, world!");
+
+        let archive = Archive::from_reader(reader).unwrap();
+        assert!(archive.get_table_of_contents().is_some());
+        assert!(archive.get_package_contents().unwrap().data.is_empty());
+    }
+
+    #[test]
+    fn linear_packages_have_no_toc() {
+        let package = zstd::encode_all(&[0u8; 1024][..], 3).unwrap();
+        assert!(TableOfContents::read_from(&mut Cursor::new(package))
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,180 @@
+use std::io::{self, Write};
+
+use zstd::{stream::raw, zstd_safe::CParameter, Encoder};
+
+use crate::toc::{TableOfContents, TocEntry};
+
+/// A writer that counts how many bytes have been written into it.
+struct CountingWriter<W> {
+    inner: W,
+    written: u64,
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+enum FrameState<W: Write> {
+    Idle(CountingWriter<W>),
+    Frame {
+        encoder: Encoder<'static, CountingWriter<W>>,
+        offset: u64,
+        size: u64,
+    },
+}
+
+fn poisoned_frame_writer_error() -> io::Error {
+    io::Error::other("the frame writer is poisoned by a previous error")
+}
+
+/// A zstd writer that can end the current frame at any moment.
+///
+/// Every bytes written after [`FrameWriter::end_frame`] go into a new independent frame,
+/// which allows to decode them without decoding what was written before.
+pub struct FrameWriter<W: Write> {
+    state: Option<FrameState<W>>,
+    compression_level: i32,
+    workers: u32,
+    long_distance_matching: bool,
+}
+
+impl<W: Write> FrameWriter<W> {
+    pub fn new(writer: W, compression_level: i32) -> Self {
+        Self {
+            state: Some(FrameState::Idle(CountingWriter {
+                inner: writer,
+                written: 0,
+            })),
+            compression_level,
+            workers: 0,
+            long_distance_matching: false,
+        }
+    }
+    /// Compress every frame with `workers` zstd threads, `0` disables the multithreading.
+    pub fn set_workers(&mut self, workers: u32) {
+        self.workers = workers;
+    }
+    pub fn set_long_distance_matching(&mut self, long_distance_matching: bool) {
+        self.long_distance_matching = long_distance_matching;
+    }
+    /// A raw encoder with the frame settings.
+    fn new_encoder(&self) -> io::Result<raw::Encoder<'static>> {
+        let mut encoder = raw::Encoder::new(self.compression_level)?;
+        if self.workers != 0 {
+            encoder.set_parameter(CParameter::NbWorkers(self.workers))?;
+        }
+        encoder.set_parameter(CParameter::EnableLongDistanceMatching(
+            self.long_distance_matching,
+        ))?;
+        Ok(encoder)
+    }
+    fn start_frame(&mut self) -> io::Result<()> {
+        // A frame is already started (or the writer is poisoned)
+        if !matches!(self.state, Some(FrameState::Idle(_))) {
+            return Ok(());
+        }
+        // Built before taking the writer, so a settings error doesn't lose it
+        let encoder = self.new_encoder()?;
+        if let Some(FrameState::Idle(writer)) = self.state.take() {
+            let offset = writer.written;
+            self.state.replace(FrameState::Frame {
+                encoder: Encoder::with_encoder(writer, encoder),
+                offset,
+                size: 0,
+            });
+        }
+        Ok(())
+    }
+    /// Finish the current frame and return where it is located.
+    ///
+    /// Returns `None` if nothing has been written since the last frame.
+    pub fn end_frame(&mut self) -> io::Result<Option<TocEntry>> {
+        match self.state.take().ok_or_else(poisoned_frame_writer_error)? {
+            FrameState::Idle(writer) => {
+                self.state.replace(FrameState::Idle(writer));
+                Ok(None)
+            }
+            FrameState::Frame {
+                encoder,
+                offset,
+                size,
+            } => {
+                let writer = encoder.finish()?;
+                let entry = TocEntry {
+                    offset,
+                    compressed_size: writer.written - offset,
+                    size,
+                };
+                self.state.replace(FrameState::Idle(writer));
+                Ok(Some(entry))
+            }
+        }
+    }
+    /// Finish the last frame, write the table of contents and return the underlying writer.
+    pub fn finish(mut self, toc: &TableOfContents) -> io::Result<W> {
+        self.end_frame()?;
+        let Some(FrameState::Idle(mut writer)) = self.state.take() else {
+            return Err(poisoned_frame_writer_error());
+        };
+        toc.write_to(&mut writer)?;
+        Ok(writer.inner)
+    }
+}
+
+impl<W: Write> Write for FrameWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.start_frame()?;
+        match self.state.as_mut() {
+            Some(FrameState::Frame { encoder, size, .. }) => {
+                let written = encoder.write(buf)?;
+                *size += written as u64;
+                Ok(written)
+            }
+            _ => Err(poisoned_frame_writer_error()),
+        }
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        match self.state.as_mut() {
+            Some(FrameState::Frame { encoder, .. }) => encoder.flush(),
+            Some(FrameState::Idle(writer)) => writer.flush(),
+            None => Err(poisoned_frame_writer_error()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::FrameWriter;
+    use crate::toc::{TableOfContents, TocEntry};
+
+    #[test]
+    fn writes_in_the_same_frame_until_it_ends() {
+        let mut writer = FrameWriter::new(Vec::new(), 0);
+        writer.write_all(b"first ").unwrap();
+        writer.write_all(b"frame").unwrap();
+        let first = writer.end_frame().unwrap().unwrap();
+        assert!(writer.end_frame().unwrap().is_none());
+        writer.write_all(b"second frame").unwrap();
+        let second = writer.end_frame().unwrap().unwrap();
+        let package = writer.finish(&TableOfContents::default()).unwrap();
+
+        assert_eq!(first.size, 11);
+        assert_eq!(second.offset, first.offset + first.compressed_size);
+        let frame = |entry: TocEntry| {
+            let start = entry.offset as usize;
+            let end = start + entry.compressed_size as usize;
+            zstd::decode_all(&package[start..end]).unwrap()
+        };
+        assert_eq!(frame(first), b"first frame");
+        assert_eq!(frame(second), b"second frame");
+    }
+}
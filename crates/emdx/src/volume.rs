@@ -0,0 +1,158 @@
+//! Packages split into size-limited parts.
+//!
+//! Each part is written by [`PackageBuilder::build_parts`](crate::PackageBuilder::build_parts)
+//! as a package holding its own entries,
+//! with a `contents.cbor` listing them and the part [`PackageVolume`].
+//! [`Archive::from_parts`](crate::Archive::from_parts) reads the parts back as one package.
+
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Where a part stands in its package.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackageVolume {
+    /// The part index, starting from `1`
+    pub index: u32,
+    /// `true` if this is the last part of the package
+    pub last: bool,
+}
+
+/// The path of the part `index` of the package `name`: `name.part{index}.tar.zstd`.
+///
+/// A `.emdx` extension is removed from `name`.
+pub fn volume_path<P: AsRef<Path>>(name: P, index: u32) -> PathBuf {
+    let name = name.as_ref();
+    let mut path = if name.extension().is_some_and(|extension| extension == "emdx") {
+        name.with_extension("")
+    } else {
+        name.to_path_buf()
+    }
+    .into_os_string();
+    path.push(format!(".part{index}.tar.zstd"));
+    path.into()
+}
+
+/// Every existing part of the package `name`, in order.
+pub fn find_volumes<P: AsRef<Path>>(name: P) -> io::Result<Vec<PathBuf>> {
+    let volumes = (1..)
+        .map(|index| volume_path(&name, index))
+        .take_while(|path| path.exists())
+        .collect::<Vec<_>>();
+    if volumes.is_empty() {
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("no part of {} is found", name.as_ref().display()),
+        ))
+    } else {
+        Ok(volumes)
+    }
+}
+
+/// Reads the package parts one after the other, as if they were a single file.
+pub struct MultiPartReader {
+    parts: Vec<File>,
+    /// The offset of every part start, followed by the total length
+    starts: Vec<u64>,
+    position: u64,
+}
+
+impl MultiPartReader {
+    pub fn new(parts: Vec<File>) -> io::Result<Self> {
+        let mut starts = vec![0];
+        for part in &parts {
+            let start = starts.last().copied().unwrap_or_default();
+            starts.push(start + part.metadata()?.len());
+        }
+        Ok(Self {
+            parts,
+            starts,
+            position: 0,
+        })
+    }
+    fn len(&self) -> u64 {
+        self.starts.last().copied().unwrap_or_default()
+    }
+}
+
+impl Read for MultiPartReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position >= self.len() || buf.is_empty() {
+            return Ok(0);
+        }
+        let index = self.starts.partition_point(|start| *start <= self.position) - 1;
+        let part_start = self.starts[index];
+        let remaining = self.starts[index + 1] - self.position;
+        let part = &mut self.parts[index];
+        part.seek(SeekFrom::Start(self.position - part_start))?;
+        let max = usize::try_from(remaining).unwrap_or(usize::MAX).min(buf.len());
+        let read = part.read(&mut buf[..max])?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl Seek for MultiPartReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(position) => Some(position),
+            SeekFrom::End(offset) => self.len().checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        }
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+        self.position = position;
+        Ok(position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Seek, SeekFrom, Write},
+        path::PathBuf,
+    };
+
+    use super::{volume_path, MultiPartReader};
+
+    #[test]
+    fn parts_are_read_as_one_file() {
+        let parts = [&b"Hello, "[..], b"", b"world!"]
+            .into_iter()
+            .map(|data| {
+                let mut part = tempfile::tempfile().unwrap();
+                part.write_all(data).unwrap();
+                part
+            })
+            .collect();
+        let mut reader = MultiPartReader::new(parts).unwrap();
+        let mut text = String::new();
+        reader.read_to_string(&mut text).unwrap();
+        assert_eq!(text, "Hello, world!");
+
+        reader.seek(SeekFrom::End(-8)).unwrap();
+        text.clear();
+        reader.read_to_string(&mut text).unwrap();
+        assert_eq!(text, ", world!");
+    }
+
+    #[test]
+    fn volume_names() {
+        assert_eq!(
+            volume_path("shared/my-package.emdx", 2),
+            PathBuf::from("shared/my-package.part2.tar.zstd")
+        );
+        assert_eq!(
+            volume_path("my-package", 1),
+            PathBuf::from("my-package.part1.tar.zstd")
+        );
+    }
+}
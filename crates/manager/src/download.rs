@@ -21,6 +21,7 @@ pub mod chapter;
 pub mod cover;
 pub mod manga;
 pub mod messages;
+pub mod scheduler;
 pub mod state;
 pub mod traits;
 
@@ -46,10 +47,12 @@ impl Actor for DownloadManager {
 
 impl From<Addr<DownloadManagerState>> for DownloadManager {
     fn from(state: Addr<DownloadManagerState>) -> Self {
+        let cover = CoverDownloadManager::new(state.clone()).start();
+        let chapter = ChapterDownloadManager::new(state.clone()).start();
         Self {
-            manga: MangaDownloadManager::new(state.clone()).start(),
-            cover: CoverDownloadManager::new(state.clone()).start(),
-            chapter: ChapterDownloadManager::new(state.clone()).start(),
+            manga: MangaDownloadManager::new(state.clone(), cover.clone(), chapter.clone()).start(),
+            cover,
+            chapter,
             state,
         }
     }
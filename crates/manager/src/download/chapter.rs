@@ -5,7 +5,7 @@ use std::{collections::HashMap, sync::Arc};
 
 use actix::{WeakAddr, prelude::*};
 use log::{debug, trace};
-use task::DownloadMode;
+use task::{ChapterRetryPolicy, DownloadMode};
 use tokio::sync::Notify;
 use uuid::Uuid;
 
@@ -14,7 +14,8 @@ use crate::download::messages::StopTask;
 use self::task::ChapterDownloadTask;
 
 use super::{
-    messages::{DropSingleTaskMessage, GetTaskMessage, StartDownload},
+    messages::{DropSingleTaskMessage, GetTaskMessage},
+    scheduler::{TaskPriority, TaskScheduler},
     state::{DownloadManagerState, DownloadMessageState},
     traits::{managers::TaskManager, task::AsyncState},
 };
@@ -24,6 +25,7 @@ pub struct ChapterDownloadManager {
     state: Addr<DownloadManagerState>,
     tasks: HashMap<Uuid, WeakAddr<ChapterDownloadTask>>,
     notify: Arc<Notify>,
+    scheduler: TaskScheduler<ChapterDownloadTask>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -32,6 +34,8 @@ pub struct ChapterDownloadMessage {
     state: DownloadMessageState,
     mode: DownloadMode,
     force_port_443: bool,
+    priority: TaskPriority,
+    retry_policy: ChapterRetryPolicy,
 }
 
 impl ChapterDownloadMessage {
@@ -41,6 +45,8 @@ impl ChapterDownloadMessage {
             state: DownloadMessageState::Pending,
             mode: DownloadMode::Normal,
             force_port_443: false,
+            priority: TaskPriority::Normal,
+            retry_policy: Default::default(),
         }
     }
     pub fn state(self, state: DownloadMessageState) -> Self {
@@ -58,6 +64,17 @@ impl ChapterDownloadMessage {
             ..self
         }
     }
+    /// The priority of the task in the manager download queue
+    pub fn priority(self, priority: TaskPriority) -> Self {
+        Self { priority, ..self }
+    }
+    /// How the task should retry the images that failed to download
+    pub fn retry_policy(self, retry_policy: ChapterRetryPolicy) -> Self {
+        Self {
+            retry_policy,
+            ..self
+        }
+    }
 }
 
 impl From<Uuid> for ChapterDownloadMessage {
@@ -141,23 +158,27 @@ impl TaskManager for ChapterDownloadManager {
         self.notify.notify_waiters();
 
         if let DownloadMessageState::Downloading = msg.state {
-            debug!("Telling task to download chapter");
+            debug!("Queueing chapter download");
+            let id = msg.id;
+            let priority = msg.priority;
             let fut = async move {
                 trace!("getting state...");
                 let state = re_task.state().await?;
-                if !state.is_loading() {
+                if !state.is_loading() && !state.is_queued() {
                     trace!("Sending mode message");
                     re_task.send(msg.mode).await?;
-                    trace!("Sending start download");
-                    re_task.send(StartDownload).await?;
+                    trace!("Sending retry policy message");
+                    re_task.send(msg.retry_policy).await?;
+                    Ok::<_, actix::MailboxError>(Some(re_task))
+                } else {
+                    Ok(None)
                 }
-                Ok::<_, actix::MailboxError>(())
             }
             .into_actor(self)
-            .map(|s, _, _| {
-                if let Err(err) = s {
-                    log::error!("{err}");
-                }
+            .map(move |s, this, ctx| match s {
+                Ok(Some(task)) => this.schedule_task(id, task, priority, ctx),
+                Ok(None) => {}
+                Err(err) => log::error!("{err}"),
             });
             ctx.wait(fut);
         }
@@ -170,11 +191,19 @@ impl TaskManager for ChapterDownloadManager {
         {
             self.tasks.remove(&id);
         }
+        // The task is dropped or stopped, so it can't be started anymore
+        self.scheduler.remove(&id);
         self.notify.notify_waiters();
     }
     fn get_task(&self, id: Uuid) -> Option<Addr<Self::Task>> {
         self.tasks.get(&id).and_then(WeakAddr::upgrade)
     }
+    fn scheduler(&self) -> &TaskScheduler<Self::Task> {
+        &self.scheduler
+    }
+    fn scheduler_mut(&mut self) -> &mut TaskScheduler<Self::Task> {
+        &mut self.scheduler
+    }
 }
 
 impl Handler<ChapterDownloadMessage> for ChapterDownloadManager {
@@ -199,6 +228,7 @@ impl ChapterDownloadManager {
             state,
             tasks: Default::default(),
             notify: Arc::new(Notify::new()),
+            scheduler: Default::default(),
         }
     }
 }
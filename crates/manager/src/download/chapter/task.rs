@@ -1,12 +1,15 @@
+mod images;
 pub mod messages;
+mod retry;
 
-use std::ops::Deref;
+use std::{ops::Deref, time::Duration};
 
 use actix::prelude::*;
 use futures_util::FutureExt;
 use log::trace;
 use mangadex_api::utils::download::chapter::DownloadMode as Mode;
 use mangadex_api_schema_rust::v5::ChapterObject;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::{
@@ -34,12 +37,27 @@ pub enum ChapterDownloadingState {
         len: usize,
     },
     FetchingAtHomeData,
+    /// Waiting before retrying the images that failed to download
+    WaitingForRetry {
+        attempt: u32,
+        max_attempts: u32,
+        delay: Duration,
+        failed: usize,
+    },
+    RetryingImage {
+        filename: String,
+        attempt: u32,
+        max_attempts: u32,
+        index: usize,
+        len: usize,
+    },
 }
 
 pub type ChapterDownloadTaskState = DownloadTaskState<ChapterObject, ChapterDownloadingState>;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum DownloadMode {
+    #[default]
     Normal,
     DataSaver,
 }
@@ -76,10 +94,89 @@ impl From<DownloadMode> for api_core::data_push::chapter::image::Mode {
     }
 }
 
+/// How a chapter download task retries the images that failed to download.
+///
+/// Only the failed images are fetched again,
+/// waiting an exponentially growing delay before each new attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChapterRetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: u32,
+}
+
+impl Default for ChapterRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2,
+        }
+    }
+}
+
+impl ChapterRetryPolicy {
+    /// A policy that never retries
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+    /// The total number of download attempts, including the first one.
+    ///
+    /// `0` is treated as `1`.
+    pub fn max_attempts(self, max_attempts: u32) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            ..self
+        }
+    }
+    pub fn initial_backoff(self, initial_backoff: Duration) -> Self {
+        Self {
+            initial_backoff,
+            ..self
+        }
+    }
+    pub fn max_backoff(self, max_backoff: Duration) -> Self {
+        Self {
+            max_backoff,
+            ..self
+        }
+    }
+    pub fn multiplier(self, multiplier: u32) -> Self {
+        Self {
+            multiplier: multiplier.max(1),
+            ..self
+        }
+    }
+    pub fn get_max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+    /// The delay to wait before the given retry (starting at `1`)
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = self
+            .multiplier
+            .checked_pow(retry.saturating_sub(1))
+            .unwrap_or(u32::MAX);
+        self.initial_backoff
+            .checked_mul(factor)
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff)
+    }
+}
+
+impl Message for ChapterRetryPolicy {
+    type Result = ();
+}
+
 #[derive(Debug)]
 pub struct ChapterDownloadTask {
     id: Uuid,
     mode: DownloadMode,
+    retry_policy: ChapterRetryPolicy,
     handle: Option<SpawnHandle>,
     state: ArcRwLock<ChapterDownloadTaskState>,
     manager: Addr<ChapterDownloadManager>,
@@ -146,6 +243,8 @@ impl Handler<StopTask> for ChapterDownloadTask {
     type Result = ();
     fn handle(&mut self, _msg: StopTask, ctx: &mut Self::Context) -> Self::Result {
         self.should_stop = true;
+        // Removes the task from the manager download queue
+        self.manager.do_send(DropSingleTaskMessage(self.id));
         ctx.terminate();
     }
 }
@@ -160,6 +259,7 @@ impl ChapterDownloadTask {
         Self {
             id,
             mode: mode.into(),
+            retry_policy: Default::default(),
             handle: None,
             state: Default::default(),
             subscribers: Default::default(),
@@ -180,6 +280,16 @@ impl Handler<DownloadMode> for ChapterDownloadTask {
     }
 }
 
+impl Handler<ChapterRetryPolicy> for ChapterDownloadTask {
+    type Result = <ChapterRetryPolicy as Message>::Result;
+    fn handle(&mut self, msg: ChapterRetryPolicy, _ctx: &mut Self::Context) -> Self::Result {
+        let state = std::convert::Into::<TaskState>::into(self.state.read().deref());
+        if !state.is_loading() {
+            self.retry_policy = msg;
+        }
+    }
+}
+
 impl Handler<FilesDirSubscriberMessage> for ChapterDownloadTask {
     type Result = ();
     fn handle(&mut self, msg: FilesDirSubscriberMessage, ctx: &mut Self::Context) -> Self::Result {
@@ -0,0 +1,104 @@
+//! The chapter images download, throttled by the [`BandwidthLimiter`].
+
+use std::time::Instant;
+
+use futures_util::StreamExt;
+use mangadex_api::{error::Error, utils::download::chapter::AtHomePreDownloadImageData};
+use reqwest::Response;
+use serde_json::json;
+use tokio_stream::Stream;
+use url::Url;
+
+use crate::download::state::BandwidthLimiter;
+
+use super::retry::ImageDownload;
+
+const AT_HOME_REPORT_URL: &str = "https://api.mangadex.network/report";
+
+/// Download the `images` one by one, like the `mangadex_api` chapter download streams,
+/// but read their bodies with [`BandwidthLimiter::read_body`].
+///
+/// The images for which `should_skip` returns `true` are yielded with an [`Error::SkippedDownload`].
+pub(crate) fn download_images<'a, C>(
+    images: Vec<AtHomePreDownloadImageData>,
+    bandwidth: &'a BandwidthLimiter,
+    should_skip: C,
+) -> impl Stream<Item = ImageDownload> + 'a
+where
+    C: FnMut(&AtHomePreDownloadImageData, &Response) -> bool + Copy + 'a,
+{
+    let len = images.len();
+    futures_util::stream::iter(images.into_iter().enumerate()).then(
+        move |(index, image)| async move {
+            let res = download_image(&image, bandwidth, should_skip).await;
+            ((image.filename, res), index + 1, len)
+        },
+    )
+}
+
+async fn download_image<C>(
+    image: &AtHomePreDownloadImageData,
+    bandwidth: &BandwidthLimiter,
+    mut should_skip: C,
+) -> mangadex_api::Result<bytes::Bytes>
+where
+    C: FnMut(&AtHomePreDownloadImageData, &Response) -> bool,
+{
+    let page_url = image.build_page_url()?;
+    let start = Instant::now();
+    let res = match image.http_client.get(page_url.clone()).send().await {
+        Ok(res) => res,
+        Err(e) => {
+            report(image, start, page_url, 0, false, false).await;
+            return Err(Error::RequestError(e));
+        }
+    };
+    if should_skip(image, &res) {
+        return Err(Error::SkippedDownload(image.filename.clone()));
+    }
+    let cached = res
+        .headers()
+        .get("X-Cache")
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("HIT"));
+    match bandwidth.read_body(res).await {
+        Ok(bytes) => {
+            report(image, start, page_url, bytes.len(), true, cached).await;
+            Ok(bytes)
+        }
+        Err(e) => {
+            report(image, start, page_url, 0, false, cached).await;
+            Err(Error::RequestError(e))
+        }
+    }
+}
+
+/// Send the MangaDex@Home report of an image download if it is enabled.
+///
+/// See <https://api.mangadex.org/docs/retrieving-chapter/#the-mangadexhome-report-endpoint>
+async fn report(
+    image: &AtHomePreDownloadImageData,
+    start: Instant,
+    url: Url,
+    bytes: usize,
+    success: bool,
+    cached: bool,
+) {
+    // The mangadex.org servers must not be reported
+    if !image.report || url.as_str().contains("mangadex.org") {
+        return;
+    }
+    let _ = image
+        .http_client
+        .post(AT_HOME_REPORT_URL)
+        .json(&json!({
+            "url": url,
+            "success": success,
+            "cached": cached,
+            "bytes": bytes,
+            "duration": start.elapsed().as_millis(),
+        }))
+        .timeout(std::time::Duration::from_secs(2))
+        .send()
+        .await;
+}
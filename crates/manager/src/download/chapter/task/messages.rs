@@ -1,5 +1,6 @@
 pub mod cancel;
 pub mod download;
+pub mod queue;
 pub mod state;
 pub mod sub;
 pub mod wait;
@@ -12,14 +12,23 @@ use bytes::{Buf, Bytes};
 use futures_util::FutureExt;
 use mangadex_api_schema_rust::v5::ChapterObject as Object;
 use mangadex_api_types_rust::RelationshipType;
-use tokio_stream::StreamExt;
+use uuid::Uuid;
 
 use crate::{
     ManagerCoreResult,
-    data_push::chapter::{ChapterRequiredRelationship, image::ChapterImagePushEntry},
+    data_push::chapter::{
+        ChapterRequiredRelationship,
+        image::{ChapterImagePushEntry, Mode},
+    },
     download::{
-        chapter::task::{
-            ChapterDownloadTask as Task, ChapterDownloadTaskState, ChapterDownloadingState as State,
+        chapter::{
+            ChapterDownloadManager,
+            task::{
+                ChapterDownloadTask as Task, ChapterDownloadTaskState,
+                ChapterDownloadingState as State, DownloadMode,
+                images::download_images,
+                retry::{ChapterImagesStore, retry_failed_images, store_images},
+            },
         },
         messages::StartDownload,
         state::{DownloadTaskState, TaskState, messages::get::GetManagerStateData},
@@ -32,6 +41,48 @@ use crate::{
     prelude::{ChapterDataPullAsyncTrait, DeleteDataAsyncTrait, PushActorAddr},
 };
 
+/// An image that is already stored
+enum StoredImage {
+    /// Its content matches the hash in its filename
+    Verified,
+    /// Its filename doesn't contain a hash, so only its size can be compared
+    Sized(usize),
+}
+
+impl StoredImage {
+    fn is_up_to_date(&self, content_length: Option<u64>) -> bool {
+        match self {
+            Self::Verified => true,
+            Self::Sized(len) => content_length
+                .and_then(|cl| usize::try_from(cl).ok())
+                .is_some_and(|cl| cl == *len),
+        }
+    }
+}
+
+/// Writes the images of a chapter download task into the library
+struct TaskImagesStore {
+    manager: Addr<ChapterDownloadManager>,
+    id: Uuid,
+    mode: DownloadMode,
+}
+
+impl ChapterImagesStore for TaskImagesStore {
+    fn chapter(&self) -> Uuid {
+        self.id
+    }
+    async fn store(&self, filename: String, bytes: Bytes) -> ManagerCoreResult<()> {
+        self.manager
+            .push(ChapterImagePushEntry::new(self.id, filename, bytes.reader()).mode(self.mode))
+            .await
+    }
+    async fn remove(&self, filename: String) -> ManagerCoreResult<()> {
+        self.manager
+            .delete_chapter_image(self.id, self.mode, filename)
+            .await
+    }
+}
+
 impl Task {
     fn preloading(&self) {
         *self.state.write() = DownloadTaskState::Loading(State::Preloading);
@@ -57,10 +108,12 @@ impl Download for Task {
             self.preloading();
             let manager = self.manager.clone();
             let mode = self.mode;
+            let retry_policy = self.retry_policy;
             let id = self.id;
             let force_port_443 = self.force_port_443;
 
-            let entry = HistoryEntry::new(id, RelationshipType::Chapter);
+            let mut entry = HistoryEntry::new(id, RelationshipType::Chapter);
+            entry.set_mode(Some(mode));
             let send_to_subscrbers = self.send_to_subscrbers();
             let send_to_subs_map = send_to_subscrbers.clone();
             if let Some(t) = self.handle.replace(
@@ -69,6 +122,7 @@ impl Download for Task {
                         // Getting manager state data
 
                         let client = manager.get_client().await?;
+                        let bandwidth = manager.get_bandwidth_limiter().await?;
                         let mut history = manager.get_history().await?;
                         // fetching chapter data
                         send_to_subscrbers(DownloadTaskState::Loading(State::FetchingData));
@@ -88,33 +142,39 @@ impl Download for Task {
                         let current_images =
                             manager.get_chapter_images(id).await.unwrap_or_default();
                         let (images, is_new) = {
-                            let mut images: HashMap<String, usize> = Default::default();
-                            // getting current images size
-                            match mode {
-                                crate::download::chapter::task::DownloadMode::Normal => {
-                                    for image in &current_images.data {
-                                        if let Ok(b) =
-                                            manager.get_chapter_image(id, image.clone()).await
-                                            && let Ok(len) =
-                                                b.metadata().map(|met| met.len() as usize)
-                                        {
-                                            images.insert(image.clone(), len);
-                                        }
-                                    }
-                                }
+                            let image_mode = match mode {
+                                crate::download::chapter::task::DownloadMode::Normal => Mode::Data,
                                 crate::download::chapter::task::DownloadMode::DataSaver => {
-                                    for image in &current_images.data_saver {
-                                        if let Ok(b) = manager
+                                    Mode::DataSaver
+                                }
+                            };
+                            let report = manager
+                                .verify_chapter_images(id, image_mode)
+                                .await
+                                .unwrap_or_default();
+                            let mut images: HashMap<String, StoredImage> = report
+                                .valid
+                                .into_iter()
+                                .map(|image| (image, StoredImage::Verified))
+                                .collect();
+                            // getting the size of the images without a hash in their filename
+                            for image in report.unknown {
+                                let file = match mode {
+                                    crate::download::chapter::task::DownloadMode::Normal => {
+                                        manager.get_chapter_image(id, image.clone()).await
+                                    }
+                                    crate::download::chapter::task::DownloadMode::DataSaver => {
+                                        manager
                                             .get_chapter_image_data_saver(id, image.clone())
                                             .await
-                                            && let Ok(len) =
-                                                b.metadata().map(|met| met.len() as usize)
-                                        {
-                                            images.insert(image.clone(), len);
-                                        }
                                     }
+                                };
+                                if let Ok(b) = file
+                                    && let Ok(len) = b.metadata().map(|met| met.len() as usize)
+                                {
+                                    images.insert(image, StoredImage::Sized(len));
                                 }
-                            };
+                            }
                             let is_new = AtomicBool::new(match mode {
                                 crate::download::chapter::task::DownloadMode::Normal => {
                                     current_images.data.is_empty()
@@ -127,15 +187,16 @@ impl Download for Task {
                         };
 
                         let is_first_loading = AtomicBool::new(true);
-                        let pre_stream = client
+                        let downloader = client
                             .download()
                             .chapter(id)
                             .report(true)
                             .mode(mode)
                             .force_port_443(force_port_443)
                             .build()?;
-                        let stream = pre_stream
-                            .download_stream_with_checker(|at_home, resp| {
+                        let at_home_images = downloader.build_at_home_urls().await?;
+                        let stream =
+                            download_images(at_home_images, &bandwidth, |at_home, resp| {
                                 if !is_new.load(AtomicOrd::Relaxed)
                                     && is_first_loading.load(AtomicOrd::Relaxed)
                                 {
@@ -171,20 +232,13 @@ impl Download for Task {
                                 if is_new.load(AtomicOrd::Relaxed) {
                                     false
                                 } else {
-                                    resp.content_length()
-                                        .and_then(|cl| {
-                                            let filename = Path::new(resp.url().path())
-                                                .file_name()?
-                                                .to_str()?;
-                                            images
-                                                .get(filename)?
-                                                .partial_cmp(&TryInto::<usize>::try_into(cl).ok()?)
-                                        })
-                                        .map(|o| o.is_eq())
+                                    Path::new(resp.url().path())
+                                        .file_name()
+                                        .and_then(|filename| images.get(filename.to_str()?))
+                                        .map(|image| image.is_up_to_date(resp.content_length()))
                                         .unwrap_or_default()
                                 }
-                            })
-                            .await?;
+                            });
                         // Delete if the chapter data is new
                         if is_new.load(AtomicOrd::Relaxed) {
                             manager
@@ -192,61 +246,59 @@ impl Download for Task {
                                 .await?;
                         }
                         // Fetches each images and stores it
-                        let mut have_error = false;
-                        let have_error_ref = &mut have_error;
-                        let mut mark_have_error = move || {
-                            if !*have_error_ref {
-                                *have_error_ref = true;
-                            }
+                        let store = TaskImagesStore {
+                            manager: manager.clone(),
+                            id,
+                            mode,
                         };
-                        let mut stream = Box::pin(stream);
-                        while let Some(((filename, res_bytes), index, len)) = stream.next().await {
+                        let failed = store_images(stream, &store, |filename, index, len| {
                             send_to_subscrbers(DownloadTaskState::Loading(State::FetchingImage {
-                                filename: filename.clone(),
+                                filename: filename.to_string(),
                                 index,
                                 len,
                             }));
-                            match res_bytes {
-                                Ok(b) => {
-                                    if let Err(e) = manager
-                                        .push(
-                                            ChapterImagePushEntry::new(
-                                                id,
-                                                filename.clone(),
-                                                b.reader(),
-                                            )
-                                            .mode(mode),
-                                        )
-                                        .await
-                                    {
-                                        mark_have_error();
-                                        log::error!("[chapter|{id}|{filename}]>write - {e}");
-                                    }
-                                }
-                                Err(e) => {
-                                    if let mangadex_api::error::Error::SkippedDownload(_) = &e {
-                                    } else {
-                                        mark_have_error();
-                                        log::error!("[chapter|{id}|{filename}]>write - {e}");
-                                        if let Err(e) = manager
-                                            .push(
-                                                ChapterImagePushEntry::new(
-                                                    id,
-                                                    filename.clone(),
-                                                    Bytes::new().reader(),
-                                                )
-                                                .mode(mode),
-                                            )
-                                            .await
-                                        {
-                                            mark_have_error();
-                                            log::error!("[chapter|{id}|{filename}]>write - {e}");
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        if !have_error {
+                        })
+                        .await;
+                        // Retries the failed images
+                        let max_attempts = retry_policy.get_max_attempts();
+                        let failed = retry_failed_images(
+                            &retry_policy,
+                            &store,
+                            failed,
+                            |state| send_to_subscrbers(DownloadTaskState::Loading(state)),
+                            async |failed, attempt| {
+                                let downloader = client
+                                    .download()
+                                    .chapter(id)
+                                    .report(true)
+                                    .mode(mode)
+                                    .force_port_443(force_port_443)
+                                    .build()?;
+                                // Only the failed images are downloaded again
+                                let at_home_images = downloader
+                                    .build_at_home_urls()
+                                    .await?
+                                    .into_iter()
+                                    .filter(|image| failed.contains(&image.filename))
+                                    .collect();
+                                let stream =
+                                    download_images(at_home_images, &bandwidth, |_, _| false);
+                                Ok(store_images(stream, &store, |filename, index, len| {
+                                    send_to_subscrbers(DownloadTaskState::Loading(
+                                        State::RetryingImage {
+                                            filename: filename.to_string(),
+                                            attempt,
+                                            max_attempts,
+                                            index,
+                                            len,
+                                        },
+                                    ));
+                                })
+                                .await)
+                            },
+                        )
+                        .await?;
+                        if failed.is_empty() {
                             history.remove_and_commit(entry).await?;
                         }
                         Ok(res.data)
@@ -0,0 +1,21 @@
+use actix::Handler;
+
+use crate::download::{
+    chapter::task::{ChapterDownloadTask as Task, ChapterDownloadTaskState as State},
+    messages::QueueTaskMessage,
+    traits::task::State as TaskStateTrait,
+};
+
+impl Handler<QueueTaskMessage> for Task {
+    type Result = ();
+    fn handle(&mut self, msg: QueueTaskMessage, _ctx: &mut Self::Context) -> Self::Result {
+        if self.state().is_loading() {
+            return;
+        }
+        *self.state.write() = match msg {
+            QueueTaskMessage::Queued => State::Queued,
+            QueueTaskMessage::Rejected(err) => State::Error(err),
+        };
+        self.sync_state_subscribers();
+    }
+}
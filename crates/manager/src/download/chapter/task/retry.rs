@@ -0,0 +1,110 @@
+//! The storing of the downloaded chapter images and the retries of the failed ones.
+
+#[cfg(test)]
+mod tests;
+
+use std::{collections::HashSet, future::Future};
+
+use bytes::Bytes;
+use tokio_stream::{Stream, StreamExt};
+use uuid::Uuid;
+
+use crate::ManagerCoreResult;
+
+use super::{ChapterDownloadingState as State, ChapterRetryPolicy};
+
+/// A downloaded chapter image with its index and the number of images,
+/// as yielded by the chapter download streams.
+pub(crate) type ImageDownload = ((String, mangadex_api::Result<Bytes>), usize, usize);
+
+/// Where a chapter download task writes its images.
+pub(crate) trait ChapterImagesStore {
+    /// The id of the downloaded chapter
+    fn chapter(&self) -> Uuid;
+    fn store(&self, filename: String, bytes: Bytes) -> impl Future<Output = ManagerCoreResult<()>>;
+    /// Remove an image that failed to download, so no empty or partial file is left behind.
+    fn remove(&self, filename: String) -> impl Future<Output = ManagerCoreResult<()>>;
+}
+
+/// Store the images yielded by `stream` and returns the filenames of the ones that failed.
+///
+/// `on_image` is called before storing each image.
+pub(crate) async fn store_images<S, St, F>(
+    stream: S,
+    store: &St,
+    mut on_image: F,
+) -> HashSet<String>
+where
+    S: Stream<Item = ImageDownload>,
+    St: ChapterImagesStore,
+    F: FnMut(&str, usize, usize),
+{
+    let id = store.chapter();
+    let mut failed = HashSet::new();
+    let mut stream = Box::pin(stream);
+    while let Some(((filename, res_bytes), index, len)) = stream.next().await {
+        on_image(&filename, index, len);
+        match res_bytes {
+            Ok(b) => {
+                if let Err(e) = store.store(filename.clone(), b).await {
+                    log::error!("[chapter|{id}|{filename}]>write - {e}");
+                    failed.insert(filename);
+                }
+            }
+            Err(mangadex_api::error::Error::SkippedDownload(_)) => {}
+            Err(e) => {
+                log::error!("[chapter|{id}|{filename}]>download - {e}");
+                failed.insert(filename);
+            }
+        }
+    }
+    failed
+}
+
+/// Remove the files of the `failed` images.
+async fn remove_failed<St: ChapterImagesStore>(store: &St, failed: &HashSet<String>) {
+    let id = store.chapter();
+    for filename in failed {
+        if let Err(e) = store.remove(filename.clone()).await {
+            log::error!("[chapter|{id}|{filename}]>remove - {e}");
+        }
+    }
+}
+
+/// Retry the `failed` images with `policy`.
+///
+/// `download` fetches and stores the given failed images,
+/// and returns the ones that failed again (see [`store_images`]).
+/// The files of the failed images are removed before each retry and after the last one.
+///
+/// Returns the images that still failed after the last attempt.
+pub(crate) async fn retry_failed_images<St, D, Sd>(
+    policy: &ChapterRetryPolicy,
+    store: &St,
+    mut failed: HashSet<String>,
+    send_state: Sd,
+    mut download: D,
+) -> ManagerCoreResult<HashSet<String>>
+where
+    St: ChapterImagesStore,
+    D: AsyncFnMut(&HashSet<String>, u32) -> ManagerCoreResult<HashSet<String>>,
+    Sd: Fn(State),
+{
+    let max_attempts = policy.get_max_attempts();
+    let mut attempt = 1;
+    while !failed.is_empty() && attempt < max_attempts {
+        remove_failed(store, &failed).await;
+        let delay = policy.backoff(attempt);
+        attempt += 1;
+        send_state(State::WaitingForRetry {
+            attempt,
+            max_attempts,
+            delay,
+            failed: failed.len(),
+        });
+        actix::clock::sleep(delay).await;
+        failed = download(&failed, attempt).await?;
+    }
+    remove_failed(store, &failed).await;
+    Ok(failed)
+}
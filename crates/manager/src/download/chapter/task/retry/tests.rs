@@ -0,0 +1,76 @@
+use std::{cell::RefCell, collections::HashSet, time::Duration};
+
+use bytes::Bytes;
+use mangadex_api::error::Error;
+use tokio_stream::StreamExt;
+use uuid::Uuid;
+
+use crate::{ManagerCoreResult, download::chapter::task::ChapterRetryPolicy};
+
+use super::{ChapterImagesStore, ImageDownload, retry_failed_images, store_images};
+
+#[derive(Debug, Default)]
+struct MockStore {
+    stored: RefCell<Vec<String>>,
+    removed: RefCell<Vec<String>>,
+}
+
+impl ChapterImagesStore for MockStore {
+    fn chapter(&self) -> Uuid {
+        Uuid::nil()
+    }
+    async fn store(&self, filename: String, _bytes: Bytes) -> ManagerCoreResult<()> {
+        self.stored.borrow_mut().push(filename);
+        Ok(())
+    }
+    async fn remove(&self, filename: String) -> ManagerCoreResult<()> {
+        self.removed.borrow_mut().push(filename);
+        Ok(())
+    }
+}
+
+/// `1.png` always fails to download, `2.png` never fails
+fn failing_stream() -> impl tokio_stream::Stream<Item = ImageDownload> {
+    tokio_stream::iter([
+        (
+            (
+                "1.png".to_string(),
+                Err(Error::ServerError(503, "unavailable".into())),
+            ),
+            1,
+            2,
+        ),
+        (("2.png".to_string(), Ok(Bytes::from_static(b"page"))), 2, 2),
+    ])
+}
+
+#[actix::test]
+async fn retry_the_failed_images_then_give_up() -> ManagerCoreResult<()> {
+    let store = MockStore::default();
+    let policy = ChapterRetryPolicy::default()
+        .max_attempts(3)
+        .initial_backoff(Duration::from_millis(1));
+    let failed = store_images(failing_stream(), &store, |_, _, _| {}).await;
+    assert_eq!(failed, HashSet::from(["1.png".to_string()]));
+
+    let attempts = RefCell::new(Vec::new());
+    let failed = retry_failed_images(
+        &policy,
+        &store,
+        failed,
+        |_| {},
+        async |failed, attempt| {
+            attempts.borrow_mut().push(attempt);
+            let stream = failing_stream().filter(|((filename, _), _, _)| failed.contains(filename));
+            Ok(store_images(stream, &store, |_, _, _| {}).await)
+        },
+    )
+    .await?;
+    assert_eq!(failed, HashSet::from(["1.png".to_string()]));
+    assert_eq!(*attempts.borrow(), vec![2, 3]);
+    // only the first download stored the image that didn't fail
+    assert_eq!(*store.stored.borrow(), vec!["2.png".to_string()]);
+    // removed before each retry and after the last one
+    assert_eq!(*store.removed.borrow(), vec!["1.png".to_string(); 3]);
+    Ok(())
+}
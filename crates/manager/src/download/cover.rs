@@ -13,7 +13,8 @@ use crate::{download::messages::StopTask, prelude::AsyncState};
 use self::task::CoverDownloadTask;
 
 use super::{
-    messages::{DropSingleTaskMessage, GetTaskMessage, StartDownload},
+    messages::{DropSingleTaskMessage, GetTaskMessage},
+    scheduler::{TaskPriority, TaskScheduler},
     state::{DownloadManagerState, DownloadMessageState},
     traits::managers::TaskManager,
 };
@@ -23,6 +24,7 @@ pub struct CoverDownloadManager {
     state: Addr<DownloadManagerState>,
     tasks: HashMap<Uuid, WeakAddr<CoverDownloadTask>>,
     notify: Arc<Notify>,
+    scheduler: TaskScheduler<CoverDownloadTask>,
 }
 
 impl CoverDownloadManager {
@@ -31,6 +33,7 @@ impl CoverDownloadManager {
             state,
             tasks: HashMap::new(),
             notify: Arc::new(Notify::new()),
+            scheduler: Default::default(),
         }
     }
 }
@@ -43,6 +46,7 @@ impl Actor for CoverDownloadManager {
 pub struct CoverDownloadMessage {
     id: Uuid,
     state: DownloadMessageState,
+    priority: TaskPriority,
 }
 
 impl CoverDownloadMessage {
@@ -50,11 +54,16 @@ impl CoverDownloadMessage {
         Self {
             id,
             state: DownloadMessageState::Pending,
+            priority: TaskPriority::Normal,
         }
     }
     pub fn state(self, state: DownloadMessageState) -> Self {
         Self { state, ..self }
     }
+    /// The priority of the task in the manager download queue
+    pub fn priority(self, priority: TaskPriority) -> Self {
+        Self { priority, ..self }
+    }
 }
 
 impl From<Uuid> for CoverDownloadMessage {
@@ -138,20 +147,22 @@ impl TaskManager for CoverDownloadManager {
         self.notify.notify_waiters();
 
         if let DownloadMessageState::Downloading = msg.state {
+            let id = msg.id;
+            let priority = msg.priority;
             let fut = async move {
                 trace!("getting task state");
                 let state = re_task.state().await?;
-                if !state.is_loading() {
-                    trace!("Sending StartDownload message");
-                    re_task.send(StartDownload).await?;
+                if !state.is_loading() && !state.is_queued() {
+                    Ok::<_, actix::MailboxError>(Some(re_task))
+                } else {
+                    Ok(None)
                 }
-                Ok::<_, actix::MailboxError>(())
             }
             .into_actor(self)
-            .map(|s, _, _| {
-                if let Err(err) = s {
-                    log::error!("{err}");
-                }
+            .map(move |s, this, ctx| match s {
+                Ok(Some(task)) => this.schedule_task(id, task, priority, ctx),
+                Ok(None) => {}
+                Err(err) => log::error!("{err}"),
             });
             ctx.wait(fut)
         }
@@ -164,11 +175,19 @@ impl TaskManager for CoverDownloadManager {
         {
             self.tasks.remove(&id);
         }
+        // The task is dropped or stopped, so it can't be started anymore
+        self.scheduler.remove(&id);
         self.notify.notify_waiters();
     }
     fn get_task(&self, id: Uuid) -> Option<Addr<Self::Task>> {
         self.tasks.get(&id).and_then(WeakAddr::upgrade)
     }
+    fn scheduler(&self) -> &TaskScheduler<Self::Task> {
+        &self.scheduler
+    }
+    fn scheduler_mut(&mut self) -> &mut TaskScheduler<Self::Task> {
+        &mut self.scheduler
+    }
 }
 
 impl Handler<CoverDownloadMessage> for CoverDownloadManager {
@@ -103,6 +103,8 @@ impl Handler<StopTask> for CoverDownloadTask {
     type Result = ();
     fn handle(&mut self, _msg: StopTask, ctx: &mut Self::Context) -> Self::Result {
         self.should_stop = true;
+        // Removes the task from the manager download queue
+        self.manager.do_send(DropSingleTaskMessage(self.id));
         ctx.terminate();
     }
 }
@@ -1,8 +1,10 @@
 use actix::prelude::*;
 use bytes::Buf;
 use futures_util::FutureExt;
+use mangadex_api::CDN_URL;
 use mangadex_api_schema_rust::v5::CoverObject;
 use mangadex_api_types_rust::RelationshipType;
+use url::Url;
 
 use crate::{
     data_push::cover::required_cover_references,
@@ -53,13 +55,30 @@ impl Download for Task {
                             .await?;
                         manager.verify_and_push(res.data.clone()).await?;
                         send_to_subscribers(DownloadTaskState::Loading(State::FetchingImage));
-                        let (_, image) = client
-                            .download()
-                            .cover()
-                            .build()?
-                            .via_cover_api_object(res.data.clone())
-                            .await;
-                        manager.push((res.data.clone(), image?.reader())).await?;
+                        let manga_id = res
+                            .data
+                            .find_first_relationships(RelationshipType::Manga)
+                            .ok_or(api_core::Error::MissingRelationships(vec![
+                                RelationshipType::Manga,
+                            ]))?
+                            .id;
+                        let image_url = Url::parse(&format!(
+                            "{CDN_URL}/covers/{manga_id}/{}",
+                            res.data.attributes.file_name
+                        ))
+                        .map_err(mangadex_api::error::Error::ParseUrlError)?;
+                        let response = client
+                            .get_reqwest_client()
+                            .await
+                            .get(image_url)
+                            .send()
+                            .await?;
+                        let image = manager
+                            .get_bandwidth_limiter()
+                            .await?
+                            .read_body(response)
+                            .await?;
+                        manager.push((res.data.clone(), image.reader())).await?;
                         history.remove_and_commit(entry).await?;
                         Ok(res.data)
                     }
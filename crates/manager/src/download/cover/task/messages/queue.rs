@@ -0,0 +1,20 @@
+use actix::Handler;
+
+use crate::download::{
+    cover::task::{CoverDownloadTask as Task, CoverDownloadTaskState as State},
+    messages::QueueTaskMessage,
+    traits::task::State as TaskStateTrait,
+};
+
+impl Handler<QueueTaskMessage> for Task {
+    type Result = ();
+    fn handle(&mut self, msg: QueueTaskMessage, _ctx: &mut Self::Context) -> Self::Result {
+        if self.state().is_loading() {
+            return;
+        }
+        self.send_to_subscrbers()(match msg {
+            QueueTaskMessage::Queued => State::Queued,
+            QueueTaskMessage::Rejected(err) => State::Error(err),
+        });
+    }
+}
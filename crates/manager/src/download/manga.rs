@@ -1,3 +1,6 @@
+#[cfg(test)]
+mod tests;
+
 pub mod messages;
 pub mod task;
 
@@ -10,10 +13,16 @@ use uuid::Uuid;
 
 use crate::download::messages::StopTask;
 
-use self::task::MangaDownloadTask;
+use self::task::{MangaDownloadOptions, MangaDownloadTask};
 
 use super::{
-    messages::{DropSingleTaskMessage, GetTaskMessage, StartDownload},
+    chapter::ChapterDownloadManager,
+    cover::CoverDownloadManager,
+    messages::{
+        DropSingleTaskMessage, FreeTaskSlotMessage, GetTaskMessage,
+        chapter::GetChapterDownloadManagerMessage, cover::GetCoverDownloadManagerMessage,
+    },
+    scheduler::{TaskPriority, TaskScheduler},
     state::{DownloadManagerState, DownloadMessageState},
     traits::{managers::TaskManager, task::AsyncState},
 };
@@ -23,14 +32,27 @@ pub struct MangaDownloadManager {
     state: Addr<DownloadManagerState>,
     tasks: HashMap<Uuid, WeakAddr<MangaDownloadTask>>,
     notify: Arc<Notify>,
+    scheduler: TaskScheduler<MangaDownloadTask>,
+    cover: Addr<CoverDownloadManager>,
+    chapter: Addr<ChapterDownloadManager>,
 }
 
 impl MangaDownloadManager {
-    pub fn new(state: Addr<DownloadManagerState>) -> Self {
+    /// Create a new manga manager.
+    ///
+    /// The manga tasks download their cover arts and chapters with the given managers.
+    pub fn new(
+        state: Addr<DownloadManagerState>,
+        cover: Addr<CoverDownloadManager>,
+        chapter: Addr<ChapterDownloadManager>,
+    ) -> Self {
         Self {
             state,
             tasks: HashMap::new(),
             notify: Arc::new(Notify::new()),
+            scheduler: Default::default(),
+            cover,
+            chapter,
         }
     }
 }
@@ -39,11 +61,12 @@ impl Actor for MangaDownloadManager {
     type Context = Context<Self>;
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct MangaDownloadMessage {
     id: Uuid,
-    // TODO Add cover_art download support
     state: DownloadMessageState,
+    priority: TaskPriority,
+    options: MangaDownloadOptions,
 }
 
 impl From<Uuid> for MangaDownloadMessage {
@@ -63,11 +86,21 @@ impl MangaDownloadMessage {
         Self {
             id,
             state: Default::default(),
+            priority: Default::default(),
+            options: Default::default(),
         }
     }
     pub fn state(self, state: DownloadMessageState) -> Self {
         Self { state, ..self }
     }
+    /// The priority of the task in the manager download queue
+    pub fn priority(self, priority: TaskPriority) -> Self {
+        Self { priority, ..self }
+    }
+    /// What should be downloaded with the manga (cover arts, chapters, ...)
+    pub fn options(self, options: MangaDownloadOptions) -> Self {
+        Self { options, ..self }
+    }
 }
 
 impl Message for MangaDownloadMessage {
@@ -83,6 +116,8 @@ impl TaskManager for MangaDownloadManager {
         {
             self.tasks.remove(&id);
         }
+        // The task is dropped or stopped, so it can't be started anymore
+        self.scheduler.remove(&id);
         self.notify.notify_waiters();
     }
     fn state(&self) -> Addr<DownloadManagerState> {
@@ -143,20 +178,25 @@ impl TaskManager for MangaDownloadManager {
         self.notify.notify_waiters();
 
         if let DownloadMessageState::Downloading = msg.state {
+            let id = msg.id;
+            let priority = msg.priority;
+            let options = msg.options;
             let fut = async move {
-                trace!("Getting task state");
-                let s = re_task.state().await?;
-                if !s.is_loading() {
-                    trace!("Sending start download message");
-                    re_task.send(StartDownload).await?;
+                trace!("getting task state");
+                let state = re_task.state().await?;
+                if !state.is_loading() && !state.is_queued() {
+                    trace!("Sending options message");
+                    re_task.send(options).await?;
+                    Ok::<_, actix::MailboxError>(Some(re_task))
+                } else {
+                    Ok(None)
                 }
-                Ok::<_, actix::MailboxError>(())
             }
             .into_actor(self)
-            .map(|s, _, _| {
-                if let Err(er) = s {
-                    log::error!("{er}");
-                }
+            .map(move |s, this, ctx| match s {
+                Ok(Some(task)) => this.schedule_task(id, task, priority, ctx),
+                Ok(None) => {}
+                Err(err) => log::error!("{err}"),
             });
             ctx.wait(fut)
         }
@@ -165,6 +205,12 @@ impl TaskManager for MangaDownloadManager {
     fn get_task(&self, id: Uuid) -> Option<Addr<Self::Task>> {
         self.tasks.get(&id).and_then(WeakAddr::upgrade)
     }
+    fn scheduler(&self) -> &TaskScheduler<Self::Task> {
+        &self.scheduler
+    }
+    fn scheduler_mut(&mut self) -> &mut TaskScheduler<Self::Task> {
+        &mut self.scheduler
+    }
 }
 
 impl Handler<MangaDownloadMessage> for MangaDownloadManager {
@@ -182,6 +228,15 @@ impl Handler<DropSingleTaskMessage> for MangaDownloadManager {
     }
 }
 
+impl Handler<FreeTaskSlotMessage> for MangaDownloadManager {
+    type Result = <FreeTaskSlotMessage as Message>::Result;
+    fn handle(&mut self, msg: FreeTaskSlotMessage, ctx: &mut Self::Context) -> Self::Result {
+        if self.scheduler_mut().finish(&msg.0) {
+            self.run_queued_tasks(ctx);
+        }
+    }
+}
+
 impl Handler<GetTaskMessage<MangaDownloadTask>> for MangaDownloadManager {
     type Result = <GetTaskMessage<MangaDownloadTask> as Message>::Result;
     fn handle(
@@ -201,3 +256,25 @@ impl Drop for MangaDownloadManager {
             .for_each(|task| task.do_send(StopTask));
     }
 }
+
+impl Handler<GetCoverDownloadManagerMessage> for MangaDownloadManager {
+    type Result = <GetCoverDownloadManagerMessage as Message>::Result;
+    fn handle(
+        &mut self,
+        _msg: GetCoverDownloadManagerMessage,
+        _ctx: &mut Self::Context,
+    ) -> Self::Result {
+        self.cover.clone()
+    }
+}
+
+impl Handler<GetChapterDownloadManagerMessage> for MangaDownloadManager {
+    type Result = <GetChapterDownloadManagerMessage as Message>::Result;
+    fn handle(
+        &mut self,
+        _msg: GetChapterDownloadManagerMessage,
+        _ctx: &mut Self::Context,
+    ) -> Self::Result {
+        self.chapter.clone()
+    }
+}
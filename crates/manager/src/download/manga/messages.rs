@@ -1,7 +1,10 @@
 use actix::{Handler, Message};
 
 use crate::download::{
-    messages::{state::GetManagerStateMessage, GetTasksListMessage, SubcribeToManagerMessage},
+    messages::{
+        state::GetManagerStateMessage, GetQueuedTasksListMessage, GetTasksListMessage,
+        SetMaxConcurrentTasksMessage, SetMaxQueuedTasksMessage, SubcribeToManagerMessage,
+    },
     traits::managers::TaskManager,
 };
 
@@ -27,3 +30,35 @@ impl Handler<GetTasksListMessage> for MangaDownloadManager {
         self.tasks_id()
     }
 }
+
+impl Handler<GetQueuedTasksListMessage> for MangaDownloadManager {
+    type Result = <GetQueuedTasksListMessage as Message>::Result;
+    fn handle(
+        &mut self,
+        _msg: GetQueuedTasksListMessage,
+        _ctx: &mut Self::Context,
+    ) -> Self::Result {
+        self.scheduler().queued_ids()
+    }
+}
+
+impl Handler<SetMaxConcurrentTasksMessage> for MangaDownloadManager {
+    type Result = <SetMaxConcurrentTasksMessage as Message>::Result;
+    fn handle(
+        &mut self,
+        msg: SetMaxConcurrentTasksMessage,
+        ctx: &mut Self::Context,
+    ) -> Self::Result {
+        let previous = self.scheduler_mut().set_max_concurrent_tasks(msg.0);
+        // A higher limit might free some slots
+        self.run_queued_tasks(ctx);
+        previous
+    }
+}
+
+impl Handler<SetMaxQueuedTasksMessage> for MangaDownloadManager {
+    type Result = <SetMaxQueuedTasksMessage as Message>::Result;
+    fn handle(&mut self, msg: SetMaxQueuedTasksMessage, _ctx: &mut Self::Context) -> Self::Result {
+        self.scheduler_mut().set_max_queued_tasks(msg.0)
+    }
+}
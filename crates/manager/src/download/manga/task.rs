@@ -3,14 +3,18 @@ pub mod messages;
 use std::{ops::Deref, sync::Arc};
 
 use actix::prelude::*;
+use api_core::data_pulls::chapter::ChapterListDataPullFilterParams;
 use futures_util::FutureExt;
 use log::debug;
+use mangadex_api::{MangaDexClient, v5::chapter::get::ListChapterBuilder};
 use mangadex_api_schema_rust::v5::MangaObject;
 use uuid::Uuid;
 
 use crate::{
     ArcRwLock,
+    data_push::chapter::ChapterRequiredRelationship,
     download::{
+        chapter::task::DownloadMode,
         messages::{DropSingleTaskMessage, StopTask, TaskSubscriberMessages},
         state::{DownloadTaskState, TaskState},
         traits::task::{Cancelable, State},
@@ -23,10 +27,130 @@ use crate::{
 
 use super::MangaDownloadManager;
 
+/// The progress of the cover arts or chapters downloaded along a manga
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MangaChildrenProgress {
+    pub total: usize,
+    pub done: usize,
+    pub failed: usize,
+}
+
+impl MangaChildrenProgress {
+    pub fn new(total: usize) -> Self {
+        Self {
+            total,
+            ..Default::default()
+        }
+    }
+    pub fn is_finished(&self) -> bool {
+        self.done + self.failed >= self.total
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum MangaDonwloadingState {
     Preloading,
     FetchingData,
+    FetchingCoverArts,
+    FetchingFeed,
+    DownloadingChildren {
+        covers: MangaChildrenProgress,
+        chapters: MangaChildrenProgress,
+    },
+}
+
+/// Which cover arts should be downloaded with the manga
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MangaCoverArtDownload {
+    #[default]
+    None,
+    /// Only the main cover art (the one in the manga relationships)
+    Main,
+    /// Every cover art of the manga
+    All,
+}
+
+/// The manga feed chapters that should be downloaded with the manga.
+///
+/// The feed is requested with the [`ChapterListDataPullFilterParams`]
+/// (the `manga_ids` field is ignored).
+#[derive(Debug, Clone, Default)]
+pub struct MangaFeedDownload {
+    pub filter: ChapterListDataPullFilterParams,
+    /// Skip the chapters whose volume is lower than this one
+    pub min_volume: Option<f32>,
+    /// Skip the chapters whose volume is greater than this one
+    pub max_volume: Option<f32>,
+    pub mode: DownloadMode,
+}
+
+impl MangaFeedDownload {
+    /// A chapter list request of the `manga` chapters matching the filter.
+    ///
+    /// The API can't filter a volume range, so the chapters still need [`Self::is_volume_valid`].
+    pub(crate) fn chapter_list(&self, client: &MangaDexClient, manga: Uuid) -> ListChapterBuilder {
+        let filter = &self.filter;
+        let mut request = client.chapter().get();
+        request
+            .manga_id(manga)
+            .groups(filter.groups.clone())
+            .uploaders(filter.uploaders.clone())
+            .volumes(filter.volumes.clone())
+            .chapters(filter.chapters.clone())
+            .translated_languages(filter.translated_languages.clone())
+            .original_languages(filter.original_languages.clone())
+            .excluded_original_languages(filter.excluded_original_languages.clone())
+            .content_rating(filter.content_rating.clone())
+            .excluded_groups(filter.excluded_groups.clone())
+            .excluded_uploaders(filter.excluded_uploaders.clone())
+            .includes(ChapterRequiredRelationship::get_includes());
+        if let Some(title) = filter.title.clone() {
+            request.title(title);
+        }
+        if let Some(created_at_since) = filter.created_at_since {
+            request.created_at_since(created_at_since);
+        }
+        if let Some(updated_at_since) = filter.updated_at_since {
+            request.updated_at_since(updated_at_since);
+        }
+        if let Some(publish_at_since) = filter.publish_at_since {
+            request.publish_at_since(publish_at_since);
+        }
+        request
+    }
+    pub(crate) fn is_volume_valid(&self, volume: Option<&str>) -> bool {
+        if self.min_volume.is_none() && self.max_volume.is_none() {
+            return true;
+        }
+        let Some(volume) = volume.and_then(|v| v.parse::<f32>().ok()) else {
+            return false;
+        };
+        self.min_volume.is_none_or(|min| volume >= min)
+            && self.max_volume.is_none_or(|max| volume <= max)
+    }
+}
+
+/// What should be downloaded along the manga data
+#[derive(Debug, Clone, Default)]
+pub struct MangaDownloadOptions {
+    pub cover_art: MangaCoverArtDownload,
+    pub feed: Option<MangaFeedDownload>,
+}
+
+impl MangaDownloadOptions {
+    pub fn cover_art(self, cover_art: MangaCoverArtDownload) -> Self {
+        Self { cover_art, ..self }
+    }
+    pub fn feed(self, feed: MangaFeedDownload) -> Self {
+        Self {
+            feed: Some(feed),
+            ..self
+        }
+    }
+}
+
+impl Message for MangaDownloadOptions {
+    type Result = ();
 }
 
 pub type MangaDownloadTaskState = DownloadTaskState<MangaObject, MangaDonwloadingState>;
@@ -34,6 +158,7 @@ pub type MangaDownloadTaskState = DownloadTaskState<MangaObject, MangaDonwloadin
 #[derive(Debug, MessageResponse)]
 pub struct MangaDownloadTask {
     id: Uuid,
+    options: MangaDownloadOptions,
     handle: Option<SpawnHandle>,
     state: ArcRwLock<MangaDownloadTaskState>,
     subscribers: Recipients<TaskSubscriberMessages<MangaDownloadTaskState>>,
@@ -90,6 +215,8 @@ impl Handler<StopTask> for MangaDownloadTask {
     type Result = ();
     fn handle(&mut self, _msg: StopTask, ctx: &mut Self::Context) -> Self::Result {
         self.should_stop = true;
+        // Removes the task from the manager download queue
+        self.manager.do_send(DropSingleTaskMessage(self.id));
         ctx.terminate();
     }
 }
@@ -114,6 +241,7 @@ impl MangaDownloadTask {
     pub(super) fn new(id: Uuid, manager: Addr<MangaDownloadManager>) -> Self {
         Self {
             id,
+            options: Default::default(),
             handle: None,
             state: Default::default(),
             manager,
@@ -123,6 +251,15 @@ impl MangaDownloadTask {
     }
 }
 
+impl Handler<MangaDownloadOptions> for MangaDownloadTask {
+    type Result = <MangaDownloadOptions as Message>::Result;
+    fn handle(&mut self, msg: MangaDownloadOptions, _ctx: &mut Self::Context) -> Self::Result {
+        if !self.state().is_loading() {
+            self.options = msg;
+        }
+    }
+}
+
 impl Handler<FilesDirSubscriberMessage> for MangaDownloadTask {
     type Result = ();
     fn handle(&mut self, msg: FilesDirSubscriberMessage, ctx: &mut Self::Context) -> Self::Result {
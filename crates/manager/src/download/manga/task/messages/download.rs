@@ -1,23 +1,42 @@
+use std::{future::Future, pin::Pin};
+
 use actix::prelude::*;
 use futures_util::FutureExt;
 use mangadex_api_schema_rust::v5::MangaObject;
 use mangadex_api_types_rust::RelationshipType;
 
 use crate::{
+    ManagerCoreResult,
     data_push::manga::MangaRequiredRelationship,
     download::{
-        manga::task::{MangaDonwloadingState, MangaDownloadTask},
-        messages::StartDownload,
-        state::{messages::get::GetManagerStateData, DownloadTaskState, TaskState},
-        traits::task::{Download, State as TaskStateTrait},
+        chapter::ChapterDownloadMessage,
+        cover::CoverDownloadMessage,
+        manga::task::{
+            MangaChildrenProgress, MangaCoverArtDownload, MangaDonwloadingState,
+            MangaDownloadTask,
+        },
+        messages::{
+            FreeTaskSlotMessage, StartDownload, chapter::GetChapterDownloadManager,
+            cover::GetCoverDownloadManager,
+        },
+        state::{
+            DownloadMessageState, DownloadTaskState, TaskState, WaitForFinishedError,
+            messages::get::GetManagerStateData,
+        },
+        traits::task::{AsyncCanBeWaited, Download, State as TaskStateTrait},
     },
     history::{
-        history_w_file::traits::{AsyncAutoCommitRollbackInsert, AsyncAutoCommitRollbackRemove},
         HistoryEntry,
+        history_w_file::traits::{AsyncAutoCommitRollbackInsert, AsyncAutoCommitRollbackRemove},
     },
     prelude::PushActorAddr,
-    ManagerCoreResult,
 };
+
+const CHAPTER_PAGE_LIMIT: u32 = 100;
+const COVER_PAGE_LIMIT: u32 = 100;
+
+type ChildWait = Pin<Box<dyn Future<Output = Result<(), WaitForFinishedError>> + Send + 'static>>;
+
 impl MangaDownloadTask {
     fn preloading(&self) {
         self.send_to_subscrbers()(DownloadTaskState::Loading(
@@ -32,6 +51,7 @@ impl Download for MangaDownloadTask {
             let manager = self.manager.clone();
 
             let id = self.id;
+            let options = self.options.clone();
 
             let entry = HistoryEntry::new(id, RelationshipType::Manga);
             let send_to_subs = self.send_to_subscrbers();
@@ -54,6 +74,143 @@ impl Download for MangaDownloadTask {
                             .await?;
                         manager.verify_and_push(res.data.clone()).await?;
                         history.remove_and_commit(entry).await?;
+
+                        // Getting the cover arts to download
+                        let mut covers = Vec::new();
+                        match options.cover_art {
+                            MangaCoverArtDownload::None => {}
+                            MangaCoverArtDownload::Main => {
+                                covers.extend(
+                                    res.data
+                                        .find_first_relationships(RelationshipType::CoverArt)
+                                        .map(|rel| rel.id),
+                                );
+                            }
+                            MangaCoverArtDownload::All => {
+                                send_to_subs(DownloadTaskState::Loading(
+                                    MangaDonwloadingState::FetchingCoverArts,
+                                ));
+                                let mut offset = 0;
+                                loop {
+                                    let list = client
+                                        .cover()
+                                        .get()
+                                        .manga_ids(vec![id])
+                                        .limit(COVER_PAGE_LIMIT)
+                                        .offset(offset)
+                                        .send()
+                                        .await?;
+                                    let len = list.data.len() as u32;
+                                    covers.extend(list.data.into_iter().map(|cover| cover.id));
+                                    offset += len;
+                                    if len == 0 || offset >= list.total {
+                                        break;
+                                    }
+                                }
+                            }
+                        }
+
+                        // Getting the feed chapters to download
+                        let mut chapters = Vec::new();
+                        if let Some(feed) = options.feed.as_ref() {
+                            send_to_subs(DownloadTaskState::Loading(
+                                MangaDonwloadingState::FetchingFeed,
+                            ));
+                            let mut request = feed.chapter_list(&client, id);
+                            request.limit(CHAPTER_PAGE_LIMIT);
+                            let mut offset = 0;
+                            loop {
+                                let list = request.offset(offset).send().await?;
+                                let len = list.data.len() as u32;
+                                chapters.extend(
+                                    list.data
+                                        .into_iter()
+                                        .filter(|chapter| {
+                                            feed.is_volume_valid(
+                                                chapter.attributes.volume.as_deref(),
+                                            )
+                                        })
+                                        .map(|chapter| chapter.id),
+                                );
+                                offset += len;
+                                if len == 0 || offset >= list.total {
+                                    break;
+                                }
+                            }
+                        }
+
+                        if covers.is_empty() && chapters.is_empty() {
+                            return Ok(res.data);
+                        }
+
+                        // Queueing the children tasks
+                        let mut covers_progress = MangaChildrenProgress::new(covers.len());
+                        let mut chapters_progress = MangaChildrenProgress::new(chapters.len());
+                        let mut waits: Vec<(RelationshipType, ChildWait)> = Vec::new();
+                        let cover_manager = manager.get_cover_manager().await?;
+                        for cover_id in covers {
+                            // We subscribe to the task before starting it so we can't miss its final state
+                            let mut task = cover_manager
+                                .send(CoverDownloadMessage::new(cover_id))
+                                .await?;
+                            let wait = task.wait().await?;
+                            cover_manager
+                                .send(
+                                    CoverDownloadMessage::new(cover_id)
+                                        .state(DownloadMessageState::Downloading),
+                                )
+                                .await?;
+                            waits.push((
+                                RelationshipType::CoverArt,
+                                Box::pin(wait.map(|res| res.map(|_| ()))),
+                            ));
+                        }
+                        let chapter_manager = manager.get_chapter_manager().await?;
+                        let mode = options
+                            .feed
+                            .as_ref()
+                            .map(|feed| feed.mode)
+                            .unwrap_or_default();
+                        for chapter_id in chapters {
+                            let mut task = chapter_manager
+                                .send(ChapterDownloadMessage::new(chapter_id).mode(mode))
+                                .await?;
+                            let wait = task.wait().await?;
+                            chapter_manager
+                                .send(
+                                    ChapterDownloadMessage::new(chapter_id)
+                                        .mode(mode)
+                                        .state(DownloadMessageState::Downloading),
+                                )
+                                .await?;
+                            waits.push((
+                                RelationshipType::Chapter,
+                                Box::pin(wait.map(|res| res.map(|_| ()))),
+                            ));
+                        }
+                        let send_progress = |covers, chapters| {
+                            send_to_subs(DownloadTaskState::Loading(
+                                MangaDonwloadingState::DownloadingChildren { covers, chapters },
+                            ));
+                        };
+                        send_progress(covers_progress, chapters_progress);
+                        // Only waiting for the children from now, which run in their own managers
+                        manager.send(FreeTaskSlotMessage(id)).await?;
+                        for (data_type, wait) in waits {
+                            let progress = if data_type == RelationshipType::CoverArt {
+                                &mut covers_progress
+                            } else {
+                                &mut chapters_progress
+                            };
+                            match wait.await {
+                                Ok(()) => progress.done += 1,
+                                Err(err) => {
+                                    log::error!("[manga|{id}]>{data_type:?} - {err}");
+                                    progress.failed += 1;
+                                }
+                            }
+                            send_progress(covers_progress, chapters_progress);
+                        }
                         Ok(res.data)
                     }
                     .map(move |res: ManagerCoreResult<MangaObject>| match res {
@@ -0,0 +1,20 @@
+use actix::Handler;
+
+use crate::download::{
+    manga::task::{MangaDownloadTask as Task, MangaDownloadTaskState as State},
+    messages::QueueTaskMessage,
+    traits::task::State as TaskStateTrait,
+};
+
+impl Handler<QueueTaskMessage> for Task {
+    type Result = ();
+    fn handle(&mut self, msg: QueueTaskMessage, _ctx: &mut Self::Context) -> Self::Result {
+        if self.state().is_loading() {
+            return;
+        }
+        self.send_to_subscrbers()(match msg {
+            QueueTaskMessage::Queued => State::Queued,
+            QueueTaskMessage::Rejected(err) => State::Error(err),
+        });
+    }
+}
@@ -0,0 +1,106 @@
+use actix::prelude::*;
+use api_core::{
+    DirsOptions as DirsOptionsCore, data_pulls::chapter::ChapterListDataPullFilterParams,
+};
+use mangadex_api::MangaDexClient;
+use mangadex_api_types_rust::Language;
+use tempfile::TempDir;
+use uuid::Uuid;
+
+use crate::{
+    DirsOptions,
+    download::{
+        chapter::ChapterDownloadManager,
+        cover::CoverDownloadManager,
+        messages::{FreeTaskSlotMessage, GetQueuedTasksListMessage},
+        scheduler::TaskPriority,
+        state::DownloadManagerState,
+        traits::managers::TaskManager,
+    },
+    history::service::HistoryActorService,
+};
+
+use super::{
+    MangaDownloadManager,
+    task::{MangaDownloadTask, MangaFeedDownload},
+};
+
+#[test]
+fn the_feed_filter_is_sent_with_the_request() {
+    let manga = Uuid::new_v4();
+    let group = Uuid::new_v4();
+    let feed = MangaFeedDownload {
+        filter: ChapterListDataPullFilterParams {
+            translated_languages: vec![Language::English],
+            volumes: vec![String::from("2")],
+            groups: vec![group],
+            // The request is already limited to the manga
+            manga_ids: vec![Uuid::new_v4()],
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let request = feed
+        .chapter_list(&MangaDexClient::default(), manga)
+        .build()
+        .unwrap();
+    assert_eq!(request.manga_id, Some(manga));
+    assert_eq!(request.translated_languages, vec![Language::English]);
+    assert_eq!(request.volumes, vec![String::from("2")]);
+    assert_eq!(request.groups, vec![group]);
+}
+
+#[test]
+fn the_volume_range_is_inclusive() {
+    let feed = MangaFeedDownload {
+        min_volume: Some(2.0),
+        max_volume: Some(3.0),
+        ..Default::default()
+    };
+    assert!(!feed.is_volume_valid(Some("1")));
+    assert!(feed.is_volume_valid(Some("2")));
+    assert!(feed.is_volume_valid(Some("3")));
+    assert!(!feed.is_volume_valid(Some("3.5")));
+    assert!(!feed.is_volume_valid(None));
+    assert!(MangaFeedDownload::default().is_volume_valid(None));
+}
+
+#[actix::test]
+async fn a_freed_slot_starts_the_next_queued_task() {
+    let tmp = TempDir::new().unwrap();
+    let dirs = DirsOptionsCore::new_from_data_dir(tmp.path());
+    dirs.init_dirs().unwrap();
+    let dirs = DirsOptions::from(dirs).start();
+    let history = HistoryActorService::new(dirs.clone()).start();
+    let state = DownloadManagerState::new(dirs, MangaDexClient::default(), history).start();
+    let cover = CoverDownloadManager::new(state.clone()).start();
+    let chapter = ChapterDownloadManager::new(state.clone()).start();
+
+    let running = Uuid::new_v4();
+    let queued = Uuid::new_v4();
+    let manager = MangaDownloadManager::create(|ctx| {
+        let mut manager = MangaDownloadManager::new(state, cover, chapter);
+        let scheduler = manager.scheduler_mut();
+        scheduler.set_max_concurrent_tasks(Some(1));
+        for id in [running, queued] {
+            let task = MangaDownloadTask::new(id, ctx.address()).start();
+            scheduler.push(id, task, TaskPriority::Normal).unwrap();
+        }
+        // The first task is waiting for its children
+        assert_eq!(scheduler.pop_next().map(|(id, _)| id), Some(running));
+        manager
+    });
+    assert_eq!(
+        manager.send(GetQueuedTasksListMessage).await.unwrap(),
+        vec![queued]
+    );
+
+    manager.send(FreeTaskSlotMessage(running)).await.unwrap();
+    assert!(
+        manager
+            .send(GetQueuedTasksListMessage)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
@@ -1,6 +1,8 @@
 pub mod chapter;
 pub mod cover;
 pub mod manga;
+pub mod repair;
+pub mod resume;
 pub mod state;
 
 use std::{fmt::Debug, marker::PhantomData, sync::Arc};
@@ -9,7 +11,7 @@ use actix::prelude::*;
 use tokio::sync::Notify;
 use uuid::Uuid;
 
-use crate::recipients::MaybeWeakRecipient;
+use crate::{OwnedError, recipients::MaybeWeakRecipient};
 
 use super::state::{TaskState, WaitForFinished};
 
@@ -20,6 +22,15 @@ impl Message for DropSingleTaskMessage {
     type Result = ();
 }
 
+/// Sent by a running task to free its scheduler slot before it finishes,
+/// so that it doesn't block the queued tasks while it only waits for other tasks.
+#[derive(Debug, Clone, Copy)]
+pub struct FreeTaskSlotMessage(pub Uuid);
+
+impl Message for FreeTaskSlotMessage {
+    type Result = ();
+}
+
 #[non_exhaustive]
 #[derive(Clone, Copy, Debug)]
 pub enum TaskSubscriberMessages<State> {
@@ -82,6 +93,47 @@ impl Message for GetTasksListMessage {
     type Result = Vec<Uuid>;
 }
 
+pub struct GetQueuedTasksListMessage;
+
+impl Message for GetQueuedTasksListMessage {
+    type Result = Vec<Uuid>;
+}
+
+/// Set the manager maximum concurrent tasks.
+///
+/// `None` removes the limit.
+/// Returns the previous limit.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SetMaxConcurrentTasksMessage(pub Option<u16>);
+
+impl Message for SetMaxConcurrentTasksMessage {
+    type Result = Option<u16>;
+}
+
+/// Set the manager maximum queued tasks.
+///
+/// `None` removes the limit.
+/// Lowering the limit doesn't remove the already queued tasks.
+/// Returns the previous limit.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SetMaxQueuedTasksMessage(pub Option<u16>);
+
+impl Message for SetMaxQueuedTasksMessage {
+    type Result = Option<u16>;
+}
+
+/// Sent by a manager scheduler to a task when it is put in the download queue
+/// or when the queue refused it.
+#[derive(Debug, Clone)]
+pub enum QueueTaskMessage {
+    Queued,
+    Rejected(OwnedError),
+}
+
+impl Message for QueueTaskMessage {
+    type Result = ();
+}
+
 pub struct SubcribeToManagerMessage;
 
 impl Message for SubcribeToManagerMessage {
@@ -0,0 +1,142 @@
+#[cfg(test)]
+mod tests;
+
+use std::future::Future;
+
+use actix::prelude::*;
+use api_core::{
+    data_push::chapter::image::Mode,
+    file_dirs::verification::{LibraryIssue, RepairAction},
+};
+use dev::ToEnvelope;
+use mangadex_api_types_rust::RelationshipType;
+
+use crate::{
+    Error, ManagerCoreResult,
+    download::{
+        DownloadManager,
+        chapter::{ChapterDownloadMessage, task::DownloadMode},
+        cover::CoverDownloadMessage,
+        manga::MangaDownloadMessage,
+        state::{DownloadMessageState, messages::get::GetManagerStateData},
+    },
+    files_dirs::messages::check::CheckLibraryAsyncTrait,
+    history::{HistoryEntry, history_w_file::traits::AsyncAutoCommitRollbackInsert},
+};
+
+/// Repair a [`LibraryIssue`] found by a
+/// [`CheckLibraryMessage`](crate::files_dirs::messages::check::CheckLibraryMessage).
+///
+/// [`RepairAction::Redownload`] only queues the download, it doesn't wait for it to finish.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RepairLibraryIssueMessage {
+    pub issue: LibraryIssue,
+    pub action: RepairAction,
+}
+
+impl RepairLibraryIssueMessage {
+    pub fn new(issue: LibraryIssue, action: RepairAction) -> Self {
+        Self { issue, action }
+    }
+}
+
+impl Message for RepairLibraryIssueMessage {
+    type Result = ManagerCoreResult<()>;
+}
+
+impl Handler<RepairLibraryIssueMessage> for DownloadManager {
+    type Result = ResponseFuture<<RepairLibraryIssueMessage as Message>::Result>;
+    fn handle(
+        &mut self,
+        msg: RepairLibraryIssueMessage,
+        _ctx: &mut Self::Context,
+    ) -> Self::Result {
+        let state = self.state.clone();
+        let manga = self.manga.clone();
+        let cover = self.cover.clone();
+        let chapter = self.chapter.clone();
+        Box::pin(async move {
+            let RepairLibraryIssueMessage { issue, action } = msg;
+            if !issue.can_be_repaired_with(action) {
+                return Err(api_core::Error::UnsupportedRepairAction(action).into());
+            }
+            let (id, data_type) = issue.download_target();
+            let data_saver = matches!(
+                issue,
+                LibraryIssue::EmptyChapterImage {
+                    mode: Mode::DataSaver,
+                    ..
+                }
+            );
+            match action {
+                RepairAction::Delete => {
+                    state.delete_library_issue(issue).await?;
+                }
+                RepairAction::AddToHistory => {
+                    let mut entry = HistoryEntry::new(id, data_type);
+                    // The data saver images should be resumed in the same mode
+                    if data_saver {
+                        entry.set_mode(Some(DownloadMode::DataSaver));
+                    }
+                    let mut history = state.get_history().await?;
+                    history.insert_and_commit(entry).await?;
+                }
+                RepairAction::Redownload => match data_type {
+                    RelationshipType::Manga => {
+                        manga
+                            .send(
+                                MangaDownloadMessage::new(id)
+                                    .state(DownloadMessageState::Downloading),
+                            )
+                            .await?;
+                    }
+                    RelationshipType::CoverArt => {
+                        cover
+                            .send(
+                                CoverDownloadMessage::new(id)
+                                    .state(DownloadMessageState::Downloading),
+                            )
+                            .await?;
+                    }
+                    _ => {
+                        let mode = if data_saver {
+                            DownloadMode::DataSaver
+                        } else {
+                            DownloadMode::Normal
+                        };
+                        chapter
+                            .send(
+                                ChapterDownloadMessage::new(id)
+                                    .mode(mode)
+                                    .state(DownloadMessageState::Downloading),
+                            )
+                            .await?;
+                    }
+                },
+            }
+            Ok::<_, Error>(())
+        })
+    }
+}
+
+pub trait RepairLibraryIssueAsyncTrait: Sync {
+    fn repair_library_issue(
+        &self,
+        issue: LibraryIssue,
+        action: RepairAction,
+    ) -> impl Future<Output = ManagerCoreResult<()>> + Send;
+}
+
+impl<A> RepairLibraryIssueAsyncTrait for Addr<A>
+where
+    A: Actor + Handler<RepairLibraryIssueMessage>,
+    <A as Actor>::Context: ToEnvelope<A, RepairLibraryIssueMessage>,
+{
+    async fn repair_library_issue(
+        &self,
+        issue: LibraryIssue,
+        action: RepairAction,
+    ) -> ManagerCoreResult<()> {
+        self.send(RepairLibraryIssueMessage::new(issue, action)).await?
+    }
+}
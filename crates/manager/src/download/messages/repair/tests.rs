@@ -0,0 +1,62 @@
+use actix::prelude::*;
+use api_core::{
+    DirsOptions as DirsOptionsCore,
+    data_push::chapter::image::Mode,
+    file_dirs::verification::{LibraryIssue, RepairAction},
+};
+use mangadex_api::MangaDexClient;
+use tempfile::TempDir;
+use uuid::Uuid;
+
+use crate::{
+    DirsOptions,
+    download::{
+        DownloadManager, chapter::task::DownloadMode, state::messages::get::GetManagerStateData,
+    },
+    history::service::messages::entries::GetHistoryEntriesMessage,
+};
+
+use super::RepairLibraryIssueAsyncTrait;
+
+#[actix::test]
+async fn the_history_keeps_the_data_saver_mode() {
+    let tmp = TempDir::new().unwrap();
+    let dirs = DirsOptionsCore::new_from_data_dir(tmp.path());
+    dirs.init_dirs().unwrap();
+    let dirs = DirsOptions::from(dirs).start();
+    let manager = DownloadManager::new(dirs, MangaDexClient::default());
+    let state = manager.state.clone();
+    let manager = manager.start();
+
+    let data_saver = Uuid::new_v4();
+    let without_data = Uuid::new_v4();
+    for issue in [
+        LibraryIssue::EmptyChapterImage {
+            id: data_saver,
+            mode: Mode::DataSaver,
+            filename: "1.png".into(),
+        },
+        LibraryIssue::ChapterWithoutData { id: without_data },
+    ] {
+        manager
+            .repair_library_issue(issue, RepairAction::AddToHistory)
+            .await
+            .unwrap();
+    }
+
+    let entries = state
+        .get_history()
+        .await
+        .unwrap()
+        .send(GetHistoryEntriesMessage(None))
+        .await
+        .unwrap();
+    let mode = |id: Uuid| {
+        entries
+            .iter()
+            .find(|entry| entry.get_id() == id)
+            .map(|entry| entry.get_mode())
+    };
+    assert_eq!(mode(data_saver), Some(Some(DownloadMode::DataSaver)));
+    assert_eq!(mode(without_data), Some(None));
+}
@@ -0,0 +1,164 @@
+use std::future::Future;
+
+use actix::prelude::*;
+use dev::ToEnvelope;
+use mangadex_api_types_rust::RelationshipType;
+
+use crate::{
+    Error, ManagerCoreResult,
+    download::{
+        DownloadManager,
+        chapter::{ChapterDownloadMessage, task::DownloadMode},
+        cover::CoverDownloadMessage,
+        manga::MangaDownloadMessage,
+        state::{DownloadMessageState, WaitForFinishedError, messages::get::GetManagerStateData},
+        traits::task::AsyncCanBeWaited,
+    },
+    history::{HistoryEntry, service::messages::entries::GetHistoryEntriesMessage},
+};
+
+/// Restart every download that is still in the history files.
+///
+/// An entry stays in the history until its download finishes successfully,
+/// so this allows recovering from a crash or an interrupted batch.
+/// The chapters are downloaded again with the mode stored in their history entry.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ResumeUnfinishedDownloads {
+    chapter_mode: Option<DownloadMode>,
+}
+
+impl ResumeUnfinishedDownloads {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// The mode used to download the chapters whose mode isn't in the history
+    /// (e.g. the entries written by an older version).
+    ///
+    /// Default: [`DownloadMode::Normal`]
+    pub fn chapter_mode<M: Into<DownloadMode>>(self, mode: M) -> Self {
+        Self {
+            chapter_mode: Some(mode.into()),
+        }
+    }
+}
+
+/// The result of a [`ResumeUnfinishedDownloads`] message
+#[derive(Debug, Default)]
+pub struct ResumeUnfinishedDownloadsReport {
+    /// The entries that were downloaded successfully
+    pub done: Vec<HistoryEntry>,
+    /// The entries whose download failed or was canceled
+    pub failed: Vec<(HistoryEntry, WaitForFinishedError)>,
+    /// The entries that can't be downloaded (e.g. unsupported relationship types)
+    pub skipped: Vec<HistoryEntry>,
+}
+
+impl ResumeUnfinishedDownloadsReport {
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+impl Message for ResumeUnfinishedDownloads {
+    type Result = ManagerCoreResult<ResumeUnfinishedDownloadsReport>;
+}
+
+type ResumeFuture =
+    std::pin::Pin<Box<dyn Future<Output = Result<(), WaitForFinishedError>> + Send + 'static>>;
+
+impl Handler<ResumeUnfinishedDownloads> for DownloadManager {
+    type Result = ResponseFuture<<ResumeUnfinishedDownloads as Message>::Result>;
+    fn handle(&mut self, msg: ResumeUnfinishedDownloads, _ctx: &mut Self::Context) -> Self::Result {
+        let state = self.state.clone();
+        let manga = self.manga.clone();
+        let cover = self.cover.clone();
+        let chapter = self.chapter.clone();
+        let chapter_mode = msg.chapter_mode.unwrap_or(DownloadMode::Normal);
+        Box::pin(async move {
+            let history = state.get_history().await?;
+            let entries = history.send(GetHistoryEntriesMessage(None)).await?;
+            log::debug!("Resuming {} unfinished downloads", entries.len());
+            let mut report = ResumeUnfinishedDownloadsReport::default();
+            let mut waits: Vec<(HistoryEntry, ResumeFuture)> = Vec::new();
+            for entry in entries {
+                let id = entry.get_id();
+                // We subscribe to the task before starting it so we can't miss its final state
+                let wait: ResumeFuture = match entry.get_data_type() {
+                    RelationshipType::Manga => {
+                        let mut task = manga.send(MangaDownloadMessage::new(id)).await?;
+                        let wait = task.wait().await?;
+                        manga
+                            .send(
+                                MangaDownloadMessage::new(id)
+                                    .state(DownloadMessageState::Downloading),
+                            )
+                            .await?;
+                        Box::pin(async move { wait.await.map(|_| ()) })
+                    }
+                    RelationshipType::CoverArt => {
+                        let mut task = cover.send(CoverDownloadMessage::new(id)).await?;
+                        let wait = task.wait().await?;
+                        cover
+                            .send(
+                                CoverDownloadMessage::new(id)
+                                    .state(DownloadMessageState::Downloading),
+                            )
+                            .await?;
+                        Box::pin(async move { wait.await.map(|_| ()) })
+                    }
+                    RelationshipType::Chapter => {
+                        let chapter_mode = entry.get_mode().unwrap_or(chapter_mode);
+                        let mut task = chapter
+                            .send(ChapterDownloadMessage::new(id).mode(chapter_mode))
+                            .await?;
+                        let wait = task.wait().await?;
+                        chapter
+                            .send(
+                                ChapterDownloadMessage::new(id)
+                                    .mode(chapter_mode)
+                                    .state(DownloadMessageState::Downloading),
+                            )
+                            .await?;
+                        Box::pin(async move { wait.await.map(|_| ()) })
+                    }
+                    _ => {
+                        report.skipped.push(entry);
+                        continue;
+                    }
+                };
+                waits.push((entry, wait));
+            }
+            // The tasks are already running in their managers, we just need to collect the results
+            for (entry, wait) in waits {
+                match wait.await {
+                    Ok(()) => report.done.push(entry),
+                    Err(err) => {
+                        log::error!("[{:?}|{}] {err}", entry.get_data_type(), entry.get_id());
+                        report.failed.push((entry, err));
+                    }
+                }
+            }
+            Ok::<_, Error>(report)
+        })
+    }
+}
+
+pub trait ResumeUnfinishedDownloadsAsyncTrait: Sync {
+    fn resume_unfinished_downloads(
+        &self,
+        msg: ResumeUnfinishedDownloads,
+    ) -> impl Future<Output = ManagerCoreResult<ResumeUnfinishedDownloadsReport>> + Send;
+}
+
+impl<A> ResumeUnfinishedDownloadsAsyncTrait for Addr<A>
+where
+    A: Actor + Handler<ResumeUnfinishedDownloads>,
+    <A as Actor>::Context: ToEnvelope<A, ResumeUnfinishedDownloads>,
+{
+    async fn resume_unfinished_downloads(
+        &self,
+        msg: ResumeUnfinishedDownloads,
+    ) -> ManagerCoreResult<ResumeUnfinishedDownloadsReport> {
+        self.send(msg).await?
+    }
+}
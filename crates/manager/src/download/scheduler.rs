@@ -0,0 +1,230 @@
+#[cfg(test)]
+mod tests;
+
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap, HashSet},
+    future::Future,
+};
+
+use actix::{WeakAddr, prelude::*};
+use uuid::Uuid;
+
+use crate::{
+    Error, MailBoxResult, ManagerCoreResult,
+    download::{
+        messages::{QueueTaskMessage, StartDownload, TaskStateMessage, WaitForFinishedMessage},
+        traits::task::CanBeWaited,
+    },
+};
+
+/// The priority of a queued download task.
+///
+/// Tasks with a higher priority are started first.
+/// Tasks with the same priority are started in FIFO order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum TaskPriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+type QueueKey = (Reverse<TaskPriority>, u64);
+
+/// A scheduler that limits how many download tasks of a manager can run at the same time.
+///
+/// Tasks that can't be started right away are put in a priority queue
+/// and are started as soon as a running task finishes.
+/// The queue only keeps weak addresses, so a stopped task doesn't stay alive in it.
+#[derive(Debug)]
+pub struct TaskScheduler<T>
+where
+    T: Actor,
+{
+    max_concurrent_tasks: Option<u16>,
+    max_queued_tasks: Option<u16>,
+    running: HashSet<Uuid>,
+    queue: BTreeMap<QueueKey, (Uuid, WeakAddr<T>)>,
+    queued: HashMap<Uuid, QueueKey>,
+    next_seq: u64,
+}
+
+impl<T> Default for TaskScheduler<T>
+where
+    T: Actor,
+{
+    fn default() -> Self {
+        Self {
+            max_concurrent_tasks: None,
+            max_queued_tasks: None,
+            running: Default::default(),
+            queue: Default::default(),
+            queued: Default::default(),
+            next_seq: 0,
+        }
+    }
+}
+
+impl<T> TaskScheduler<T>
+where
+    T: Actor,
+{
+    pub fn new(max_concurrent_tasks: Option<u16>) -> Self {
+        Self {
+            max_concurrent_tasks,
+            ..Default::default()
+        }
+    }
+    /// The maximum number of tasks that can run at the same time.
+    ///
+    /// `None` means no limit.
+    pub fn max_concurrent_tasks(&self) -> Option<u16> {
+        self.max_concurrent_tasks
+    }
+    pub fn set_max_concurrent_tasks(&mut self, limit: Option<u16>) -> Option<u16> {
+        std::mem::replace(&mut self.max_concurrent_tasks, limit)
+    }
+    /// The maximum number of tasks that can wait in the queue.
+    ///
+    /// Pushing a task into a full queue gives an [`Error::DownloadTaskLimitExceded`].
+    pub fn max_queued_tasks(&self) -> Option<u16> {
+        self.max_queued_tasks
+    }
+    /// Lowering the limit doesn't remove the already queued tasks.
+    pub fn set_max_queued_tasks(&mut self, limit: Option<u16>) -> Option<u16> {
+        std::mem::replace(&mut self.max_queued_tasks, limit)
+    }
+    pub fn running_len(&self) -> usize {
+        self.running.len()
+    }
+    pub fn queued_len(&self) -> usize {
+        self.queued.len()
+    }
+    pub fn is_running(&self, id: &Uuid) -> bool {
+        self.running.contains(id)
+    }
+    pub fn is_queued(&self, id: &Uuid) -> bool {
+        self.queued.contains_key(id)
+    }
+    /// The queued tasks ids in the order they will be started
+    pub fn queued_ids(&self) -> Vec<Uuid> {
+        self.queue
+            .values()
+            .filter(|(_, task)| task.upgrade().is_some())
+            .map(|(id, _)| *id)
+            .collect()
+    }
+    /// Remove the queued tasks that have been stopped.
+    fn remove_stopped(&mut self) {
+        let queued = &mut self.queued;
+        self.queue.retain(|_, (id, task)| {
+            let is_alive = task.upgrade().is_some();
+            if !is_alive {
+                queued.remove(id);
+            }
+            is_alive
+        });
+    }
+    fn has_free_slot(&self) -> bool {
+        self.max_concurrent_tasks
+            .is_none_or(|limit| self.running.len() < usize::from(limit))
+    }
+    /// Put a task in the queue.
+    ///
+    /// Pushing a task that is already queued or running does nothing.
+    pub fn push(
+        &mut self,
+        id: Uuid,
+        task: Addr<T>,
+        priority: TaskPriority,
+    ) -> ManagerCoreResult<()> {
+        self.remove_stopped();
+        if self.is_queued(&id) || self.is_running(&id) {
+            return Ok(());
+        }
+        if let Some(limit) = self.max_queued_tasks
+            && self.queued.len() >= usize::from(limit)
+        {
+            return Err(Error::DownloadTaskLimitExceded {
+                current: self.queued.len().try_into().unwrap_or(u16::MAX),
+                limit,
+            });
+        }
+        let key = (Reverse(priority), self.next_seq);
+        self.next_seq += 1;
+        self.queue.insert(key, (id, task.downgrade()));
+        self.queued.insert(id, key);
+        Ok(())
+    }
+    /// Remove a task from the queue without starting it.
+    ///
+    /// Returns the task if it is still alive.
+    pub fn remove(&mut self, id: &Uuid) -> Option<Addr<T>> {
+        let key = self.queued.remove(id)?;
+        self.queue.remove(&key).and_then(|(_, task)| task.upgrade())
+    }
+    /// Pop the next task to start if there is a free slot.
+    ///
+    /// The stopped tasks are skipped.
+    /// The returned task is marked as running until [`Self::finish`] is called.
+    pub fn pop_next(&mut self) -> Option<(Uuid, Addr<T>)> {
+        if !self.has_free_slot() {
+            return None;
+        }
+        loop {
+            let (_, (id, task)) = self.queue.pop_first()?;
+            self.queued.remove(&id);
+            if let Some(task) = task.upgrade() {
+                self.running.insert(id);
+                return Some((id, task));
+            }
+        }
+    }
+    /// Mark a running task as finished, freeing its slot.
+    pub fn finish(&mut self, id: &Uuid) -> bool {
+        self.running.remove(id)
+    }
+}
+
+/// A download task that can be started by a [`TaskScheduler`].
+pub trait SchedulableTask:
+    Actor<Context = Context<Self>>
+    + Handler<QueueTaskMessage>
+    + Handler<TaskStateMessage>
+    + Handler<StartDownload>
+{
+    /// Start the task if it is still queued and wait until it finishes.
+    fn run_until_finished(addr: Addr<Self>) -> impl Future<Output = MailBoxResult<()>> + 'static;
+}
+
+impl<T> SchedulableTask for T
+where
+    T: Actor<Context = Context<Self>>
+        + Handler<QueueTaskMessage>
+        + Handler<TaskStateMessage>
+        + Handler<StartDownload>
+        + CanBeWaited
+        + Handler<WaitForFinishedMessage<<T as CanBeWaited>::Ok, <T as CanBeWaited>::Loading>>,
+    <T as CanBeWaited>::Ok: Send + Sync + 'static,
+    <T as CanBeWaited>::Loading: Send + Sync + Unpin + 'static,
+{
+    async fn run_until_finished(addr: Addr<Self>) -> MailBoxResult<()> {
+        // The task might have been canceled or started by someone else while queued
+        if !addr.send(TaskStateMessage).await?.is_queued() {
+            return Ok(());
+        }
+        // Subscribing before starting the download so we don't miss the final state
+        let wait = addr
+            .send(WaitForFinishedMessage::<
+                <T as CanBeWaited>::Ok,
+                <T as CanBeWaited>::Loading,
+            >::new())
+            .await?;
+        addr.send(StartDownload).await?;
+        if let Err(err) = wait.await {
+            log::debug!("{err}");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,100 @@
+use std::time::Duration;
+
+use actix::prelude::*;
+use uuid::Uuid;
+
+use crate::Error;
+
+use super::{TaskPriority, TaskScheduler};
+
+#[derive(Debug, Default)]
+struct DummyTask;
+
+impl Actor for DummyTask {
+    type Context = Context<Self>;
+}
+
+#[actix::test]
+async fn respect_the_concurrent_limit() {
+    let mut scheduler = TaskScheduler::<DummyTask>::new(Some(2));
+    let ids = [Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4()];
+    for id in ids {
+        scheduler
+            .push(id, DummyTask.start(), TaskPriority::Normal)
+            .unwrap();
+    }
+    assert_eq!(scheduler.pop_next().map(|(id, _)| id), Some(ids[0]));
+    assert_eq!(scheduler.pop_next().map(|(id, _)| id), Some(ids[1]));
+    assert!(scheduler.pop_next().is_none());
+    assert!(scheduler.finish(&ids[0]));
+    assert_eq!(scheduler.pop_next().map(|(id, _)| id), Some(ids[2]));
+    assert_eq!(scheduler.running_len(), 2);
+    assert_eq!(scheduler.queued_len(), 0);
+}
+
+#[actix::test]
+async fn start_higher_priority_first() {
+    let mut scheduler = TaskScheduler::<DummyTask>::new(Some(1));
+    let low = Uuid::new_v4();
+    let normal = Uuid::new_v4();
+    let high = Uuid::new_v4();
+    scheduler
+        .push(low, DummyTask.start(), TaskPriority::Low)
+        .unwrap();
+    scheduler
+        .push(normal, DummyTask.start(), TaskPriority::Normal)
+        .unwrap();
+    scheduler
+        .push(high, DummyTask.start(), TaskPriority::High)
+        .unwrap();
+    assert_eq!(scheduler.queued_ids(), vec![high, normal, low]);
+    assert_eq!(scheduler.pop_next().map(|(id, _)| id), Some(high));
+}
+
+#[actix::test]
+async fn reject_when_the_queue_is_full() {
+    let mut scheduler = TaskScheduler::<DummyTask>::new(Some(1));
+    scheduler.set_max_queued_tasks(Some(1));
+    scheduler
+        .push(Uuid::new_v4(), DummyTask.start(), TaskPriority::Normal)
+        .unwrap();
+    assert!(matches!(
+        scheduler.push(Uuid::new_v4(), DummyTask.start(), TaskPriority::Normal),
+        Err(Error::DownloadTaskLimitExceded {
+            current: 1,
+            limit: 1
+        })
+    ));
+}
+
+#[actix::test]
+async fn skip_the_stopped_tasks() {
+    let mut scheduler = TaskScheduler::<DummyTask>::new(Some(1));
+    let stopped = Uuid::new_v4();
+    let alive = Uuid::new_v4();
+    let alive_task = DummyTask.start();
+    scheduler
+        .push(stopped, DummyTask.start(), TaskPriority::High)
+        .unwrap();
+    scheduler
+        .push(alive, alive_task.clone(), TaskPriority::Normal)
+        .unwrap();
+    // The queue doesn't keep the first task alive
+    actix::clock::sleep(Duration::from_millis(10)).await;
+    assert_eq!(scheduler.queued_ids(), vec![alive]);
+    assert_eq!(scheduler.pop_next().map(|(id, _)| id), Some(alive));
+    assert_eq!(scheduler.queued_len(), 0);
+}
+
+#[actix::test]
+async fn remove_a_queued_task() {
+    let mut scheduler = TaskScheduler::<DummyTask>::new(Some(1));
+    let id = Uuid::new_v4();
+    let task = DummyTask.start();
+    scheduler
+        .push(id, task.clone(), TaskPriority::Normal)
+        .unwrap();
+    assert!(scheduler.remove(&id).is_some());
+    assert!(!scheduler.is_queued(&id));
+    assert!(scheduler.pop_next().is_none());
+}
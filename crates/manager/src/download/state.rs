@@ -1,9 +1,10 @@
+pub mod bandwidth;
 pub mod messages;
 pub mod task;
 
 use crate::{history::service::HistoryActorService, DirsOptions};
 
-pub use self::task::*;
+pub use self::{bandwidth::BandwidthLimiter, task::*};
 
 use actix::prelude::*;
 use mangadex_api::MangaDexClient;
@@ -13,6 +14,7 @@ pub struct DownloadManagerState {
     dir_option: Addr<DirsOptions>,
     client: MangaDexClient,
     history: Addr<HistoryActorService>,
+    bandwidth: BandwidthLimiter,
 }
 
 impl DownloadManagerState {
@@ -25,6 +27,7 @@ impl DownloadManagerState {
             dir_option,
             client,
             history,
+            bandwidth: Default::default(),
         }
     }
 }
@@ -0,0 +1,174 @@
+use std::{num::NonZeroU64, pin::pin, sync::Arc, time::Duration};
+
+use actix::{
+    MessageResponse,
+    clock::{Instant, sleep},
+};
+use bytes::{Bytes, BytesMut};
+use parking_lot::Mutex;
+use reqwest::Response;
+use tokio_stream::StreamExt;
+
+#[derive(Debug)]
+struct TokenBucket {
+    /// The maximum bytes per second, also used as the bucket capacity
+    rate: NonZeroU64,
+    /// Can be negative if a download consumed more than what was available
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: NonZeroU64) -> Self {
+        Self {
+            rate,
+            tokens: rate.get() as f64,
+            last_refill: Instant::now(),
+        }
+    }
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let rate = self.rate.get() as f64;
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(rate);
+        self.last_refill = now;
+    }
+    /// Change the rate, keeping the current tokens up to the new capacity.
+    fn set_rate(&mut self, rate: NonZeroU64) {
+        self.refill();
+        self.rate = rate;
+        self.tokens = self.tokens.min(rate.get() as f64);
+    }
+    /// Take `bytes` tokens and returns how long the caller should wait for them.
+    fn take(&mut self, bytes: u64) -> Duration {
+        self.refill();
+        let rate = self.rate.get() as f64;
+        self.tokens -= bytes as f64;
+        if self.tokens < 0.0 {
+            Duration::from_secs_f64(-self.tokens / rate)
+        } else {
+            Duration::ZERO
+        }
+    }
+}
+
+/// A token bucket rate limiter shared by every chapter and cover download task.
+///
+/// The images are read chunk by chunk with [`Self::read_body`],
+/// so even a single big image is received at the limited rate.
+#[derive(Debug, Clone, Default, MessageResponse)]
+pub struct BandwidthLimiter(Arc<Mutex<Option<TokenBucket>>>);
+
+impl BandwidthLimiter {
+    pub fn new(max_bandwidth: Option<NonZeroU64>) -> Self {
+        Self(Arc::new(Mutex::new(max_bandwidth.map(TokenBucket::new))))
+    }
+    /// The maximum bytes per second, `None` means unlimited.
+    pub fn max_bandwidth(&self) -> Option<NonZeroU64> {
+        self.0.lock().as_ref().map(|bucket| bucket.rate)
+    }
+    /// Set the maximum bytes per second and returns the previous one.
+    ///
+    /// The tokens that are already available (or owed) are kept, so changing the limit doesn't allow a new burst.
+    pub fn set_max_bandwidth(&self, max_bandwidth: Option<NonZeroU64>) -> Option<NonZeroU64> {
+        let mut bucket = self.0.lock();
+        let previous = bucket.as_ref().map(|bucket| bucket.rate);
+        match (bucket.as_mut(), max_bandwidth) {
+            (Some(current), Some(rate)) => current.set_rate(rate),
+            _ => *bucket = max_bandwidth.map(TokenBucket::new),
+        }
+        previous
+    }
+    /// Wait until `bytes` can be downloaded without exceeding the limit.
+    pub async fn consume(&self, bytes: u64) {
+        let wait = match self.0.lock().as_mut() {
+            Some(bucket) => bucket.take(bytes),
+            None => return,
+        };
+        if !wait.is_zero() {
+            sleep(wait).await;
+        }
+    }
+    /// Read the body of `response`, waiting after each received chunk until the limit allows the next one.
+    pub async fn read_body(&self, response: Response) -> reqwest::Result<Bytes> {
+        let mut body = BytesMut::new();
+        let mut chunks = pin!(response.bytes_stream());
+        while let Some(chunk) = chunks.next().await {
+            let chunk = chunk?;
+            self.consume(chunk.len() as u64).await;
+            body.extend_from_slice(&chunk);
+        }
+        Ok(body.freeze())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        num::NonZeroU64,
+        time::{Duration, Instant},
+    };
+
+    use super::{BandwidthLimiter, TokenBucket};
+
+    #[test]
+    fn token_bucket_debt() {
+        let mut bucket = TokenBucket::new(NonZeroU64::new(1000).unwrap());
+        assert_eq!(bucket.take(1000), Duration::ZERO);
+        let wait = bucket.take(500);
+        assert!(wait > Duration::from_millis(400) && wait <= Duration::from_millis(500));
+    }
+
+    #[test]
+    fn changing_the_limit_keeps_the_tokens() {
+        let limiter = BandwidthLimiter::new(NonZeroU64::new(1000));
+        limiter.0.lock().as_mut().unwrap().take(1000);
+        // A higher limit doesn't refill the bucket
+        limiter.set_max_bandwidth(NonZeroU64::new(10_000));
+        let tokens = limiter.0.lock().as_ref().unwrap().tokens;
+        assert!(tokens < 100.0, "{tokens}");
+
+        // A lower limit caps the available tokens
+        let limiter = BandwidthLimiter::new(NonZeroU64::new(1000));
+        assert_eq!(
+            limiter.set_max_bandwidth(NonZeroU64::new(10)),
+            NonZeroU64::new(1000)
+        );
+        let tokens = limiter.0.lock().as_ref().unwrap().tokens;
+        assert!(tokens <= 10.0, "{tokens}");
+    }
+
+    #[actix::test]
+    async fn the_body_is_read_at_the_limited_rate() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/image.png", listener.local_addr().unwrap());
+        let body = vec![1u8; 30_000];
+        let server_body = body.clone();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request).unwrap();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                server_body.len()
+            )
+            .unwrap();
+            // Sent in several chunks, like a real image
+            for chunk in server_body.chunks(5_000) {
+                stream.write_all(chunk).unwrap();
+                stream.flush().unwrap();
+            }
+        });
+
+        // The first 20 000 bytes are already available, the 10 000 others take half a second
+        let limiter = BandwidthLimiter::new(NonZeroU64::new(20_000));
+        let start = Instant::now();
+        let response = reqwest::Client::new().get(url).send().await.unwrap();
+        let read = limiter.read_body(response).await.unwrap();
+        assert_eq!(read.as_ref(), body.as_slice());
+        assert!(start.elapsed() >= Duration::from_millis(400));
+    }
+}
@@ -1,3 +1,4 @@
+pub mod bandwidth;
 pub mod client;
 pub mod dir_options;
 pub mod history;
@@ -8,19 +9,24 @@ use actix::Addr;
 use mangadex_api::MangaDexClient;
 
 use crate::{
-    download::{messages::state::GetManagerState, state::DownloadManagerState},
+    download::{
+        messages::state::GetManagerState,
+        state::{BandwidthLimiter, DownloadManagerState},
+    },
     history::service::HistoryActorService,
     DirsOptions, MailBoxResult,
 };
 
 pub use self::{
-    client::GetClientMessage, dir_options::GetDirsOptionsMessage, history::GetHistoryMessage,
+    bandwidth::GetBandwidthLimiterMessage, client::GetClientMessage,
+    dir_options::GetDirsOptionsMessage, history::GetHistoryMessage,
 };
 
 pub trait GetManagerStateData: Sync {
     fn get_client(&self) -> impl Future<Output = MailBoxResult<MangaDexClient>> + Send;
     fn get_dir_options(&self) -> impl Future<Output = MailBoxResult<Addr<DirsOptions>>> + Send;
     fn get_history(&self) -> impl Future<Output = MailBoxResult<Addr<HistoryActorService>>> + Send;
+    fn get_bandwidth_limiter(&self) -> impl Future<Output = MailBoxResult<BandwidthLimiter>> + Send;
 }
 
 impl GetManagerStateData for Addr<DownloadManagerState> {
@@ -33,6 +39,11 @@ impl GetManagerStateData for Addr<DownloadManagerState> {
     fn get_history(&self) -> impl Future<Output = MailBoxResult<Addr<HistoryActorService>>> + Send {
         self.send(GetHistoryMessage)
     }
+    fn get_bandwidth_limiter(
+        &self,
+    ) -> impl Future<Output = MailBoxResult<BandwidthLimiter>> + Send {
+        self.send(GetBandwidthLimiterMessage)
+    }
 }
 
 impl<A> GetManagerStateData for A
@@ -48,4 +59,10 @@ where
     async fn get_history(&self) -> MailBoxResult<Addr<HistoryActorService>> {
         self.get_manager_state().await?.get_history().await
     }
+    async fn get_bandwidth_limiter(&self) -> MailBoxResult<BandwidthLimiter> {
+        self.get_manager_state()
+            .await?
+            .get_bandwidth_limiter()
+            .await
+    }
 }
@@ -0,0 +1,21 @@
+use actix::prelude::*;
+
+use crate::download::state::{BandwidthLimiter, DownloadManagerState};
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GetBandwidthLimiterMessage;
+
+impl Message for GetBandwidthLimiterMessage {
+    type Result = BandwidthLimiter;
+}
+
+impl Handler<GetBandwidthLimiterMessage> for DownloadManagerState {
+    type Result = <GetBandwidthLimiterMessage as Message>::Result;
+    fn handle(
+        &mut self,
+        _msg: GetBandwidthLimiterMessage,
+        _ctx: &mut Self::Context,
+    ) -> Self::Result {
+        self.bandwidth.clone()
+    }
+}
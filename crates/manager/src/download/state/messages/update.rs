@@ -1,8 +1,9 @@
+pub mod bandwidth;
 pub mod client;
 pub mod dir_options;
 pub mod history;
 
-use std::future::Future;
+use std::{future::Future, num::NonZeroU64};
 
 use actix::Addr;
 use mangadex_api::MangaDexClient;
@@ -14,8 +15,8 @@ use crate::{
 };
 
 pub use self::{
-    client::UpdateClientMessage, dir_options::UpdateDirOptionsMessage,
-    history::UpdateHistoryMessage,
+    bandwidth::UpdateMaxBandwidthMessage, client::UpdateClientMessage,
+    dir_options::UpdateDirOptionsMessage, history::UpdateHistoryMessage,
 };
 
 pub trait UpdateManagerStateData: Sync {
@@ -31,6 +32,13 @@ pub trait UpdateManagerStateData: Sync {
         &self,
         history: impl Into<Addr<HistoryActorService>> + Send + 'static,
     ) -> impl Future<Output = MailBoxResult<Addr<HistoryActorService>>> + Send;
+    /// Set the maximum bytes per second shared by every chapter and cover download.
+    ///
+    /// `None` removes the limit.
+    fn set_max_bandwidth(
+        &self,
+        max_bandwidth: Option<NonZeroU64>,
+    ) -> impl Future<Output = MailBoxResult<Option<NonZeroU64>>> + Send;
 }
 
 impl UpdateManagerStateData for Addr<DownloadManagerState> {
@@ -52,6 +60,12 @@ impl UpdateManagerStateData for Addr<DownloadManagerState> {
     ) -> impl Future<Output = MailBoxResult<Addr<HistoryActorService>>> + Send {
         self.send(UpdateHistoryMessage(history.into()))
     }
+    fn set_max_bandwidth(
+        &self,
+        max_bandwidth: Option<NonZeroU64>,
+    ) -> impl Future<Output = MailBoxResult<Option<NonZeroU64>>> + Send {
+        self.send(UpdateMaxBandwidthMessage(max_bandwidth))
+    }
 }
 
 impl<A> UpdateManagerStateData for A
@@ -79,4 +93,13 @@ where
     ) -> MailBoxResult<Addr<HistoryActorService>> {
         self.get_manager_state().await?.set_history(history).await
     }
+    async fn set_max_bandwidth(
+        &self,
+        max_bandwidth: Option<NonZeroU64>,
+    ) -> MailBoxResult<Option<NonZeroU64>> {
+        self.get_manager_state()
+            .await?
+            .set_max_bandwidth(max_bandwidth)
+            .await
+    }
 }
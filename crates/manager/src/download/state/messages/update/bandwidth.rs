@@ -0,0 +1,23 @@
+use std::num::NonZeroU64;
+
+use actix::prelude::*;
+
+use crate::download::state::DownloadManagerState;
+
+/// Set the maximum bytes per second shared by every chapter and cover download.
+///
+/// `None` removes the limit.
+/// Returns the previous limit.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UpdateMaxBandwidthMessage(pub Option<NonZeroU64>);
+
+impl Message for UpdateMaxBandwidthMessage {
+    type Result = Option<NonZeroU64>;
+}
+
+impl Handler<UpdateMaxBandwidthMessage> for DownloadManagerState {
+    type Result = <UpdateMaxBandwidthMessage as Message>::Result;
+    fn handle(&mut self, msg: UpdateMaxBandwidthMessage, _ctx: &mut Self::Context) -> Self::Result {
+        self.bandwidth.set_max_bandwidth(msg.0)
+    }
+}
@@ -13,6 +13,7 @@ use crate::{ManagerCoreResult, OwnedError, download::messages::TaskSubscriberMes
 pub enum DownloadTaskState<T, L> {
     #[default]
     Pending,
+    Queued,
     Loading(L),
     Error(OwnedError),
     Done(T),
@@ -23,6 +24,7 @@ pub enum DownloadTaskState<T, L> {
 pub enum TaskState {
     #[default]
     Pending,
+    Queued,
     Loading,
     Error,
     Done,
@@ -39,6 +41,9 @@ impl TaskState {
     pub fn is_loading(&self) -> bool {
         matches!(*self, Self::Loading)
     }
+    pub fn is_queued(&self) -> bool {
+        matches!(*self, Self::Queued)
+    }
 }
 
 impl<T, L> From<DownloadTaskState<T, L>> for TaskState {
@@ -51,6 +56,7 @@ impl<T, L> From<&DownloadTaskState<T, L>> for TaskState {
     fn from(value: &DownloadTaskState<T, L>) -> Self {
         match value {
             DownloadTaskState::Pending => Self::Pending,
+            DownloadTaskState::Queued => Self::Queued,
             DownloadTaskState::Loading(_) => Self::Loading,
             DownloadTaskState::Error(_) => Self::Error,
             DownloadTaskState::Done(_) => Self::Done,
@@ -9,9 +9,12 @@ use super::MailBoxResult;
 
 use crate::download::{
     messages::{
-        DropSingleTaskMessage, GetTaskMessage, GetTasksListMessage, SubcribeToManagerMessage,
+        DropSingleTaskMessage, GetQueuedTasksListMessage, GetTaskMessage, GetTasksListMessage,
+        QueueTaskMessage, SetMaxConcurrentTasksMessage, SetMaxQueuedTasksMessage,
+        SubcribeToManagerMessage,
         state::GetManagerStateMessage,
     },
+    scheduler::{SchedulableTask, TaskPriority, TaskScheduler},
     state::DownloadManagerState,
 };
 
@@ -30,6 +33,49 @@ where
     -> Addr<Self::Task>;
     fn drop_task(&mut self, id: Uuid);
     fn get_task(&self, id: Uuid) -> Option<Addr<Self::Task>>;
+    fn scheduler(&self) -> &TaskScheduler<Self::Task>;
+    fn scheduler_mut(&mut self) -> &mut TaskScheduler<Self::Task>;
+    /// Put the task in the download queue and start as many queued tasks as the scheduler allows.
+    fn schedule_task(
+        &mut self,
+        id: Uuid,
+        task: Addr<Self::Task>,
+        priority: TaskPriority,
+        ctx: &mut Context<Self>,
+    ) where
+        Self: Actor<Context = Context<Self>>,
+        Self::Task: SchedulableTask,
+    {
+        match self.scheduler_mut().push(id, task.clone(), priority) {
+            Ok(()) => task.do_send(QueueTaskMessage::Queued),
+            Err(err) => {
+                log::error!("{err}");
+                task.do_send(QueueTaskMessage::Rejected(err.into()));
+                return;
+            }
+        }
+        self.run_queued_tasks(ctx);
+    }
+    /// Start the queued tasks until the concurrent tasks limit is reached.
+    fn run_queued_tasks(&mut self, ctx: &mut Context<Self>)
+    where
+        Self: Actor<Context = Context<Self>>,
+        Self::Task: SchedulableTask,
+    {
+        while let Some((id, task)) = self.scheduler_mut().pop_next() {
+            log::trace!("Starting queued task {id}");
+            <Self::Task as SchedulableTask>::run_until_finished(task)
+                .into_actor(self)
+                .map(move |res, this, ctx| {
+                    if let Err(err) = res {
+                        log::error!("{err}");
+                    }
+                    this.scheduler_mut().finish(&id);
+                    this.run_queued_tasks(ctx);
+                })
+                .spawn(ctx);
+        }
+    }
 }
 
 pub trait TaskManagerAddr: Sync
@@ -52,6 +98,22 @@ where
         &self,
         id: Uuid,
     ) -> impl Future<Output = MailBoxResult<Option<Addr<Self::Task>>>> + Send;
+    fn queued_tasks_id(&self) -> impl Future<Output = MailBoxResult<Vec<Uuid>>> + Send;
+    /// Set the maximum number of tasks that can download at the same time and returns the previous one.
+    ///
+    /// `None` removes the limit.
+    fn set_max_concurrent_tasks(
+        &self,
+        limit: Option<u16>,
+    ) -> impl Future<Output = MailBoxResult<Option<u16>>> + Send;
+    /// Set the maximum number of tasks that can wait in the download queue
+    /// and returns the previous one.
+    ///
+    /// `None` removes the limit.
+    fn set_max_queued_tasks(
+        &self,
+        limit: Option<u16>,
+    ) -> impl Future<Output = MailBoxResult<Option<u16>>> + Send;
 }
 
 impl<T> TaskManagerAddr for Addr<T>
@@ -62,14 +124,20 @@ where
         + Handler<GetTasksListMessage>
         + Handler<DropSingleTaskMessage>
         + Handler<T::DownloadMessage>
-        + Handler<GetTaskMessage<T::Task>>,
+        + Handler<GetTaskMessage<T::Task>>
+        + Handler<GetQueuedTasksListMessage>
+        + Handler<SetMaxConcurrentTasksMessage>
+        + Handler<SetMaxQueuedTasksMessage>,
     T::DownloadMessage: Send,
     <T as Actor>::Context: ToEnvelope<T, GetManagerStateMessage>
         + ToEnvelope<T, SubcribeToManagerMessage>
         + ToEnvelope<T, GetTasksListMessage>
         + ToEnvelope<T, DropSingleTaskMessage>
         + ToEnvelope<T, T::DownloadMessage>
-        + ToEnvelope<T, GetTaskMessage<T::Task>>,
+        + ToEnvelope<T, GetTaskMessage<T::Task>>
+        + ToEnvelope<T, GetQueuedTasksListMessage>
+        + ToEnvelope<T, SetMaxConcurrentTasksMessage>
+        + ToEnvelope<T, SetMaxQueuedTasksMessage>,
 {
     type DownloadMessage = T::DownloadMessage;
     type Task = T::Task;
@@ -95,4 +163,13 @@ where
     async fn get_task(&self, id: Uuid) -> MailBoxResult<Option<Addr<Self::Task>>> {
         self.send(GetTaskMessage::<Self::Task>::new(id)).await
     }
+    async fn queued_tasks_id(&self) -> MailBoxResult<Vec<Uuid>> {
+        self.send(GetQueuedTasksListMessage).await
+    }
+    async fn set_max_concurrent_tasks(&self, limit: Option<u16>) -> MailBoxResult<Option<u16>> {
+        self.send(SetMaxConcurrentTasksMessage(limit)).await
+    }
+    async fn set_max_queued_tasks(&self, limit: Option<u16>) -> MailBoxResult<Option<u16>> {
+        self.send(SetMaxQueuedTasksMessage(limit)).await
+    }
 }
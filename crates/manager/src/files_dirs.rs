@@ -1,12 +1,11 @@
 use std::{
     ffi::OsStr,
-    fs::File,
-    io::BufReader,
     ops::{Deref, DerefMut},
     path::Path,
+    time::Duration,
 };
 
-use actix::{Actor, Context, Handler, Message};
+use actix::{Actor, AsyncContext, Context, Handler, Message};
 use log::error;
 use mangadex_api_schema_rust::v5::CoverObject;
 use mangadex_api_types_rust::RelationshipType;
@@ -22,7 +21,7 @@ use crate::{
     history::{IsIn, service::messages::is_in::IsInMessage},
     recipients::Recipients,
 };
-use api_core::{DirsOptions as DirsOptionsCore, data_push::Push};
+use api_core::{DirsOptions as DirsOptionsCore, data_pulls::Pull, data_push::Push};
 
 // TODO add an message for push and other thingy
 #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -70,29 +69,41 @@ impl DirsOptions {
     }
 }
 
+/// How often the library index is written on the disk if it has unsaved changes
+const INDEX_SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
 impl Actor for DirsOptions {
     type Context = Context<Self>;
-    fn started(&mut self, _ctx: &mut Self::Context) {
+    fn started(&mut self, ctx: &mut Self::Context) {
         if let Err(e) = self.verify_and_init() {
             error!("{e:#?}");
         }
+        // a stale index is rebuilt from every file of the library, so it's not done on the actor
+        let dirs = self.core.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = dirs.load_index() {
+                error!("Cannot load the library index: {e}");
+            }
+        });
+        ctx.run_interval(INDEX_SAVE_INTERVAL, |this, _ctx| {
+            if let Err(e) = this.save_index() {
+                error!("{e}");
+            }
+        });
+    }
+    fn stopped(&mut self, _ctx: &mut Self::Context) {
+        if let Err(e) = self.save_index() {
+            error!("{e}");
+        }
     }
 }
 
 impl DirsOptions {
     fn is_chapter_here(&self, id: Uuid) -> bool {
-        let chapter = self.chapters_add(format!("{id}"));
-        chapter.exists() && chapter.join("data.json").exists()
-    }
-    fn re_ca(&self, id: Uuid) -> ManagerCoreResult<CoverObject> {
-        use mangadex_api_schema_rust::ApiData;
-        use serde_json::from_reader;
-        let file = BufReader::new(File::open(self.covers_add(format!("{id}.json")))?);
-        let d: ApiData<CoverObject> = from_reader(file)?;
-        Ok(d.data)
+        self.chapter_file(id).is_some()
     }
     fn cover_art(&self, id: Uuid) -> bool {
-        let cover: ManagerCoreResult<CoverObject> = self.re_ca(id);
+        let cover = Pull::<CoverObject, Uuid>::pull(&self.core, id);
         if let Ok(p) = cover.map(|c| self.cover_images_add(c.attributes.file_name)) {
             p.exists()
         } else {
@@ -101,11 +112,19 @@ impl DirsOptions {
     }
 
     fn manga(&self, id: Uuid) -> bool {
-        self.mangas_add(format!("{id}.json")).exists()
+        self.manga_file(id).is_some()
     }
     pub(crate) fn subscribers(&self) -> &Recipients<FilesDirSubscriberMessage> {
         &self.subscribers
     }
+    pub(crate) fn send_moved_dirs(&self) {
+        self.subscribers.do_send(FilesDirSubscriberMessage::MovedDirs {
+            data_dir: self.data_dir_add(""),
+            chapters: self.chapters_add(""),
+            covers: self.covers_add(""),
+            mangas: self.mangas_add(""),
+        });
+    }
 }
 
 impl IsIn<(Uuid, RelationshipType)> for DirsOptions {
@@ -1,9 +1,11 @@
+use std::path::PathBuf;
+
 use actix::Message;
 use uuid::Uuid;
 
 use crate::files_dirs::messages::delete::chapter::images::ChapterImages;
 
-#[derive(Debug, Clone, Message)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Message)]
 #[non_exhaustive]
 #[rtype("()")]
 pub enum FilesDirSubscriberMessage {
@@ -20,4 +22,34 @@ pub enum FilesDirSubscriberMessage {
         id: Uuid,
         mode: Option<ChapterImages>,
     },
+    /// A manga that wasn't stored has been pushed
+    AddedManga {
+        id: Uuid,
+    },
+    /// An already stored manga has been pushed again
+    UpdatedManga {
+        id: Uuid,
+    },
+    /// A chapter data has been pushed (added or replaced)
+    AddedChapter {
+        id: Uuid,
+    },
+    /// Some images of a chapter have been pushed
+    AddedChapterImages {
+        id: Uuid,
+        mode: ChapterImages,
+    },
+    /// A cover data (and maybe its image) has been pushed (added or replaced)
+    AddedCover {
+        id: Uuid,
+    },
+    /// One of the library directories has been changed.
+    ///
+    /// Contains the new resolved directories.
+    MovedDirs {
+        data_dir: PathBuf,
+        chapters: PathBuf,
+        covers: PathBuf,
+        mangas: PathBuf,
+    },
 }
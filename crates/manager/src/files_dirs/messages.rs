@@ -1,6 +1,9 @@
+pub mod check;
 pub mod delete;
+pub mod index;
 pub mod join;
 pub mod modify;
+pub mod package;
 pub mod pull;
 pub mod push;
 pub mod subscribe;
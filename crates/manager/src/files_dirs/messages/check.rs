@@ -0,0 +1,83 @@
+use std::future::Future;
+
+use actix::prelude::*;
+use api_core::file_dirs::verification::{LibraryCheckReport, LibraryIssue};
+
+use crate::{
+    DirsOptions, ManagerCoreResult, download::state::messages::get::GetManagerStateData,
+    files_dirs::events::FilesDirSubscriberMessage,
+};
+
+/// Walk the whole library and report the problems found.
+#[derive(Debug, Clone, Copy, Hash, Default, Message)]
+#[rtype(result = "ManagerCoreResult<LibraryCheckReport>")]
+pub struct CheckLibraryMessage;
+
+impl Handler<CheckLibraryMessage> for DirsOptions {
+    type Result = ManagerCoreResult<LibraryCheckReport>;
+    fn handle(&mut self, _msg: CheckLibraryMessage, _ctx: &mut Self::Context) -> Self::Result {
+        Ok(self.core.check_library()?)
+    }
+}
+
+/// Delete the data behind a [`LibraryIssue`].
+///
+/// Fails if the issue doesn't support the `Delete` repair action.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Message)]
+#[rtype(result = "ManagerCoreResult<()>")]
+pub struct DeleteLibraryIssueMessage(pub LibraryIssue);
+
+impl Handler<DeleteLibraryIssueMessage> for DirsOptions {
+    type Result = ManagerCoreResult<()>;
+    fn handle(
+        &mut self,
+        msg: DeleteLibraryIssueMessage,
+        _ctx: &mut Self::Context,
+    ) -> Self::Result {
+        self.delete_issue_data(&msg.0)?;
+        match msg.0 {
+            LibraryIssue::ChapterWithoutData { id } | LibraryIssue::OrphanChapter { id, .. } => {
+                self.subscribers()
+                    .do_send(FilesDirSubscriberMessage::RemovedChapter { id });
+            }
+            LibraryIssue::MissingCoverImage { id, .. } => {
+                self.subscribers()
+                    .do_send(FilesDirSubscriberMessage::RemovedCoverArt { id });
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+pub trait CheckLibraryAsyncTrait: Sync {
+    fn check_library(&self) -> impl Future<Output = ManagerCoreResult<LibraryCheckReport>> + Send;
+    fn delete_library_issue(
+        &self,
+        issue: LibraryIssue,
+    ) -> impl Future<Output = ManagerCoreResult<()>> + Send;
+}
+
+impl CheckLibraryAsyncTrait for Addr<DirsOptions> {
+    async fn check_library(&self) -> ManagerCoreResult<LibraryCheckReport> {
+        self.send(CheckLibraryMessage).await?
+    }
+    async fn delete_library_issue(&self, issue: LibraryIssue) -> ManagerCoreResult<()> {
+        self.send(DeleteLibraryIssueMessage(issue)).await?
+    }
+}
+
+impl<A> CheckLibraryAsyncTrait for A
+where
+    A: GetManagerStateData + Sync,
+{
+    async fn check_library(&self) -> ManagerCoreResult<LibraryCheckReport> {
+        self.get_dir_options().await?.check_library().await
+    }
+    async fn delete_library_issue(&self, issue: LibraryIssue) -> ManagerCoreResult<()> {
+        self.get_dir_options()
+            .await?
+            .delete_library_issue(issue)
+            .await
+    }
+}
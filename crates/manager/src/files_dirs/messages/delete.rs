@@ -12,7 +12,10 @@ use uuid::Uuid;
 use crate::{download::state::messages::get::GetManagerStateData, DirsOptions, ManagerCoreResult};
 
 pub use self::{
-    chapter::{images::DeleteChapterImagesMessage, DeleteChapterMessage},
+    chapter::{
+        images::{DeleteChapterImageMessage, DeleteChapterImagesMessage},
+        DeleteChapterMessage,
+    },
     cover::DeleteCoverMessage,
     manga::DeleteMangaMessage,
 };
@@ -28,6 +31,12 @@ pub trait DeleteDataAsyncTrait: Sync {
         id: Uuid,
         mode: impl Into<ChapterImages> + Send + 'static,
     ) -> impl Future<Output = ManagerCoreResult<()>> + Send;
+    fn delete_chapter_image(
+        &self,
+        id: Uuid,
+        mode: impl Into<ChapterImages> + Send + 'static,
+        filename: String,
+    ) -> impl Future<Output = ManagerCoreResult<()>> + Send;
     fn delete_chapter(&self, id: Uuid) -> impl Future<Output = ManagerCoreResult<()>> + Send;
     fn delete_cover(&self, id: Uuid) -> impl Future<Output = ManagerCoreResult<()>> + Send;
     fn delete_manga(
@@ -52,6 +61,15 @@ impl DeleteDataAsyncTrait for Addr<DirsOptions> {
         self.send(DeleteChapterImagesMessage::new(id, mode).ignore_conflict(true))
             .await?
     }
+    async fn delete_chapter_image(
+        &self,
+        id: Uuid,
+        mode: impl Into<ChapterImages> + Send + 'static,
+        filename: String,
+    ) -> ManagerCoreResult<()> {
+        self.send(DeleteChapterImageMessage::new(id, mode, filename))
+            .await?
+    }
     async fn delete_chapter(&self, id: Uuid) -> ManagerCoreResult<()> {
         self.send(DeleteChapterMessage::new(id)).await?
     }
@@ -87,6 +105,17 @@ where
             .delete_chapter_images_ignore_conflict(id, mode)
             .await
     }
+    async fn delete_chapter_image(
+        &self,
+        id: Uuid,
+        mode: impl Into<ChapterImages> + Send + 'static,
+        filename: String,
+    ) -> ManagerCoreResult<()> {
+        self.get_dir_options()
+            .await?
+            .delete_chapter_image(id, mode, filename)
+            .await
+    }
     async fn delete_chapter(&self, id: Uuid) -> ManagerCoreResult<()> {
         self.get_dir_options().await?.delete_chapter(id).await
     }
@@ -51,6 +51,9 @@ impl Handler<DeleteChapterMessage> for DirsOptions {
                     if let crate::Error::DeleteChapterImages(e) = e {
                         if let DeleteChapterImagesError::Conflict = e {
                             remove_dir_all(chapter_path)?;
+                            self.update_index(|index| {
+                                index.remove_chapter(msg.id);
+                            });
                             self.subscribers()
                                 .do_send(FilesDirSubscriberMessage::RemovedChapter { id: msg.id });
                             Ok(())
@@ -64,6 +67,9 @@ impl Handler<DeleteChapterMessage> for DirsOptions {
             }
         } else {
             remove_dir_all(chapter_path)?;
+            self.update_index(|index| {
+                index.remove_chapter(msg.id);
+            });
             self.subscribers()
                 .do_send(FilesDirSubscriberMessage::RemovedChapter { id: msg.id });
             Ok(())
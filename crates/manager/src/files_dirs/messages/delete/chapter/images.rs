@@ -1,4 +1,7 @@
-use std::fs::remove_dir_all;
+use std::{
+    fs::{remove_dir_all, remove_file},
+    io,
+};
 
 use actix::prelude::*;
 use uuid::Uuid;
@@ -8,7 +11,7 @@ use crate::{
     files_dirs::events::FilesDirSubscriberMessage,
 };
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ChapterImages {
     Data,
     DataSaver,
@@ -23,6 +26,15 @@ impl From<Mode> for ChapterImages {
     }
 }
 
+impl From<ChapterImages> for Mode {
+    fn from(value: ChapterImages) -> Self {
+        match value {
+            ChapterImages::Data => Self::Data,
+            ChapterImages::DataSaver => Self::DataSaver,
+        }
+    }
+}
+
 impl From<DownloadMode> for ChapterImages {
     fn from(value: DownloadMode) -> Self {
         match value {
@@ -107,3 +119,46 @@ impl Handler<DeleteChapterImagesMessage> for DirsOptions {
         }
     }
 }
+
+/// Delete a single chapter image, doing nothing if it doesn't exist.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DeleteChapterImageMessage {
+    id: Uuid,
+    images: ChapterImages,
+    filename: String,
+}
+
+impl DeleteChapterImageMessage {
+    pub fn new<I: Into<ChapterImages>>(id: Uuid, images: I, filename: String) -> Self {
+        Self {
+            id,
+            images: images.into(),
+            filename,
+        }
+    }
+}
+
+impl Message for DeleteChapterImageMessage {
+    type Result = crate::ManagerCoreResult<()>;
+}
+
+impl Handler<DeleteChapterImageMessage> for DirsOptions {
+    type Result = <DeleteChapterImageMessage as Message>::Result;
+    fn handle(&mut self, msg: DeleteChapterImageMessage, _ctx: &mut Self::Context) -> Self::Result {
+        let path = self
+            .chapter_images_dir(msg.id, msg.images.into())
+            .join(&msg.filename);
+        match remove_file(path) {
+            Ok(()) => {
+                self.subscribers()
+                    .do_send(FilesDirSubscriberMessage::RemovedChapterImages {
+                        id: msg.id,
+                        mode: Some(msg.images),
+                    });
+                Ok(())
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
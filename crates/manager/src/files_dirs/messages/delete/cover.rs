@@ -33,7 +33,12 @@ impl Handler<DeleteCoverMessage> for DirsOptions {
         let cover = self.handle(CoverDataPullMessage(msg.into()), ctx)?;
         let image_path = self.cover_images_add(cover.attributes.file_name);
         remove_file(image_path)?;
-        remove_file(self.covers_add(format!("{}.json", msg.0)))?;
+        if let Some(cover_path) = self.cover_file(msg.0) {
+            remove_file(cover_path)?;
+        }
+        self.update_index(|index| {
+            index.remove_cover(msg.0);
+        });
         self.subscribers()
             .do_send(FilesDirSubscriberMessage::RemovedCoverArt { id: msg.0 });
         Ok(())
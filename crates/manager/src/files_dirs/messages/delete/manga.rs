@@ -12,7 +12,7 @@ use crate::{
     },
     files_dirs::{
         events::FilesDirSubscriberMessage,
-        messages::pull::{chapter::ChapterListDataPullMessage, cover::CoverListDataPullMessage},
+        messages::index::{ChapterIndexedPullMessage, CoverIndexedPullMessage},
     },
 };
 
@@ -46,15 +46,16 @@ pub struct MangaDeleteData {
 impl Handler<DeleteMangaMessage> for DirsOptions {
     type Result = ResponseActFuture<Self, <DeleteMangaMessage as Message>::Result>;
     fn handle(&mut self, msg: DeleteMangaMessage, ctx: &mut Self::Context) -> Self::Result {
-        let manga_path = self.mangas_add(format!("{}.json", msg.0));
+        let manga_path = self.manga_file(msg.0);
         let manga_chapters_data_pull = {
-            let mut to_fn = || {
+            let filter = ChapterListDataPullFilterParams {
+                manga_ids: vec![msg.0],
+                ..Default::default()
+            };
+            let to_fn = || {
                 Ok::<_, crate::Error>(
-                    self.handle(ChapterListDataPullMessage, ctx)?
-                        .to_filtered(ChapterListDataPullFilterParams {
-                            manga_ids: vec![msg.0],
-                            ..Default::default()
-                        })
+                    self.handle(ChapterIndexedPullMessage(filter.clone()), ctx)?
+                        .to_filtered(filter)
                         .map(|c| c.id)
                         .collect::<Vec<Uuid>>(),
                 )
@@ -62,13 +63,14 @@ impl Handler<DeleteMangaMessage> for DirsOptions {
             to_fn()
         };
         let manga_covers_data_pull = {
-            let mut to_fn = || {
+            let filter = CoverListDataPullFilterParams {
+                manga_ids: vec![msg.0],
+                ..Default::default()
+            };
+            let to_fn = || {
                 Ok::<_, crate::Error>(
-                    self.handle(CoverListDataPullMessage, ctx)?
-                        .to_filtered(CoverListDataPullFilterParams {
-                            manga_ids: vec![msg.0],
-                            ..Default::default()
-                        })
+                    self.handle(CoverIndexedPullMessage(filter.clone()), ctx)?
+                        .to_filtered(filter)
                         .map(|c| c.id)
                         .collect::<Vec<Uuid>>(),
                 )
@@ -93,9 +95,14 @@ impl Handler<DeleteMangaMessage> for DirsOptions {
                     log::error!("{e}");
                 }
             }
-            if let Err(e) = remove_file(manga_path) {
+            if let Some(manga_path) = manga_path
+                && let Err(e) = remove_file(manga_path)
+            {
                 log::error!("{e}");
             }
+            this.update_index(|index| {
+                index.remove_manga(msg.0);
+            });
             this.subscribers()
                 .do_send(FilesDirSubscriberMessage::RemovedManga { id: msg.0 });
             delete_data
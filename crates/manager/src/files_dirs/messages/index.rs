@@ -0,0 +1,57 @@
+use actix::prelude::*;
+
+use crate::{
+    DirsOptions, ManagerCoreResult,
+    data_pulls::{
+        chapter::{ChapterListDataPullFilterParams, ids::ChapterIdsListDataPull},
+        cover::{CoverIdsListDataPull, CoverListDataPullFilterParams},
+    },
+};
+
+/// Rebuild the library index from scratch.
+///
+/// Useful if the library was modified outside of the manager.
+#[derive(Debug, Clone, Copy, Hash, Default, Message)]
+#[rtype(result = "ManagerCoreResult<()>")]
+pub struct RebuildIndexMessage;
+
+impl Handler<RebuildIndexMessage> for DirsOptions {
+    type Result = ManagerCoreResult<()>;
+    fn handle(&mut self, _msg: RebuildIndexMessage, _ctx: &mut Self::Context) -> Self::Result {
+        Ok(self.rebuild_index()?)
+    }
+}
+
+/// Pull the chapters that might match the filter by using the library index.
+///
+/// The result still needs to be filtered with the same params.
+#[derive(Debug, Clone, Default)]
+pub struct ChapterIndexedPullMessage(pub ChapterListDataPullFilterParams);
+
+impl Message for ChapterIndexedPullMessage {
+    type Result = ManagerCoreResult<ChapterIdsListDataPull>;
+}
+
+impl Handler<ChapterIndexedPullMessage> for DirsOptions {
+    type Result = <ChapterIndexedPullMessage as Message>::Result;
+    fn handle(&mut self, msg: ChapterIndexedPullMessage, _ctx: &mut Self::Context) -> Self::Result {
+        Ok(self.pull_indexed_chapters(&msg.0)?)
+    }
+}
+
+/// Pull the covers that might match the filter by using the library index.
+///
+/// The result still needs to be filtered with the same params.
+#[derive(Debug, Clone, Default)]
+pub struct CoverIndexedPullMessage(pub CoverListDataPullFilterParams);
+
+impl Message for CoverIndexedPullMessage {
+    type Result = ManagerCoreResult<CoverIdsListDataPull>;
+}
+
+impl Handler<CoverIndexedPullMessage> for DirsOptions {
+    type Result = <CoverIndexedPullMessage as Message>::Result;
+    fn handle(&mut self, msg: CoverIndexedPullMessage, _ctx: &mut Self::Context) -> Self::Result {
+        Ok(self.pull_indexed_covers(&msg.0)?)
+    }
+}
@@ -1,3 +1,4 @@
+pub mod migrate_storage;
 pub mod modify_chapters_path;
 pub mod modify_covers_path;
 pub mod modify_data_path;
@@ -6,12 +7,17 @@ pub mod modify_mangas_path;
 use std::{fmt::Debug, future::Future, path::Path};
 
 use actix::Addr;
+use api_core::file_dirs::{StorageFormat, storage::StorageMigrationReport};
 
-use crate::{download::state::messages::get::GetManagerStateData, DirsOptions, MailBoxResult};
+use crate::{
+    download::state::messages::get::GetManagerStateData, DirsOptions, MailBoxResult,
+    ManagerCoreResult,
+};
 
 pub use self::{
-    modify_chapters_path::ModifyChaptersDirMessage, modify_covers_path::ModifyCoversDirMessage,
-    modify_data_path::ModifyDataDirMessage, modify_mangas_path::ModifyMangaDirMessage,
+    migrate_storage::MigrateStorageMessage, modify_chapters_path::ModifyChaptersDirMessage,
+    modify_covers_path::ModifyCoversDirMessage, modify_data_path::ModifyDataDirMessage,
+    modify_mangas_path::ModifyMangaDirMessage,
 };
 
 pub trait ModifyDirOptionAsyncTrait: Sync {
@@ -31,6 +37,10 @@ pub trait ModifyDirOptionAsyncTrait: Sync {
         &self,
         path: impl AsRef<Path> + Send + 'static + Debug,
     ) -> impl Future<Output = MailBoxResult<()>> + Send;
+    fn migrate_storage(
+        &self,
+        format: StorageFormat,
+    ) -> impl Future<Output = ManagerCoreResult<StorageMigrationReport>> + Send;
 }
 
 impl ModifyDirOptionAsyncTrait for Addr<DirsOptions> {
@@ -61,6 +71,13 @@ impl ModifyDirOptionAsyncTrait for Addr<DirsOptions> {
     ) -> impl Future<Output = MailBoxResult<()>> + Send {
         self.send(ModifyMangaDirMessage(path))
     }
+
+    async fn migrate_storage(
+        &self,
+        format: StorageFormat,
+    ) -> ManagerCoreResult<StorageMigrationReport> {
+        self.send(MigrateStorageMessage(format)).await?
+    }
 }
 
 impl<A> ModifyDirOptionAsyncTrait for A
@@ -94,4 +111,11 @@ where
     ) -> MailBoxResult<()> {
         self.get_dir_options().await?.modify_mangas_path(path).await
     }
+
+    async fn migrate_storage(
+        &self,
+        format: StorageFormat,
+    ) -> ManagerCoreResult<StorageMigrationReport> {
+        self.get_dir_options().await?.migrate_storage(format).await
+    }
 }
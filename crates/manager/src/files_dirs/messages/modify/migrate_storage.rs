@@ -0,0 +1,26 @@
+use crate::{ManagerCoreResult, files_dirs::DirsOptions};
+use actix::prelude::*;
+use api_core::file_dirs::{StorageFormat, storage::StorageMigrationReport};
+
+/// Rewrite the whole library in the given [`StorageFormat`]
+/// and use it for the next pushes.
+#[derive(Debug, Clone, Copy, Message)]
+#[rtype(result = "ManagerCoreResult<StorageMigrationReport>")]
+pub struct MigrateStorageMessage(pub StorageFormat);
+
+impl From<StorageFormat> for MigrateStorageMessage {
+    fn from(value: StorageFormat) -> Self {
+        Self(value)
+    }
+}
+
+impl Handler<MigrateStorageMessage> for DirsOptions {
+    type Result = ManagerCoreResult<StorageMigrationReport>;
+    fn handle(&mut self, msg: MigrateStorageMessage, _ctx: &mut Self::Context) -> Self::Result {
+        let report = self.migrate_storage(msg.0)?;
+        for (path, err) in &report.failed {
+            log::error!("Cannot migrate {}: {err}", path.display());
+        }
+        Ok(report)
+    }
+}
@@ -49,5 +49,6 @@ where
         if let Err(e) = self.verify_and_init() {
             log::error!("{e}");
         }
+        self.send_moved_dirs();
     }
 }
@@ -45,5 +45,6 @@ where
         if let Err(e) = self.verify_and_init() {
             log::error!("{e}");
         }
+        self.send_moved_dirs();
     }
 }
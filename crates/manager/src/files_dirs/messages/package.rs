@@ -0,0 +1,177 @@
+#[cfg(test)]
+mod tests;
+
+use std::{
+    fs::File,
+    future::Future,
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
+
+use actix::prelude::*;
+use emdx::{
+    Archive, PackageBuilder, PackageContents,
+    archive::import::{ConflictResolution, ImportEntry, ImportOptions, ImportReport},
+    tasks::PackageTask,
+};
+
+use crate::{
+    DirsOptions, MailBoxResult, download::state::messages::get::GetManagerStateData,
+    files_dirs::events::FilesDirSubscriberMessage,
+};
+
+/// Import an emdx package (or the parts of a split package) into the library.
+///
+/// The package is read on a blocking thread, so the actor keeps handling the other messages:
+/// the running [`PackageTask`] is returned right away.
+/// The subscribers are told about the imported data once the import is done.
+#[derive(Debug, Clone, Message)]
+#[rtype(result = "PackageTask<ImportReport>")]
+pub struct ImportPackageMessage {
+    pub path: PathBuf,
+    pub options: ImportOptions,
+}
+
+/// The events of the entries written into the library.
+fn import_events(report: &ImportReport) -> Vec<FilesDirSubscriberMessage> {
+    let updated = report
+        .conflicted
+        .iter()
+        .filter(|conflict| conflict.resolution != ConflictResolution::KeptExisting)
+        .map(|conflict| (&conflict.entry, true));
+    let added = report.imported.iter().map(|entry| (entry, false));
+    let mut events = Vec::new();
+    for (entry, is_update) in added.chain(updated) {
+        let event = match entry {
+            ImportEntry::Manga(id) if is_update => {
+                FilesDirSubscriberMessage::UpdatedManga { id: *id }
+            }
+            ImportEntry::Manga(id) => FilesDirSubscriberMessage::AddedManga { id: *id },
+            ImportEntry::Cover(id) => FilesDirSubscriberMessage::AddedCover { id: *id },
+            ImportEntry::Chapter(id) => FilesDirSubscriberMessage::AddedChapter { id: *id },
+            ImportEntry::ChapterImage { id, mode, .. } => {
+                FilesDirSubscriberMessage::AddedChapterImages {
+                    id: *id,
+                    mode: (*mode).into(),
+                }
+            }
+        };
+        // Multiple images of the same chapter only needs one event
+        if !events.contains(&event) {
+            events.push(event);
+        }
+    }
+    events
+}
+
+impl Handler<ImportPackageMessage> for DirsOptions {
+    type Result = MessageResult<ImportPackageMessage>;
+    fn handle(&mut self, msg: ImportPackageMessage, _ctx: &mut Self::Context) -> Self::Result {
+        let mut dirs = self.core.clone();
+        let subscribers = self.subscribers().clone();
+        MessageResult(PackageTask::spawn(move |progress| {
+            let mut archive = Archive::open(&msg.path)?;
+            let report = archive.import_with_progress(&mut dirs, &msg.options, |current| {
+                progress.send_replace(current);
+            })?;
+            for event in import_events(&report) {
+                subscribers.do_send(event);
+            }
+            Ok(report)
+        }))
+    }
+}
+
+/// Package the library (or a part of it) into an emdx package file.
+///
+/// The package is written on a blocking thread, so the actor keeps handling the other messages:
+/// the running [`PackageTask`] is returned right away.
+#[derive(Debug, Clone, Message)]
+#[rtype(result = "PackageTask<PackageContents>")]
+pub struct ExportPackageMessage {
+    pub path: PathBuf,
+    /// What to package, the whole library if `None`
+    pub contents: Option<PackageContents>,
+    /// The package settings, its directories are replaced by the library ones
+    pub builder: PackageBuilder,
+}
+
+impl ExportPackageMessage {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            contents: None,
+            builder: Default::default(),
+        }
+    }
+}
+
+impl Handler<ExportPackageMessage> for DirsOptions {
+    type Result = MessageResult<ExportPackageMessage>;
+    fn handle(&mut self, msg: ExportPackageMessage, _ctx: &mut Self::Context) -> Self::Result {
+        let dirs = self.core.clone();
+        MessageResult(PackageTask::spawn(move |progress| {
+            let contents = match msg.contents {
+                Some(contents) => contents,
+                None => (&dirs).try_into()?,
+            };
+            let builder = msg.builder.set_dir_options(dirs).set_content(contents);
+            let mut writer = BufWriter::new(File::create(&msg.path)?);
+            let contents = builder.build_with_progress(&mut writer, |current| {
+                progress.send_replace(current);
+            })?;
+            writer.flush()?;
+            Ok(contents)
+        }))
+    }
+}
+
+pub trait PackageAsyncTrait: Sync {
+    fn import_package(
+        &self,
+        path: PathBuf,
+        options: ImportOptions,
+    ) -> impl Future<Output = MailBoxResult<PackageTask<ImportReport>>> + Send;
+    fn export_package(
+        &self,
+        message: ExportPackageMessage,
+    ) -> impl Future<Output = MailBoxResult<PackageTask<PackageContents>>> + Send;
+}
+
+impl PackageAsyncTrait for Addr<DirsOptions> {
+    fn import_package(
+        &self,
+        path: PathBuf,
+        options: ImportOptions,
+    ) -> impl Future<Output = MailBoxResult<PackageTask<ImportReport>>> + Send {
+        self.send(ImportPackageMessage { path, options })
+    }
+    fn export_package(
+        &self,
+        message: ExportPackageMessage,
+    ) -> impl Future<Output = MailBoxResult<PackageTask<PackageContents>>> + Send {
+        self.send(message)
+    }
+}
+
+impl<A> PackageAsyncTrait for A
+where
+    A: GetManagerStateData + Sync,
+{
+    async fn import_package(
+        &self,
+        path: PathBuf,
+        options: ImportOptions,
+    ) -> MailBoxResult<PackageTask<ImportReport>> {
+        self.get_dir_options()
+            .await?
+            .import_package(path, options)
+            .await
+    }
+    async fn export_package(
+        &self,
+        message: ExportPackageMessage,
+    ) -> MailBoxResult<PackageTask<PackageContents>> {
+        self.get_dir_options().await?.export_package(message).await
+    }
+}
@@ -0,0 +1,139 @@
+use std::path::Path;
+
+use actix::prelude::*;
+use api_core::{
+    DirsOptions as DirsOptionsCore,
+    data_push::{Push, chapter::image::ChapterImagePushEntry},
+};
+use emdx::archive::import::{ImportEntry, ImportOptions};
+use mangadex_api_schema_rust::v5::{ChapterObject, CoverObject, MangaObject, Relationship};
+use mangadex_api_types_rust::RelationshipType;
+use tempfile::TempDir;
+use uuid::Uuid;
+
+use crate::{
+    DirsOptions,
+    files_dirs::{
+        events::FilesDirSubscriberMessage,
+        messages::{
+            delete::chapter::images::ChapterImages, subscribe::DirsOptionsSubscribeMessage,
+        },
+    },
+    recipients::MaybeWeakRecipient,
+};
+
+use super::{ExportPackageMessage, ImportPackageMessage};
+
+/// Collects the library events.
+#[derive(Debug, Default)]
+struct EventsCollector(Vec<FilesDirSubscriberMessage>);
+
+impl Actor for EventsCollector {
+    type Context = Context<Self>;
+}
+
+impl Handler<FilesDirSubscriberMessage> for EventsCollector {
+    type Result = ();
+    fn handle(&mut self, msg: FilesDirSubscriberMessage, _ctx: &mut Self::Context) -> Self::Result {
+        self.0.push(msg);
+    }
+}
+
+#[derive(Debug, Message)]
+#[rtype(result = "Vec<FilesDirSubscriberMessage>")]
+struct TakeEvents;
+
+impl Handler<TakeEvents> for EventsCollector {
+    type Result = MessageResult<TakeEvents>;
+    fn handle(&mut self, _msg: TakeEvents, _ctx: &mut Self::Context) -> Self::Result {
+        MessageResult(std::mem::take(&mut self.0))
+    }
+}
+
+/// A library at `dir` with a manga, its cover and one of its chapters.
+fn library(dir: &Path) -> (DirsOptionsCore, Uuid, Uuid, Uuid) {
+    let mut dirs = DirsOptionsCore::new_from_data_dir(dir);
+    dirs.init_dirs().unwrap();
+    let mut manga = MangaObject::default();
+    manga.id = Uuid::new_v4();
+    let mut manga_relationship = Relationship::default();
+    manga_relationship.id = manga.id;
+    manga_relationship.type_ = RelationshipType::Manga;
+    let mut cover = CoverObject::default();
+    cover.id = Uuid::new_v4();
+    cover.attributes.file_name = format!("{}.png", cover.id);
+    cover.relationships.push(manga_relationship.clone());
+    let mut chapter = ChapterObject::default();
+    chapter.id = Uuid::new_v4();
+    chapter.relationships.push(manga_relationship);
+    dirs.push(manga.clone()).unwrap();
+    dirs.push((cover.clone(), &b"cover"[..])).unwrap();
+    dirs.push(chapter.clone()).unwrap();
+    dirs.push(ChapterImagePushEntry::new(
+        chapter.id,
+        "1.png".into(),
+        &b"page"[..],
+    ))
+    .unwrap();
+    (dirs, manga.id, cover.id, chapter.id)
+}
+
+#[actix::test]
+async fn export_then_import_a_package() {
+    let tmp = TempDir::new().unwrap();
+    let (source, manga, cover, chapter) = library(&tmp.path().join("source"));
+    let path = tmp.path().join("library.emdx");
+
+    let source = DirsOptions::from(source).start();
+    let contents = source
+        .send(ExportPackageMessage::new(path.clone()))
+        .await
+        .unwrap()
+        .wait()
+        .await
+        .unwrap();
+    assert_eq!(contents.data[&manga].chapters[&chapter].data, ["1.png"]);
+
+    let target_dirs = DirsOptionsCore::new_from_data_dir(tmp.path().join("target"));
+    let target = DirsOptions::from(target_dirs.clone()).start();
+    let events = EventsCollector::default().start();
+    target
+        .send(DirsOptionsSubscribeMessage(MaybeWeakRecipient::Strong(
+            events.clone().recipient(),
+        )))
+        .await
+        .unwrap();
+    let report = target
+        .send(ImportPackageMessage {
+            path,
+            options: ImportOptions::new(),
+        })
+        .await
+        .unwrap()
+        .wait()
+        .await
+        .unwrap();
+    assert!(report.imported.contains(&ImportEntry::Manga(manga)));
+    assert!(report.imported.contains(&ImportEntry::Cover(cover)));
+    assert!(report.imported.contains(&ImportEntry::Chapter(chapter)));
+    assert!(
+        target_dirs
+            .chapter_images_dir(chapter, Default::default())
+            .join("1.png")
+            .exists()
+    );
+
+    // the events are sent before the task ends, so they are already in the collector mailbox
+    let received = events.send(TakeEvents).await.unwrap();
+    for event in [
+        FilesDirSubscriberMessage::AddedManga { id: manga },
+        FilesDirSubscriberMessage::AddedCover { id: cover },
+        FilesDirSubscriberMessage::AddedChapter { id: chapter },
+        FilesDirSubscriberMessage::AddedChapterImages {
+            id: chapter,
+            mode: ChapterImages::Data,
+        },
+    ] {
+        assert!(received.contains(&event), "{event:?} is missing");
+    }
+}
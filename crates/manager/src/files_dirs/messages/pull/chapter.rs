@@ -3,6 +3,7 @@ pub mod chapter_data_pull;
 pub mod chapter_ids_list_data_pull;
 pub mod chapter_image_data_pull;
 pub mod chapter_image_data_saver_pull;
+pub mod chapter_images_verify;
 pub mod chapter_list_data_pull;
 
 use std::{fs::File, future::Future, path::Path};
@@ -13,8 +14,11 @@ use uuid::Uuid;
 
 use crate::{
     data_pulls::chapter::{
-        ids::ChapterIdsListDataPull, images::ChapterImagesData, list::ChapterListDataPull,
+        ids::ChapterIdsListDataPull,
+        images::{ChapterImagesData, verify::ChapterImagesVerificationReport},
+        list::ChapterListDataPull,
     },
+    data_push::chapter::image::Mode,
     download::state::messages::get::GetManagerStateData,
     DirsOptions, MailBoxResult, ManagerCoreResult,
 };
@@ -24,6 +28,7 @@ pub use self::{
     chapter_ids_list_data_pull::ChapterIdsListDataPullMessage,
     chapter_image_data_pull::ChapterImageDataPullMessage,
     chapter_image_data_saver_pull::ChapterImageDataSaverPullMessage,
+    chapter_images_verify::VerifyChapterImagesMessage,
     chapter_list_data_pull::ChapterListDataPullMessage,
 };
 
@@ -36,6 +41,17 @@ pub trait ChapterDataPullAsyncTrait: Sync {
         &self,
         id: Uuid,
     ) -> impl Future<Output = ManagerCoreResult<ChapterImagesData>> + Send;
+    /// Same as [`get_chapter_images`](Self::get_chapter_images)
+    /// but hides the images that doesn't match the hash in their filename.
+    fn get_chapter_images_verified(
+        &self,
+        id: Uuid,
+    ) -> impl Future<Output = ManagerCoreResult<ChapterImagesData>> + Send;
+    fn verify_chapter_images(
+        &self,
+        id: Uuid,
+        mode: Mode,
+    ) -> impl Future<Output = ManagerCoreResult<ChapterImagesVerificationReport>> + Send;
     fn get_chapters(&self) -> impl Future<Output = ManagerCoreResult<ChapterListDataPull>> + Send;
     fn get_chapter_image(
         &self,
@@ -58,7 +74,18 @@ impl ChapterDataPullAsyncTrait for Addr<DirsOptions> {
         self.send(ChapterDataPullMessage(id)).await?
     }
     async fn get_chapter_images(&self, id: Uuid) -> ManagerCoreResult<ChapterImagesData> {
-        self.send(ChapterImagesPullMessage(id)).await?
+        self.send(ChapterImagesPullMessage::new(id)).await?
+    }
+    async fn get_chapter_images_verified(&self, id: Uuid) -> ManagerCoreResult<ChapterImagesData> {
+        self.send(ChapterImagesPullMessage::new(id).verify_hashes(true))
+            .await?
+    }
+    async fn verify_chapter_images(
+        &self,
+        id: Uuid,
+        mode: Mode,
+    ) -> ManagerCoreResult<ChapterImagesVerificationReport> {
+        self.send(VerifyChapterImagesMessage::new(id, mode)).await?
     }
     async fn get_chapters(&self) -> ManagerCoreResult<ChapterListDataPull> {
         self.send(ChapterListDataPullMessage).await?
@@ -98,6 +125,24 @@ where
         self.get_dir_options().await?.get_chapter_images(id).await
     }
 
+    async fn get_chapter_images_verified(&self, id: Uuid) -> ManagerCoreResult<ChapterImagesData> {
+        self.get_dir_options()
+            .await?
+            .get_chapter_images_verified(id)
+            .await
+    }
+
+    async fn verify_chapter_images(
+        &self,
+        id: Uuid,
+        mode: Mode,
+    ) -> ManagerCoreResult<ChapterImagesVerificationReport> {
+        self.get_dir_options()
+            .await?
+            .verify_chapter_images(id, mode)
+            .await
+    }
+
     async fn get_chapters(&self) -> ManagerCoreResult<ChapterListDataPull> {
         self.get_dir_options().await?.get_chapters().await
     }
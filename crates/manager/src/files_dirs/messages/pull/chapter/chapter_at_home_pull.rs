@@ -1,26 +1,44 @@
-use std::{fs::read_dir, ops::Deref, path::Path};
-
 use actix::prelude::*;
 use uuid::Uuid;
 
 use crate::{
-    data_pulls::{chapter::images::ChapterImagesData, Pull},
-    files_dirs::FileExtension,
     DirsOptions, ManagerCoreResult,
+    data_pulls::{Pull, chapter::images::ChapterImagesData},
 };
 
 #[derive(Debug, Clone, Hash, Default, Copy)]
-pub struct ChapterImagesPullMessage(pub Uuid);
+pub struct ChapterImagesPullMessage {
+    pub id: Uuid,
+    pub verify_hashes: bool,
+}
+
+impl ChapterImagesPullMessage {
+    pub fn new(id: Uuid) -> Self {
+        Self {
+            id,
+            verify_hashes: false,
+        }
+    }
+    /// Hide the images whose content doesn't match the SHA-256 hash in their filename.
+    ///
+    /// Every image is read and hashed on a blocking thread, so this is a lot slower.
+    pub fn verify_hashes(self, verify_hashes: bool) -> Self {
+        Self {
+            verify_hashes,
+            ..self
+        }
+    }
+}
 
 impl From<Uuid> for ChapterImagesPullMessage {
     fn from(value: Uuid) -> Self {
-        Self(value)
+        Self::new(value)
     }
 }
 
 impl From<ChapterImagesPullMessage> for Uuid {
     fn from(value: ChapterImagesPullMessage) -> Self {
-        value.0
+        value.id
     }
 }
 
@@ -29,32 +47,29 @@ impl Message for ChapterImagesPullMessage {
 }
 
 impl Pull<ChapterImagesData, ChapterImagesPullMessage> for DirsOptions {
-    fn pull(&self, id: ChapterImagesPullMessage) -> ManagerCoreResult<ChapterImagesData> {
-        self.deref()
-            .pull(Into::<Uuid>::into(id))
-            .map_err(|e: api_core::Error| e.into())
+    fn pull(&self, msg: ChapterImagesPullMessage) -> ManagerCoreResult<ChapterImagesData> {
+        Ok(self.core.pull_chapter_images(msg.id, msg.verify_hashes)?)
     }
 
     type Error = crate::Error;
 }
 
-fn get_data<P: AsRef<Path>>(p: P) -> Vec<String> {
-    read_dir(p)
-        .map(|read| {
-            read.flatten()
-                .map(|p| p.path())
-                .filter(|p| p.is_image())
-                .flat_map(|p| Some(String::from(p.as_os_str().to_str()?)))
-                .collect::<Vec<_>>()
-        })
-        .unwrap_or_default()
-}
-
 impl Handler<ChapterImagesPullMessage> for DirsOptions {
-    type Result = <ChapterImagesPullMessage as Message>::Result;
+    type Result = ResponseFuture<<ChapterImagesPullMessage as Message>::Result>;
     fn handle(&mut self, msg: ChapterImagesPullMessage, _ctx: &mut Self::Context) -> Self::Result {
-        let data = get_data(self.chapters_id_data_add(msg.0));
-        let data_saver = get_data(self.chapters_id_data_saver_add(msg.0));
-        Ok(ChapterImagesData { data, data_saver })
+        if !msg.verify_hashes {
+            let images = self.pull(msg);
+            return Box::pin(async move { images });
+        }
+        // the images are hashed on a blocking thread, so the actor isn't blocked
+        let dirs = self.core.clone();
+        Box::pin(async move {
+            let images = tokio::task::spawn_blocking(move || {
+                dirs.pull_chapter_images(msg.id, msg.verify_hashes)
+            })
+            .await
+            .map_err(std::io::Error::other)??;
+            Ok(images)
+        })
     }
 }
@@ -0,0 +1,43 @@
+use actix::prelude::*;
+use api_core::data_pulls::chapter::images::verify::ChapterImagesVerificationReport;
+use uuid::Uuid;
+
+use crate::{DirsOptions, ManagerCoreResult, data_push::chapter::image::Mode};
+
+/// Hash every stored `mode` image of a chapter and report the ones
+/// whose content doesn't match the SHA-256 hash in their filename.
+///
+/// The images are hashed on a blocking thread, so the actor keeps handling the other messages.
+#[derive(Debug, Clone, Hash, Default, Copy)]
+pub struct VerifyChapterImagesMessage {
+    pub id: Uuid,
+    pub mode: Mode,
+}
+
+impl VerifyChapterImagesMessage {
+    pub fn new(id: Uuid, mode: Mode) -> Self {
+        Self { id, mode }
+    }
+}
+
+impl Message for VerifyChapterImagesMessage {
+    type Result = ManagerCoreResult<ChapterImagesVerificationReport>;
+}
+
+impl Handler<VerifyChapterImagesMessage> for DirsOptions {
+    type Result = ResponseFuture<<VerifyChapterImagesMessage as Message>::Result>;
+    fn handle(
+        &mut self,
+        msg: VerifyChapterImagesMessage,
+        _ctx: &mut Self::Context,
+    ) -> Self::Result {
+        let dirs = self.core.clone();
+        Box::pin(async move {
+            let report =
+                tokio::task::spawn_blocking(move || dirs.verify_chapter_images(msg.id, msg.mode))
+                    .await
+                    .map_err(std::io::Error::other)??;
+            Ok(report)
+        })
+    }
+}
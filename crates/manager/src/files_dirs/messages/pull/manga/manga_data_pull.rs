@@ -8,7 +8,7 @@ use uuid::Uuid;
 
 use crate::{
     data_pulls::Pull,
-    files_dirs::messages::pull::chapter::ChapterListDataPullMessage,
+    files_dirs::messages::index::ChapterIndexedPullMessage,
     prelude::{ChapterListDataPullFilterParams, IntoParamedFilteredStream},
     DirsOptions, ManagerCoreResult,
 };
@@ -39,17 +39,18 @@ impl Handler<MangaDataPullMessage> for DirsOptions {
         let manga: ManagerCoreResult<MangaObject> = self
             .deref()
             .deref()
-            .pull(msg.into())
+            .pull(msg.0)
             .map_err(|e: api_core::Error| e.into());
-        let chapter_pull = self.handle(ChapterListDataPullMessage, ctx);
+        let filter = ChapterListDataPullFilterParams {
+            manga_ids: vec![msg.0],
+            ..Default::default()
+        };
+        let chapter_pull = self.handle(ChapterIndexedPullMessage(filter.clone()), ctx);
         Box::pin(async move {
             let mut manga = manga?;
             if let Ok(pull) = chapter_pull {
                 let langs = pull
-                    .to_filtered(ChapterListDataPullFilterParams {
-                        manga_ids: vec![manga.id],
-                        ..Default::default()
-                    })
+                    .to_filtered(filter)
                     .fold(Vec::<Language>::new(), |mut acc, chapter| {
                         let lang = chapter.attributes.translated_language;
                         if !acc.contains(&lang) {
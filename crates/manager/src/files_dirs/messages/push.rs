@@ -1,13 +1,81 @@
-use std::future::Future;
+#[cfg(test)]
+mod tests;
+
+use std::{collections::HashSet, future::Future};
 
 use actix::prelude::*;
 use dev::ToEnvelope;
+use mangadex_api_schema_rust::v5::{ChapterObject, CoverObject, MangaObject};
 
 use crate::{
-    data_push::Push, download::state::messages::get::GetManagerStateData, DirsOptions,
-    ManagerCoreResult,
+    DirsOptions, ManagerCoreResult,
+    data_push::{Push, chapter::image::ChapterImagePushEntry},
+    download::state::messages::get::GetManagerStateData,
+    files_dirs::events::FilesDirSubscriberMessage,
 };
 
+/// The events sent to the [`DirsOptions`] subscribers when some data is pushed.
+///
+/// The data without any event can use the default implementation.
+pub trait PushEvents {
+    /// Called before the push, so the stored data is still the previous one.
+    fn push_events(&self, _dirs: &DirsOptions) -> Vec<FilesDirSubscriberMessage> {
+        Vec::new()
+    }
+}
+
+impl PushEvents for MangaObject {
+    fn push_events(&self, dirs: &DirsOptions) -> Vec<FilesDirSubscriberMessage> {
+        let id = self.id;
+        if dirs.manga_file(id).is_some() {
+            vec![FilesDirSubscriberMessage::UpdatedManga { id }]
+        } else {
+            vec![FilesDirSubscriberMessage::AddedManga { id }]
+        }
+    }
+}
+
+impl PushEvents for ChapterObject {
+    fn push_events(&self, _dirs: &DirsOptions) -> Vec<FilesDirSubscriberMessage> {
+        vec![FilesDirSubscriberMessage::AddedChapter { id: self.id }]
+    }
+}
+
+impl PushEvents for CoverObject {
+    fn push_events(&self, _dirs: &DirsOptions) -> Vec<FilesDirSubscriberMessage> {
+        vec![FilesDirSubscriberMessage::AddedCover { id: self.id }]
+    }
+}
+
+impl<R> PushEvents for (CoverObject, R) {
+    fn push_events(&self, dirs: &DirsOptions) -> Vec<FilesDirSubscriberMessage> {
+        self.0.push_events(dirs)
+    }
+}
+
+impl<R> PushEvents for ChapterImagePushEntry<R> {
+    fn push_events(&self, _dirs: &DirsOptions) -> Vec<FilesDirSubscriberMessage> {
+        vec![FilesDirSubscriberMessage::AddedChapterImages {
+            id: self.get_id(),
+            mode: self.get_mode().into(),
+        }]
+    }
+}
+
+impl<T: PushEvents> PushEvents for Vec<T> {
+    fn push_events(&self, dirs: &DirsOptions) -> Vec<FilesDirSubscriberMessage> {
+        // Multiple images of the same chapter only needs one event
+        let mut sent = HashSet::new();
+        self.iter()
+            .flat_map(|data| data.push_events(dirs))
+            .filter(|event| sent.insert(event.clone()))
+            .collect()
+    }
+}
+
+/// Push some data into the library.
+///
+/// The [`PushEvents`] of the data are sent to the [`DirsOptions`] subscribers once it is pushed.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub struct PushDataMessage<T> {
     data: T,
@@ -31,15 +99,21 @@ impl<T> Handler<PushDataMessage<T>> for DirsOptions
 where
     Self: Push<T>,
     <Self as Push<T>>::Error: Into<crate::Error>,
+    T: PushEvents,
 {
     type Result = <PushDataMessage<T> as Message>::Result;
     fn handle(&mut self, msg: PushDataMessage<T>, _ctx: &mut Self::Context) -> Self::Result {
+        let events = msg.data.push_events(self);
         let res = if msg.verify {
             self.verify_and_push(msg.data)
         } else {
             self.push(msg.data)
         };
-        res.map_err(|e| e.into())
+        res.map_err(|e| e.into())?;
+        for event in events {
+            self.subscribers().do_send(event);
+        }
+        Ok(())
     }
 }
 
@@ -0,0 +1,112 @@
+use actix::prelude::*;
+use api_core::{DirsOptions as DirsOptionsCore, data_push::chapter::image::ChapterImagePushEntry};
+use mangadex_api_schema_rust::v5::{ChapterObject, MangaObject};
+use tempfile::TempDir;
+use uuid::Uuid;
+
+use crate::{
+    DirsOptions,
+    files_dirs::{
+        events::FilesDirSubscriberMessage,
+        messages::{
+            delete::chapter::images::ChapterImages, subscribe::DirsOptionsSubscribeMessage,
+        },
+    },
+    recipients::MaybeWeakRecipient,
+};
+
+use super::PushDataMessage;
+
+/// Collects the library events.
+#[derive(Debug, Default)]
+struct EventsCollector(Vec<FilesDirSubscriberMessage>);
+
+impl Actor for EventsCollector {
+    type Context = Context<Self>;
+}
+
+impl Handler<FilesDirSubscriberMessage> for EventsCollector {
+    type Result = ();
+    fn handle(&mut self, msg: FilesDirSubscriberMessage, _ctx: &mut Self::Context) -> Self::Result {
+        self.0.push(msg);
+    }
+}
+
+#[derive(Debug, Message)]
+#[rtype(result = "Vec<FilesDirSubscriberMessage>")]
+struct TakeEvents;
+
+impl Handler<TakeEvents> for EventsCollector {
+    type Result = MessageResult<TakeEvents>;
+    fn handle(&mut self, _msg: TakeEvents, _ctx: &mut Self::Context) -> Self::Result {
+        MessageResult(std::mem::take(&mut self.0))
+    }
+}
+
+/// A running empty library and the collector of its events.
+async fn library(tmp: &TempDir) -> (Addr<DirsOptions>, Addr<EventsCollector>) {
+    let dirs = DirsOptionsCore::new_from_data_dir(tmp.path());
+    dirs.init_dirs().unwrap();
+    let dirs = DirsOptions::from(dirs).start();
+    let events = EventsCollector::default().start();
+    dirs.send(DirsOptionsSubscribeMessage(MaybeWeakRecipient::Strong(
+        events.clone().recipient(),
+    )))
+    .await
+    .unwrap();
+    (dirs, events)
+}
+
+async fn push<T>(dirs: &Addr<DirsOptions>, msg: PushDataMessage<T>)
+where
+    T: Send + 'static,
+    DirsOptions: Handler<PushDataMessage<T>>,
+{
+    dirs.send(msg).await.unwrap().unwrap();
+}
+
+#[actix::test]
+async fn pushing_a_manga_twice_adds_then_updates_it() {
+    let tmp = TempDir::new().unwrap();
+    let (dirs, events) = library(&tmp).await;
+    let mut manga = MangaObject::default();
+    manga.id = Uuid::new_v4();
+
+    for _ in 0..2 {
+        push(&dirs, PushDataMessage::new(manga.clone()).verify(false)).await;
+    }
+
+    // the events are sent before the push ends, so they are already in the collector mailbox
+    assert_eq!(
+        events.send(TakeEvents).await.unwrap(),
+        [
+            FilesDirSubscriberMessage::AddedManga { id: manga.id },
+            FilesDirSubscriberMessage::UpdatedManga { id: manga.id },
+        ]
+    );
+}
+
+#[actix::test]
+async fn pushing_chapter_images_sends_one_event_per_chapter() {
+    let tmp = TempDir::new().unwrap();
+    let (dirs, events) = library(&tmp).await;
+    let mut chapter = ChapterObject::default();
+    chapter.id = Uuid::new_v4();
+
+    push(&dirs, PushDataMessage::new(chapter.clone()).verify(false)).await;
+    let images = ["1.png", "2.png", "3.png"]
+        .map(|filename| ChapterImagePushEntry::new(chapter.id, filename.into(), &b"page"[..]))
+        .to_vec();
+    push(&dirs, PushDataMessage::new(images).verify(false)).await;
+
+    assert_eq!(
+        events.send(TakeEvents).await.unwrap(),
+        [
+            FilesDirSubscriberMessage::AddedChapter { id: chapter.id },
+            FilesDirSubscriberMessage::AddedChapterImages {
+                id: chapter.id,
+                mode: ChapterImages::Data,
+            },
+        ]
+    );
+}
@@ -1,10 +1,15 @@
-use std::sync::Arc;
+#[cfg(test)]
+mod tests;
+
+use std::{collections::HashMap, sync::Arc};
 
 use tokio::sync::RwLock;
 
 use mangadex_api_types_rust::RelationshipType;
 use serde::{Deserialize, Serialize};
 
+use crate::download::chapter::task::DownloadMode;
+
 use super::{HistoryBaseError, HistoryEntry, Insert, IsIn, Remove};
 
 pub mod error;
@@ -15,6 +20,9 @@ pub(crate) type HBResult<T> = Result<T, HistoryBaseError>;
 pub struct HistoryBase {
     history_list: Vec<uuid::Uuid>,
     data_type: RelationshipType,
+    /// The download modes of the entries that have one
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    modes: HashMap<uuid::Uuid, DownloadMode>,
 }
 
 impl From<HistoryBase> for Arc<RwLock<HistoryBase>> {
@@ -28,6 +36,7 @@ impl HistoryBase {
         HistoryBase {
             history_list: Vec::new(),
             data_type,
+            modes: HashMap::new(),
         }
     }
     pub fn get_history_list_mut(&mut self) -> &mut Vec<uuid::Uuid> {
@@ -45,6 +54,20 @@ impl HistoryBase {
     pub fn is_this_type(&self, to_use_rel: RelationshipType) -> bool {
         self.data_type == to_use_rel
     }
+    pub fn get_mode(&self, id: uuid::Uuid) -> Option<DownloadMode> {
+        self.modes.get(&id).copied()
+    }
+    /// The entries of this history, with their download mode.
+    pub fn get_entries(&self) -> Vec<HistoryEntry> {
+        self.history_list
+            .iter()
+            .map(|id| {
+                let mut entry = HistoryEntry::new(*id, self.data_type);
+                entry.set_mode(self.get_mode(*id));
+                entry
+            })
+            .collect()
+    }
 }
 
 impl IsIn<uuid::Uuid> for HistoryBase {
@@ -87,6 +110,10 @@ impl Insert<HistoryEntry> for HistoryBase {
 
     fn insert(&mut self, input: HistoryEntry) -> Self::Output {
         let result = <Self as IsIn<HistoryEntry>>::is_in(self, input)?;
+        // A download restarted with another mode should be resumed with it
+        if let Some(mode) = input.mode {
+            self.modes.insert(input.id, mode);
+        }
         if !result {
             self.get_history_list_mut().push(input.id);
         } else {
@@ -103,6 +130,7 @@ impl Remove<uuid::Uuid> for HistoryBase {
         let position = <Self as IsIn<uuid::Uuid>>::is_in(self, input)
             .ok_or(HistoryBaseError::NotFound(input))?;
         self.get_history_list_mut().remove(position);
+        self.modes.remove(&input);
         Ok(())
     }
 }
@@ -0,0 +1,78 @@
+use mangadex_api_types_rust::RelationshipType;
+use tempfile::TempDir;
+use uuid::Uuid;
+
+use crate::{
+    download::chapter::task::DownloadMode,
+    history::{
+        HistoryEntry, HistoryWFile, Insert, Remove,
+        history_w_file::traits::{AutoCommitRollbackInsert, RollBackable},
+    },
+};
+
+use super::{HistoryBase, HistoryBaseError};
+
+fn chapter_entry(id: Uuid, mode: Option<DownloadMode>) -> HistoryEntry {
+    let mut entry = HistoryEntry::new(id, RelationshipType::Chapter);
+    entry.set_mode(mode);
+    entry
+}
+
+#[test]
+fn the_entries_keep_their_mode() {
+    let mut history = HistoryBase::new(RelationshipType::Chapter);
+    let data_saver = chapter_entry(Uuid::new_v4(), Some(DownloadMode::DataSaver));
+    let no_mode = chapter_entry(Uuid::new_v4(), None);
+    history.insert(data_saver).unwrap();
+    history.insert(no_mode).unwrap();
+
+    assert_eq!(history.get_entries(), [data_saver, no_mode]);
+
+    history.remove(data_saver).unwrap();
+    assert_eq!(history.get_mode(data_saver.id), None);
+}
+
+#[test]
+fn inserting_again_changes_the_mode() {
+    let mut history = HistoryBase::new(RelationshipType::Chapter);
+    let id = Uuid::new_v4();
+    history
+        .insert(chapter_entry(id, Some(DownloadMode::Normal)))
+        .unwrap();
+
+    assert_eq!(
+        history.insert(chapter_entry(id, Some(DownloadMode::DataSaver))),
+        Err(HistoryBaseError::AlreadyExists(id))
+    );
+    assert_eq!(history.get_mode(id), Some(DownloadMode::DataSaver));
+}
+
+#[test]
+fn the_history_files_without_modes_are_still_read() {
+    let id = Uuid::new_v4();
+    let history: HistoryBase = serde_json::from_str(&format!(
+        r#"{{"history_list":["{id}"],"data_type":"chapter"}}"#
+    ))
+    .unwrap();
+
+    assert_eq!(history.get_entries(), [chapter_entry(id, None)]);
+}
+
+#[test]
+fn the_mode_is_written_in_the_history_file() {
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join("chapter.json");
+    let entry = chapter_entry(Uuid::new_v4(), Some(DownloadMode::DataSaver));
+    let mut history = HistoryWFile::new(RelationshipType::Chapter, &path);
+    AutoCommitRollbackInsert::insert(&mut history, entry).unwrap();
+
+    assert_eq!(
+        HistoryWFile::from_file(&path)
+            .unwrap()
+            .get_history()
+            .get_entries(),
+        [entry]
+    );
+    history.rollback().unwrap();
+    assert_eq!(history.get_history().get_entries(), [entry]);
+}
@@ -2,15 +2,24 @@ use mangadex_api_schema_rust::{v5::Relationship, ApiObject, ApiObjectNoRelations
 use mangadex_api_types_rust::RelationshipType;
 use serde::{Deserialize, Serialize};
 
+use crate::download::chapter::task::DownloadMode;
+
 #[derive(Serialize, Deserialize, Clone, Copy, Hash, PartialEq, Eq, Debug)]
 pub struct HistoryEntry {
     pub(crate) id: uuid::Uuid,
     pub(crate) data_type: RelationshipType,
+    /// The mode of a chapter download
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) mode: Option<DownloadMode>,
 }
 
 impl HistoryEntry {
     pub fn new(id: uuid::Uuid, data_type: RelationshipType) -> HistoryEntry {
-        HistoryEntry { id, data_type }
+        HistoryEntry {
+            id,
+            data_type,
+            mode: None,
+        }
     }
     pub fn get_id(&self) -> uuid::Uuid {
         self.id
@@ -24,6 +33,12 @@ impl HistoryEntry {
     pub fn set_data_type(&mut self, data_type: RelationshipType) {
         self.data_type = data_type;
     }
+    pub fn get_mode(&self) -> Option<DownloadMode> {
+        self.mode
+    }
+    pub fn set_mode(&mut self, mode: Option<DownloadMode>) {
+        self.mode = mode;
+    }
 }
 
 impl<A> From<&ApiObjectNoRelationships<A>> for HistoryEntry {
@@ -1,4 +1,5 @@
 pub mod commit;
+pub mod entries;
 pub mod insert;
 pub mod is_in;
 pub mod remove;
@@ -0,0 +1,25 @@
+use actix::prelude::*;
+use mangadex_api_types_rust::RelationshipType;
+
+use crate::history::{HistoryEntry, service::HistoryActorService};
+
+/// Get every entry stored in the history files.
+///
+/// If a [`RelationshipType`] is given, only the entries of this type are returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct GetHistoryEntriesMessage(pub Option<RelationshipType>);
+
+impl Message for GetHistoryEntriesMessage {
+    type Result = Vec<HistoryEntry>;
+}
+
+impl Handler<GetHistoryEntriesMessage> for HistoryActorService {
+    type Result = <GetHistoryEntriesMessage as Message>::Result;
+    fn handle(&mut self, msg: GetHistoryEntriesMessage, _ctx: &mut Self::Context) -> Self::Result {
+        self.files
+            .iter()
+            .filter(|(data_type, _)| msg.0.is_none_or(|rel| rel == **data_type))
+            .flat_map(|(_, file)| file.get_history().get_entries())
+            .collect()
+    }
+}
@@ -36,7 +36,10 @@ pub mod prelude {
             messages::{
                 chapter::GetChapterDownloadManager, cover::GetCoverDownloadManager,
                 manga::GetMangaDownloadManager,
+                repair::RepairLibraryIssueAsyncTrait,
+                resume::ResumeUnfinishedDownloadsAsyncTrait,
             },
+            scheduler::TaskPriority,
             state::{
                 DownloadManagerState,
                 messages::{get::GetManagerStateData, update::UpdateManagerStateData},
@@ -51,9 +54,11 @@ pub mod prelude {
         files_dirs::{
             DirsOptions,
             messages::{
+                check::CheckLibraryAsyncTrait,
                 delete::DeleteDataAsyncTrait,
                 join::JoinPathAsyncTraits,
                 modify::ModifyDirOptionAsyncTrait,
+                package::PackageAsyncTrait,
                 pull::{
                     chapter::ChapterDataPullAsyncTrait, cover::CoverDataPullAsyncTrait,
                     manga::MangaDataPullAsyncTrait,
@@ -78,6 +83,9 @@ pub mod prelude {
             },
         },
         data_push::{Push, chapter::image::ChapterImagePushEntry},
-        file_dirs::DirsOptions as DirsOptionsCore,
+        file_dirs::{
+            DirsOptions as DirsOptionsCore, StorageFormat,
+            verification::{LibraryCheckReport, LibraryIssue, RepairAction},
+        },
     };
 }
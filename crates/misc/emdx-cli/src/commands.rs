@@ -0,0 +1,64 @@
+pub mod create;
+pub mod dict;
+pub mod diff;
+pub mod extract;
+pub mod inspect;
+pub mod merge;
+pub mod reencode;
+pub mod selection;
+pub mod verify;
+
+use std::{io::BufReader, path::Path};
+
+use clap::Subcommand;
+use emdx::{volume::MultiPartReader, Archive};
+
+#[derive(Debug, Subcommand)]
+pub enum Commands {
+    /// Create a package from a data directory
+    Create(Box<create::CreateArgs>),
+    /// List the mangas, covers and chapters inside of a package
+    List(inspect::ListArgs),
+    /// Print the package contents and options
+    Inspect(inspect::InspectArgs),
+    /// Extract a package into a data directory
+    #[command(alias = "import")]
+    Extract(extract::ExtractArgs),
+    /// Print the mangas, covers, chapters and entries that differ between two packages
+    Diff(diff::DiffArgs),
+    /// Merge packages into one, keeping the newest metadata
+    Merge(Box<merge::MergeArgs>),
+    /// Write a package again with other options, without extracting it
+    Reencode(Box<reencode::ReencodeArgs>),
+    /// Check the package entries against its manifest, or that they can be read for older packages
+    Verify(verify::VerifyArgs),
+    /// Train a zstd dictionary from the data that would be put in a package
+    Dict(Box<dict::DictArgs>),
+}
+
+pub trait Run {
+    fn run(&self) -> anyhow::Result<()>;
+}
+
+impl Run for Commands {
+    fn run(&self) -> anyhow::Result<()> {
+        match self {
+            Commands::Create(create_args) => create_args.run(),
+            Commands::List(list_args) => list_args.run(),
+            Commands::Inspect(inspect_args) => inspect_args.run(),
+            Commands::Extract(extract_args) => extract_args.run(),
+            Commands::Diff(diff_args) => diff_args.run(),
+            Commands::Merge(merge_args) => merge_args.run(),
+            Commands::Reencode(reencode_args) => reencode_args.run(),
+            Commands::Verify(verify_args) => verify_args.run(),
+            Commands::Dict(dict_args) => dict_args.run(),
+        }
+    }
+}
+
+/// Open a package file, or every part of a split package if there is no file at `path`.
+pub(crate) fn open_package<P: AsRef<Path>>(
+    path: P,
+) -> anyhow::Result<Archive<'static, BufReader<MultiPartReader>>> {
+    Ok(Archive::open(path)?)
+}
@@ -0,0 +1,172 @@
+use std::{
+    fs::{read, File},
+    io::{BufWriter, Write},
+    num::NonZeroUsize,
+    ops::Add,
+    path::PathBuf,
+};
+
+use clap::{Args, ValueEnum};
+use emdx::{
+    builder::{ImageProfile, ImageTargetFormat},
+    volume::volume_path,
+};
+
+use super::{open_package, selection::PackageSelectionArgs, Run};
+
+#[derive(Debug, Args)]
+pub struct CreateArgs {
+    /// The package file to write, or the name of the parts with `--max-part-size`
+    #[arg(short, long)]
+    pub output: PathBuf,
+    #[command(flatten)]
+    pub selection: PackageSelectionArgs,
+    /// The zstd compression level (0 means the zstd default level)
+    #[arg(long, default_value_t = 0)]
+    pub compression_level: i32,
+    /// Compress the images with zstd
+    #[arg(long)]
+    pub zstd_images: bool,
+    /// Compress the manga, cover and chapter metadata with zstd
+    #[arg(long)]
+    pub zstd_metadata: bool,
+    #[command(flatten)]
+    pub images: ImageProfileArgs,
+    /// A zstd dictionary file (made with `emdx dict`) to embed and compress the entries with
+    #[arg(long, conflicts_with = "train_dict")]
+    pub dict: Option<PathBuf>,
+    /// Train a dictionary of this maximum size (in bytes) from the packaged metadata and embed it
+    #[arg(long)]
+    pub train_dict: Option<usize>,
+    /// How many threads prepare the entries (defaults to the available parallelism)
+    #[arg(short = 'j', long)]
+    pub workers: Option<NonZeroUsize>,
+    /// How many zstd threads compress each entry frame
+    #[arg(long, default_value_t = 0)]
+    pub zstd_workers: u32,
+    /// Enable the zstd long distance matching
+    #[arg(long)]
+    pub long_distance_matching: bool,
+    /// Split the package into `<output>.part<N>.tar.zstd` parts of at most this size (in bytes)
+    #[arg(long)]
+    pub max_part_size: Option<u64>,
+    /// Only write the entries that are new or changed since this package (or split package),
+    /// with its compression options and dictionary
+    #[arg(
+        long,
+        conflicts_with_all = ["compression_level", "zstd_images", "zstd_metadata", "dict", "train_dict"]
+    )]
+    pub base: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ImageFormatArg {
+    Jpeg,
+    Png,
+    /// Lossless WebP
+    Webp,
+}
+
+impl From<ImageFormatArg> for ImageTargetFormat {
+    fn from(value: ImageFormatArg) -> Self {
+        match value {
+            ImageFormatArg::Jpeg => Self::Jpeg,
+            ImageFormatArg::Png => Self::Png,
+            ImageFormatArg::Webp => Self::WebPLossless,
+        }
+    }
+}
+
+/// How the images are transcoded
+#[derive(Debug, Args, Clone)]
+pub struct ImageProfileArgs {
+    /// Convert the images to JPEG (GIFs are kept as is)
+    #[arg(long)]
+    pub jpeg: bool,
+    /// Transcode the images to this format (GIFs are kept as is)
+    #[arg(long, conflicts_with = "jpeg")]
+    pub image_format: Option<ImageFormatArg>,
+    /// The JPEG quality of the transcoded images
+    #[arg(long, default_value_t = 75, value_parser = clap::value_parser!(u8).range(1..=100))]
+    pub quality: u8,
+    /// Shrink the wider images, keeping their aspect ratio (transcodes to JPEG by default)
+    #[arg(long)]
+    pub max_width: Option<u32>,
+    /// Shrink the taller images, keeping their aspect ratio (transcodes to JPEG by default)
+    #[arg(long)]
+    pub max_height: Option<u32>,
+    /// Convert the images to grayscale for e-ink readers (transcodes to JPEG by default)
+    #[arg(long)]
+    pub grayscale: bool,
+}
+
+impl ImageProfileArgs {
+    pub fn image_profile(&self) -> Option<ImageProfile> {
+        let transcode = self.jpeg
+            || self.image_format.is_some()
+            || self.max_width.is_some()
+            || self.max_height.is_some()
+            || self.grayscale;
+        transcode.then(|| {
+            ImageProfile::new(self.image_format.map(Into::into).unwrap_or_default())
+                .quality(self.quality)
+                .max_size(self.max_width, self.max_height)
+                .grayscale(self.grayscale)
+        })
+    }
+}
+
+impl Run for CreateArgs {
+    fn run(&self) -> anyhow::Result<()> {
+        let mut builder = self
+            .selection
+            .to_builder()?
+            .set_image_profile(self.images.image_profile());
+        if let Some(base) = self.base.as_ref() {
+            let base = open_package(base)?.get_package_contents()?.clone();
+            builder = builder.set_base(Some(base));
+        } else {
+            builder.set_compression_level(self.compression_level);
+            builder.zstd_compressed_images(self.zstd_images);
+            builder.zstd_compressed_metadata(self.zstd_metadata);
+            if let Some(dict) = self.dict.as_ref() {
+                builder.set_dictionary(Some(read(dict)?));
+            } else if let Some(max_size) = self.train_dict {
+                builder.train_dictionary(max_size)?;
+            }
+        }
+        if let Some(workers) = self.workers {
+            builder.set_workers(workers);
+        }
+        builder.set_zstd_workers(self.zstd_workers);
+        builder.set_long_distance_matching(self.long_distance_matching);
+        let contents = if let Some(max_part_size) = self.max_part_size {
+            let parts = builder.build_parts(max_part_size, |index| {
+                Ok(BufWriter::new(File::create(volume_path(&self.output, index))?))
+            })?;
+            println!("Split the package into {} parts", parts.len());
+            parts.into_iter().reduce(Add::add).unwrap_or_default()
+        } else {
+            let mut writer = BufWriter::new(File::create(&self.output)?);
+            let contents = builder.build(&mut writer)?;
+            writer.flush()?;
+            contents
+        };
+        println!(
+            "Packaged {} mangas, {} covers and {} chapters into {}",
+            contents.data.len(),
+            contents
+                .data
+                .values()
+                .map(|manga| manga.covers.len())
+                .sum::<usize>(),
+            contents
+                .data
+                .values()
+                .map(|manga| manga.chapters.len())
+                .sum::<usize>(),
+            self.output.display()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,30 @@
+use std::{fs::write, path::PathBuf};
+
+use clap::Args;
+
+use super::{selection::PackageSelectionArgs, Run};
+
+#[derive(Debug, Args)]
+pub struct DictArgs {
+    /// The dictionary file to write
+    #[arg(short, long)]
+    pub output: PathBuf,
+    /// The maximum size of the dictionary in bytes
+    #[arg(long, default_value_t = 112_640)]
+    pub max_size: usize,
+    #[command(flatten)]
+    pub selection: PackageSelectionArgs,
+}
+
+impl Run for DictArgs {
+    fn run(&self) -> anyhow::Result<()> {
+        let dict = self.selection.to_builder()?.create_dict(self.max_size)?;
+        write(&self.output, &dict)?;
+        println!(
+            "Wrote a {} bytes dictionary into {}",
+            dict.len(),
+            self.output.display()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,57 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use emdx::contents::diff::ImagesDiff;
+
+use super::{open_package, Run};
+
+#[derive(Debug, Args)]
+pub struct DiffArgs {
+    /// The older package file, or the name of a split package parts
+    pub old: PathBuf,
+    /// The newer package file, or the name of a split package parts
+    pub new: PathBuf,
+}
+
+fn images_summary(images: &ImagesDiff) -> String {
+    format!("+{} -{}", images.added.len(), images.removed.len())
+}
+
+impl Run for DiffArgs {
+    fn run(&self) -> anyhow::Result<()> {
+        let old = open_package(&self.old)?;
+        let new = open_package(&self.new)?;
+        let diff = old
+            .get_package_contents()?
+            .diff(new.get_package_contents()?);
+        if diff.is_empty() {
+            println!("The packages have the same contents");
+            return Ok(());
+        }
+        let lists = [
+            ("+ manga", &diff.added_mangas),
+            ("- manga", &diff.removed_mangas),
+            ("+ cover", &diff.added_covers),
+            ("- cover", &diff.removed_covers),
+            ("+ chapter", &diff.added_chapters),
+            ("- chapter", &diff.removed_chapters),
+        ];
+        for (prefix, ids) in lists {
+            for id in ids {
+                println!("{prefix} {id}");
+            }
+        }
+        for chapter in &diff.changed_chapters {
+            println!(
+                "~ chapter {} (data: {}, data-saver: {})",
+                chapter.id,
+                images_summary(&chapter.data),
+                images_summary(&chapter.data_saver)
+            );
+        }
+        for path in &diff.changed_entries {
+            println!("~ entry {}", path.display());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+use std::path::PathBuf;
+
+use api_core::DirsOptions;
+use clap::{Args, ValueEnum};
+use emdx::archive::import::{ConflictPolicy, ImportOptions};
+use uuid::Uuid;
+
+use crate::DirsOptionsArgs;
+
+use super::{open_package, selection::ImageModeArg, Run};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, ValueEnum, Default)]
+pub enum ConflictPolicyArg {
+    /// Keep the library data
+    Skip,
+    /// Replace the library data (the default)
+    #[default]
+    Overwrite,
+    /// Keep the data with the highest version
+    KeepNewer,
+    /// Merge the relationships with the library data
+    Merge,
+}
+
+impl From<ConflictPolicyArg> for ConflictPolicy {
+    fn from(value: ConflictPolicyArg) -> Self {
+        match value {
+            ConflictPolicyArg::Skip => Self::SkipExisting,
+            ConflictPolicyArg::Overwrite => Self::Overwrite,
+            ConflictPolicyArg::KeepNewer => Self::KeepNewer,
+            ConflictPolicyArg::Merge => Self::Merge,
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct ExtractArgs {
+    /// The package file, or the name of a split package parts
+    pub package: PathBuf,
+    #[command(flatten)]
+    pub options: DirsOptionsArgs,
+    /// Only extract this manga with its covers and chapters
+    #[arg(id = "manga", long = "manga")]
+    pub mangas: Vec<Uuid>,
+    /// Only extract this chapter with its manga
+    #[arg(id = "chapter", long = "chapter")]
+    pub chapters: Vec<Uuid>,
+    /// Only extract the chapter images of this mode
+    #[arg(long)]
+    pub mode: Option<ImageModeArg>,
+    /// What to do with the data that is already in the library
+    #[arg(long, default_value = "overwrite")]
+    pub on_conflict: ConflictPolicyArg,
+}
+
+impl Run for ExtractArgs {
+    fn run(&self) -> anyhow::Result<()> {
+        let mut options: DirsOptions = self.options.clone().into();
+        options.init_dirs()?;
+        let mut archive = open_package(&self.package)?;
+        let report = archive.import(
+            &mut options,
+            &ImportOptions::new()
+                .mangas(self.mangas.clone())
+                .chapters(self.chapters.clone())
+                .mode(self.mode.map(From::from))
+                .policy(self.on_conflict.into()),
+        )?;
+        options.save_index()?;
+        println!(
+            "Extracted into {}: {} imported, {} skipped, {} conflicted",
+            options.data_dir_add("").display(),
+            report.imported.len(),
+            report.skipped.len(),
+            report.conflicted.len()
+        );
+        Ok(())
+    }
+}
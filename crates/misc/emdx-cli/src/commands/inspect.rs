@@ -0,0 +1,102 @@
+use std::path::PathBuf;
+
+use clap::Args;
+
+use super::{open_package, Run};
+
+#[derive(Debug, Args)]
+pub struct ListArgs {
+    /// The package file, or the name of a split package parts
+    pub package: PathBuf,
+}
+
+impl Run for ListArgs {
+    fn run(&self) -> anyhow::Result<()> {
+        let archive = open_package(&self.package)?;
+        for (manga_id, manga) in &archive.get_package_contents()?.data {
+            println!("manga {manga_id}");
+            for cover_id in &manga.covers {
+                println!("  cover {cover_id}");
+            }
+            for (chapter_id, images) in &manga.chapters {
+                println!(
+                    "  chapter {chapter_id} ({} data images, {} data-saver images)",
+                    images.data.len(),
+                    images.data_saver.len()
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct InspectArgs {
+    /// The package file, or the name of a split package parts
+    pub package: PathBuf,
+    /// Print the whole package contents as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+impl Run for InspectArgs {
+    fn run(&self) -> anyhow::Result<()> {
+        let archive = open_package(&self.package)?;
+        let contents = archive.get_package_contents()?;
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(contents)?);
+            return Ok(());
+        }
+        println!("format version: {}", contents.get_format_version());
+        if let Some(manifest) = contents.manifest.as_ref() {
+            if let Some(producer) = manifest.producer.as_ref() {
+                println!("producer: {producer}");
+            }
+            if let Some(created_at) = manifest.created_at {
+                println!("created at: {created_at} (unix time)");
+            }
+            println!("manifest entries: {}", manifest.entries.len());
+        }
+        if let Some(delta) = contents.delta.as_ref() {
+            println!(
+                "delta of the package with the manifest {}",
+                delta.get_base_manifest_hex()
+            );
+        }
+        let options = contents.get_options();
+        let dirs = options.get_dirs();
+        println!("directories:");
+        println!("  data: {}", dirs.data_dir_add("").display());
+        println!("  mangas: {}", dirs.mangas_add("").display());
+        println!("  covers: {}", dirs.covers_add("").display());
+        println!("  chapters: {}", dirs.chapters_add("").display());
+        println!("zstd compressed images: {}", options.zstd_compressed_images);
+        println!("zstd compressed metadata: {}", options.zstd_compressed_metadata);
+        match options.dictionary.as_ref() {
+            Some(dictionary) => println!("dictionary: {} bytes", dictionary.len()),
+            None => println!("dictionary: none"),
+        }
+        match archive.get_table_of_contents() {
+            Some(toc) => println!("layout: random access ({} entries)", toc.len()),
+            None => println!("layout: linear"),
+        }
+        println!("mangas: {}", contents.data.len());
+        println!(
+            "covers: {}",
+            contents
+                .data
+                .values()
+                .map(|manga| manga.covers.len())
+                .sum::<usize>()
+        );
+        println!(
+            "chapters: {}",
+            contents
+                .data
+                .values()
+                .map(|manga| manga.chapters.len())
+                .sum::<usize>()
+        );
+        Ok(())
+    }
+}
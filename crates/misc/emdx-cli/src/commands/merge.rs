@@ -0,0 +1,68 @@
+use std::{
+    fs::{read, File},
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
+
+use clap::Args;
+use emdx::{
+    builder::Reencoder,
+    contents::options::{PackageContentsOptions, ZstdDictionary},
+};
+
+use super::{open_package, Run};
+
+#[derive(Debug, Args)]
+pub struct MergeArgs {
+    /// The package files (or the names of split packages parts) to merge,
+    /// the delta packages after their base
+    #[arg(required = true, num_args = 2..)]
+    pub packages: Vec<PathBuf>,
+    /// The package file to write
+    #[arg(short, long)]
+    pub output: PathBuf,
+    /// The zstd compression level (0 means the zstd default level)
+    #[arg(long, default_value_t = 0)]
+    pub compression_level: i32,
+    /// Compress the images with zstd
+    #[arg(long)]
+    pub zstd_images: bool,
+    /// Compress the manga, cover and chapter metadata with zstd
+    #[arg(long)]
+    pub zstd_metadata: bool,
+    /// A zstd dictionary file (made with `emdx dict`) to embed and compress the entries with
+    #[arg(long)]
+    pub dict: Option<PathBuf>,
+}
+
+impl Run for MergeArgs {
+    fn run(&self) -> anyhow::Result<()> {
+        let mut archives = self
+            .packages
+            .iter()
+            .map(open_package)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let mut reencoder = Reencoder::new(PackageContentsOptions {
+            zstd_compressed_images: self.zstd_images,
+            zstd_compressed_metadata: self.zstd_metadata,
+            dictionary: self.dict.as_ref().map(read).transpose()?.map(ZstdDictionary),
+            ..Default::default()
+        });
+        reencoder.set_compression_level(self.compression_level);
+        let mut writer = BufWriter::new(File::create(&self.output)?);
+        let contents = reencoder.merge(&mut archives, &mut writer)?;
+        writer.flush()?;
+        println!(
+            "Merged {} packages into {} ({} mangas, {} chapters)",
+            self.packages.len(),
+            self.output.display(),
+            contents.data.len(),
+            contents
+                .data
+                .values()
+                .map(|manga| manga.chapters.len())
+                .sum::<usize>()
+        );
+        Ok(())
+    }
+}
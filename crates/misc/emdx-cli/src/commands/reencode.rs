@@ -0,0 +1,95 @@
+use std::{
+    fs::{read, File},
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
+
+use api_core::data_push::chapter::image::Mode;
+use clap::Args;
+use emdx::{
+    builder::Reencoder,
+    contents::options::{PackageContentsOptions, ZstdDictionary},
+};
+
+use super::{create::ImageProfileArgs, open_package, selection::ImageModeArg, Run};
+
+#[derive(Debug, Args)]
+pub struct ReencodeArgs {
+    /// The package file, or the name of a split package parts
+    pub package: PathBuf,
+    /// The package file to write
+    #[arg(short, long)]
+    pub output: PathBuf,
+    /// The zstd compression level (0 means the zstd default level)
+    #[arg(long, default_value_t = 0)]
+    pub compression_level: i32,
+    /// Compress the images with zstd
+    #[arg(long)]
+    pub zstd_images: bool,
+    /// Compress the manga, cover and chapter metadata with zstd
+    #[arg(long)]
+    pub zstd_metadata: bool,
+    /// A zstd dictionary file (made with `emdx dict`) to embed and compress the entries with
+    #[arg(long, conflicts_with = "keep_dict")]
+    pub dict: Option<PathBuf>,
+    /// Keep the package dictionary
+    #[arg(long)]
+    pub keep_dict: bool,
+    /// Only keep the chapter images of this mode
+    #[arg(long)]
+    pub mode: Option<ImageModeArg>,
+    #[command(flatten)]
+    pub images: ImageProfileArgs,
+    /// How many zstd threads compress each entry frame
+    #[arg(long, default_value_t = 0)]
+    pub zstd_workers: u32,
+    /// Enable the zstd long distance matching
+    #[arg(long)]
+    pub long_distance_matching: bool,
+}
+
+impl Run for ReencodeArgs {
+    fn run(&self) -> anyhow::Result<()> {
+        let mut archive = open_package(&self.package)?;
+        let mut contents = archive.get_package_contents()?.clone();
+        let dictionary = if let Some(dict) = self.dict.as_ref() {
+            Some(ZstdDictionary(read(dict)?))
+        } else if self.keep_dict {
+            contents.get_options().dictionary.clone()
+        } else {
+            None
+        };
+        if let Some(mode) = self.mode.map(Mode::from) {
+            for images in contents
+                .data
+                .values_mut()
+                .flat_map(|manga_data| manga_data.chapters.values_mut())
+            {
+                match mode {
+                    Mode::Data => images.data_saver.clear(),
+                    Mode::DataSaver => images.data.clear(),
+                }
+            }
+        }
+        let mut reencoder = Reencoder::new(PackageContentsOptions {
+            zstd_compressed_images: self.zstd_images,
+            zstd_compressed_metadata: self.zstd_metadata,
+            dictionary,
+            ..Default::default()
+        })
+        .set_content(Some(contents))
+        .set_image_profile(self.images.image_profile());
+        reencoder.set_compression_level(self.compression_level);
+        reencoder.set_zstd_workers(self.zstd_workers);
+        reencoder.set_long_distance_matching(self.long_distance_matching);
+        let mut writer = BufWriter::new(File::create(&self.output)?);
+        reencoder.reencode(&mut archive, &mut writer)?;
+        writer.flush()?;
+        println!(
+            "Re-encoded {} into {}",
+            self.package.display(),
+            self.output.display()
+        );
+        Ok(())
+    }
+}
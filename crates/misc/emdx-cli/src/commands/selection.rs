@@ -0,0 +1,149 @@
+use std::collections::HashSet;
+
+use api_core::{
+    data_pulls::{
+        chapter::ChapterListDataPullFilterParams, manga::MangaListDataPullFilterParams,
+        IntoFiltered,
+    },
+    data_push::chapter::image::Mode,
+    DirsOptions,
+};
+use clap::{Args, ValueEnum};
+use emdx::PackageBuilder;
+use mangadex_api_types_rust::Language;
+use uuid::Uuid;
+
+use crate::DirsOptionsArgs;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, ValueEnum, Default)]
+pub enum ImageModeArg {
+    /// The original chapter images
+    #[default]
+    Data,
+    /// The compressed chapter images
+    DataSaver,
+}
+
+impl From<ImageModeArg> for Mode {
+    fn from(value: ImageModeArg) -> Self {
+        match value {
+            ImageModeArg::Data => Self::Data,
+            ImageModeArg::DataSaver => Self::DataSaver,
+        }
+    }
+}
+
+/// What to put in a package
+#[derive(Debug, Args, Clone)]
+pub struct PackageSelectionArgs {
+    #[command(flatten)]
+    pub options: DirsOptionsArgs,
+    /// Add a manga with its stored chapters
+    #[arg(id = "manga", long = "manga")]
+    pub mangas: Vec<Uuid>,
+    /// Add a chapter
+    #[arg(id = "chapter", long = "chapter")]
+    pub chapters: Vec<Uuid>,
+    /// Add a cover
+    #[arg(id = "cover", long = "cover")]
+    pub covers: Vec<Uuid>,
+    /// Add every stored manga
+    #[arg(long)]
+    pub all: bool,
+    /// Add the mangas matching this title
+    #[arg(long)]
+    pub title: Option<String>,
+    /// Add the mangas with this original language
+    #[arg(long)]
+    pub original_language: Vec<Language>,
+    /// Only add the chapters translated in this language
+    #[arg(long)]
+    pub translated_language: Vec<Language>,
+    /// Only add the chapters of this volume
+    #[arg(long = "volume")]
+    pub volumes: Vec<String>,
+    /// The chapter images to add
+    #[arg(long, default_value = "data")]
+    pub mode: ImageModeArg,
+}
+
+impl PackageSelectionArgs {
+    fn has_manga_filter(&self) -> bool {
+        self.all || self.title.is_some() || !self.original_language.is_empty()
+    }
+    fn has_chapter_filter(&self) -> bool {
+        !self.translated_language.is_empty() || !self.volumes.is_empty()
+    }
+    fn manga_filter(&self) -> MangaListDataPullFilterParams {
+        MangaListDataPullFilterParams {
+            title: self.title.clone(),
+            original_language: self.original_language.clone(),
+            ..Default::default()
+        }
+    }
+    fn chapter_filter(&self, manga_ids: Vec<Uuid>) -> ChapterListDataPullFilterParams {
+        ChapterListDataPullFilterParams {
+            translated_languages: self.translated_language.clone(),
+            volumes: self.volumes.clone(),
+            manga_ids,
+            ..Default::default()
+        }
+    }
+    pub fn get_dirs_options(&self) -> DirsOptions {
+        self.options.clone().into()
+    }
+    fn selected_mangas(&self, options: &DirsOptions) -> anyhow::Result<Vec<Uuid>> {
+        let mut mangas = self.mangas.clone();
+        if self.has_manga_filter() {
+            mangas.extend(
+                options
+                    .pull_all_mangas()?
+                    .flatten()
+                    .to_filtered(self.manga_filter())
+                    .map(|manga| manga.id),
+            );
+        }
+        let mut seen = HashSet::new();
+        mangas.retain(|id| seen.insert(*id));
+        Ok(mangas)
+    }
+    fn selected_chapters(
+        &self,
+        options: &DirsOptions,
+        mangas: &[Uuid],
+    ) -> anyhow::Result<Vec<Uuid>> {
+        let mut chapters = self.chapters.clone();
+        if !mangas.is_empty() || self.has_chapter_filter() {
+            let filter = self.chapter_filter(mangas.to_vec());
+            chapters.extend(
+                options
+                    .pull_indexed_chapters(&filter)?
+                    .flatten()
+                    .to_filtered(filter)
+                    .map(|chapter| chapter.id),
+            );
+        }
+        let mut seen = HashSet::new();
+        chapters.retain(|id| seen.insert(*id));
+        Ok(chapters)
+    }
+    /// Make a [`PackageBuilder`] with the selected mangas, covers and chapters.
+    pub fn to_builder(&self) -> anyhow::Result<PackageBuilder> {
+        let options = self.get_dirs_options();
+        let mut builder = PackageBuilder::new(options.clone());
+        let mangas = self.selected_mangas(&options)?;
+        for manga in &mangas {
+            builder.add_manga(*manga)?;
+        }
+        for chapter in self.selected_chapters(&options, &mangas)? {
+            builder.add_chapter(chapter, self.mode.into())?;
+        }
+        for cover in &self.covers {
+            builder.add_cover(*cover)?;
+        }
+        if builder.get_package_contents().data.is_empty() {
+            anyhow::bail!("nothing is selected for the package");
+        }
+        Ok(builder)
+    }
+}
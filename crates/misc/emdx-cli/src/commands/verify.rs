@@ -0,0 +1,150 @@
+use std::{collections::HashSet, io::BufReader, path::PathBuf};
+
+use api_core::data_push::chapter::image::Mode;
+use clap::Args;
+use emdx::{
+    archive::pull::any::PossibleEntryData, volume::MultiPartReader, Archive, PackageContents,
+};
+use uuid::Uuid;
+
+use super::{open_package, Run};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum PackageEntry {
+    Manga(Uuid),
+    Cover(Uuid),
+    CoverImage(String),
+    Chapter(Uuid),
+    ChapterImage {
+        chapter: Uuid,
+        mode: Mode,
+        filename: String,
+    },
+}
+
+fn expected_entries(contents: &PackageContents) -> HashSet<PackageEntry> {
+    let mut expected = HashSet::new();
+    for (manga_id, manga) in &contents.data {
+        expected.insert(PackageEntry::Manga(*manga_id));
+        expected.extend(manga.covers.iter().copied().map(PackageEntry::Cover));
+        for (chapter_id, images) in &manga.chapters {
+            expected.insert(PackageEntry::Chapter(*chapter_id));
+            let modes = [(Mode::Data, &images.data), (Mode::DataSaver, &images.data_saver)];
+            for (mode, filenames) in modes {
+                expected.extend(filenames.iter().map(|filename| PackageEntry::ChapterImage {
+                    chapter: *chapter_id,
+                    mode,
+                    filename: filename.clone(),
+                }));
+            }
+        }
+    }
+    expected
+}
+
+#[derive(Debug, Args)]
+pub struct VerifyArgs {
+    /// The package file, or the name of a split package parts
+    pub package: PathBuf,
+}
+
+impl VerifyArgs {
+    fn verify_manifest(
+        &self,
+        archive: &mut Archive<'_, BufReader<MultiPartReader>>,
+    ) -> anyhow::Result<()> {
+        let report = archive.verify()?;
+        for path in &report.missing {
+            eprintln!("missing entry: {}", path.display());
+        }
+        for path in &report.extra {
+            eprintln!("extra entry: {}", path.display());
+        }
+        for entry in &report.corrupted {
+            eprintln!(
+                "corrupted entry: {} (expected {} bytes with sha256 {}, found {} bytes with \
+                 sha256 {})",
+                entry.path.display(),
+                entry.expected.size,
+                entry.expected.get_sha256_hex(),
+                entry.found.size,
+                entry.found.get_sha256_hex()
+            );
+        }
+        if !report.is_valid() {
+            anyhow::bail!(
+                "{} missing, {} extra and {} corrupted entries",
+                report.missing.len(),
+                report.extra.len(),
+                report.corrupted.len()
+            );
+        }
+        println!("{} is valid", self.package.display());
+        Ok(())
+    }
+}
+
+impl Run for VerifyArgs {
+    fn run(&self) -> anyhow::Result<()> {
+        let mut archive = open_package(&self.package)?;
+        if archive.get_package_contents()?.manifest.is_some() {
+            return self.verify_manifest(&mut archive);
+        }
+        // the packages written before the manifest can only be checked against their contents
+        let mut missing = expected_entries(archive.get_package_contents()?);
+        let mut cover_images = HashSet::new();
+        let mut unreadable = 0usize;
+        for entry in archive.any_pull(true)? {
+            match entry {
+                Ok(PossibleEntryData::Manga(manga)) => {
+                    missing.remove(&PackageEntry::Manga(manga.id));
+                }
+                Ok(PossibleEntryData::Cover(cover)) => {
+                    if missing.remove(&PackageEntry::Cover(cover.id)) {
+                        missing.insert(PackageEntry::CoverImage(cover.attributes.file_name));
+                    }
+                }
+                Ok(PossibleEntryData::CoverImage { filename, .. }) => {
+                    cover_images.insert(filename);
+                }
+                Ok(PossibleEntryData::Chapter(chapter)) => {
+                    missing.remove(&PackageEntry::Chapter(chapter.id));
+                }
+                Ok(PossibleEntryData::ChapterImage {
+                    filename,
+                    chapter,
+                    mode,
+                    ..
+                }) => {
+                    missing.remove(&PackageEntry::ChapterImage {
+                        chapter,
+                        mode,
+                        filename,
+                    });
+                }
+                Ok(PossibleEntryData::Any { .. }) => {}
+                Err(err) => {
+                    eprintln!("unreadable entry: {err}");
+                    unreadable += 1;
+                }
+            }
+        }
+        missing.retain(|entry| {
+            let PackageEntry::CoverImage(filename) = entry else {
+                return true;
+            };
+            !cover_images.contains(filename)
+        });
+        for entry in &missing {
+            eprintln!("missing entry: {entry:?}");
+        }
+        if !missing.is_empty() || unreadable != 0 {
+            anyhow::bail!(
+                "{} missing entries and {unreadable} unreadable entries",
+                missing.len()
+            );
+        }
+        println!("{} is valid", self.package.display());
+        Ok(())
+    }
+}
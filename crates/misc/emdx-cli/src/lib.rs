@@ -0,0 +1,23 @@
+pub mod commands;
+
+pub use api_core::file_dirs::args::{DirsOptionsArgs, StorageFormatArg};
+use clap::Parser;
+use commands::Commands;
+
+#[derive(Debug, Parser)]
+#[command(version, about, long_about = None, propagate_version = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub commands: Commands,
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use crate::Cli;
+    #[test]
+    fn verify_app() {
+        Cli::command().debug_assert();
+    }
+}
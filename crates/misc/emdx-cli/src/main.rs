@@ -0,0 +1,6 @@
+use clap::Parser;
+use emdx_cli::{commands::Run, Cli};
+
+fn main() -> anyhow::Result<()> {
+    Cli::parse().commands.run()
+}
@@ -1,6 +1,8 @@
+pub mod check;
 pub mod count;
 pub mod delete;
 pub mod download;
+pub mod migrate;
 pub mod transfer;
 
 use std::future::Future;
@@ -20,6 +22,10 @@ pub enum Commands {
     #[command(subcommand)]
     Remove(delete::DeleteSubcommands),
     Transfert(Box<transfer::TransferCommand>),
+    /// Rewrite the whole library in another storage format
+    Migrate(migrate::MigrateArgs),
+    /// Check the library integrity and optionally repair the issues found
+    Check(check::CheckArgs),
 }
 
 #[derive(Debug, Clone)]
@@ -39,6 +45,8 @@ impl AsyncRun for Commands {
             Commands::Count(count_args) => count_args.run(manager).await,
             Commands::Remove(delete_subcommands) => delete_subcommands.run(manager).await,
             Commands::Transfert(transfer_command) => transfer_command.run(manager).await,
+            Commands::Migrate(migrate_args) => migrate_args.run(manager).await,
+            Commands::Check(check_args) => check_args.run(manager).await,
         }
     }
 }
@@ -0,0 +1,91 @@
+use clap::{Args, ValueEnum};
+use eureka_mmanager::prelude::{
+    CheckLibraryAsyncTrait, LibraryIssue, RepairAction, RepairLibraryIssueAsyncTrait,
+};
+use indicatif::ProgressBar;
+use log::{error, info, warn};
+
+use super::{AsyncRun, AsyncRunContext};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, ValueEnum)]
+pub enum RepairActionArg {
+    /// Delete the broken data
+    Delete,
+    /// Queue a new download of the missing data
+    Redownload,
+    /// Add the missing data to the download history
+    AddToHistory,
+}
+
+impl From<RepairActionArg> for RepairAction {
+    fn from(value: RepairActionArg) -> Self {
+        match value {
+            RepairActionArg::Delete => Self::Delete,
+            RepairActionArg::Redownload => Self::Redownload,
+            RepairActionArg::AddToHistory => Self::AddToHistory,
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct CheckArgs {
+    /// Repair the issues found with this action.
+    ///
+    /// The issues that don't support it are skipped.
+    #[arg(long)]
+    pub repair: Option<RepairActionArg>,
+}
+
+fn describe(issue: &LibraryIssue) -> String {
+    match issue {
+        LibraryIssue::ChapterWithoutData { id } => format!("chapter {id} has no data file"),
+        LibraryIssue::EmptyChapterImage { id, mode, filename } => {
+            format!("chapter {id} has an empty {mode:?} image {filename}")
+        }
+        LibraryIssue::MissingCoverImage { id, filename } => {
+            format!("cover {id} image {filename} is missing")
+        }
+        LibraryIssue::OrphanChapter { id, manga } => {
+            format!("chapter {id} manga {manga} is not stored")
+        }
+        LibraryIssue::MissingMangaCover { id, cover } => {
+            format!("manga {id} cover art {cover} is not stored")
+        }
+    }
+}
+
+impl AsyncRun for CheckArgs {
+    async fn run(&self, ctx: AsyncRunContext) -> anyhow::Result<()> {
+        let progress = ctx.progress.add(ProgressBar::new_spinner());
+        progress.set_message("Checking the library");
+        let report = ctx.manager.check_library().await?;
+        progress.finish();
+        ctx.progress.remove(&progress);
+        if report.is_healthy() {
+            info!("No issue found");
+            return Ok(());
+        }
+        for issue in &report.issues {
+            println!("{}", describe(issue));
+        }
+        let Some(action) = self.repair.map(RepairAction::from) else {
+            anyhow::bail!("{} issues found", report.issues.len());
+        };
+        let mut failed = 0;
+        for issue in report.issues {
+            if !issue.can_be_repaired_with(action) {
+                warn!("Skipping {}: {action:?} is not supported", describe(&issue));
+                continue;
+            }
+            let description = describe(&issue);
+            if let Err(err) = ctx.manager.repair_library_issue(issue, action).await {
+                error!("Cannot repair {description}: {err}");
+                failed += 1;
+            }
+        }
+        if failed != 0 {
+            anyhow::bail!("{failed} issues cannot be repaired");
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,6 @@
 use clap::Args;
 use eureka_mmanager::{
-    files_dirs::messages::pull::chapter::ChapterListDataPullMessage,
+    files_dirs::messages::index::ChapterIndexedPullMessage,
     prelude::{
         ChapterDataPullAsyncTrait, ChapterListDataPullFilterParams, GetManagerStateData,
         IntoParamedFilteredStream,
@@ -97,10 +97,11 @@ impl CountChapterArgs {
 impl AsyncRun for CountChapterArgs {
     async fn run(&self, ctx: AsyncRunContext) -> anyhow::Result<()> {
         let dir_options = ctx.manager.get_dir_options().await?;
+        let params = self.to_params();
         let mut stream = dir_options
-            .send(ChapterListDataPullMessage)
+            .send(ChapterIndexedPullMessage(params.clone()))
             .await??
-            .to_filtered(self.to_params());
+            .to_filtered(params);
         if self.ids {
             while let Some(chapter) = stream.next().await {
                 println!("{} [{}]", chapter.id, {
@@ -1,6 +1,6 @@
 use clap::Args;
 use eureka_mmanager::{
-    files_dirs::messages::pull::cover::CoverListDataPullMessage,
+    files_dirs::messages::index::CoverIndexedPullMessage,
     prelude::{
         CoverListDataPullFilterParams, GetManagerStateData, IntoParamedFilteredStream,
         JoinPathAsyncTraits,
@@ -39,10 +39,11 @@ impl CountCoverArgs {
 impl AsyncRun for CountCoverArgs {
     async fn run(&self, ctx: AsyncRunContext) -> anyhow::Result<()> {
         let dir_options = ctx.manager.get_dir_options().await?;
+        let params = self.to_params();
         let mut stream = dir_options
-            .send(CoverListDataPullMessage)
+            .send(CoverIndexedPullMessage(params.clone()))
             .await??
-            .to_filtered(self.to_params());
+            .to_filtered(params);
 
         match (self.ids, self.filename) {
             (true, false) => {
@@ -1,6 +1,7 @@
 pub mod chapter;
 pub mod cover;
 pub mod manga;
+pub mod resume;
 
 use clap::Subcommand;
 
@@ -14,6 +15,8 @@ pub enum DownloadSubCommands {
     Cover(cover::CoverDownloadArgs),
     /// Download Chapters subcommand
     Chapter(chapter::ChapterDownloadArgs),
+    /// Resume the downloads that didn't finish
+    Resume(resume::ResumeDownloadArgs),
 }
 
 impl AsyncRun for DownloadSubCommands {
@@ -22,6 +25,7 @@ impl AsyncRun for DownloadSubCommands {
             Self::Manga(r) => r.run(ctx).await,
             Self::Cover(r) => r.run(ctx).await,
             Self::Chapter(r) => r.run(ctx).await,
+            Self::Resume(r) => r.run(ctx).await,
         }
     }
 }
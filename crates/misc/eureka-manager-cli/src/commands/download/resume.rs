@@ -0,0 +1,44 @@
+use clap::Args;
+use eureka_mmanager::download::messages::resume::{
+    ResumeUnfinishedDownloads, ResumeUnfinishedDownloadsAsyncTrait,
+};
+use indicatif::ProgressBar;
+use log::info;
+
+use crate::commands::{AsyncRun, AsyncRunContext};
+
+use super::chapter::ChapterDownloadMode;
+
+#[derive(Debug, Args)]
+pub struct ResumeDownloadArgs {
+    /// The mode used for the unfinished chapters whose mode isn't in the history
+    #[arg(short, long, default_value = "data")]
+    pub mode: ChapterDownloadMode,
+}
+
+impl AsyncRun for ResumeDownloadArgs {
+    async fn run(&self, ctx: AsyncRunContext) -> anyhow::Result<()> {
+        let progress = ctx.progress.add(ProgressBar::new_spinner());
+        progress.set_message("Resuming unfinished downloads");
+        let report = ctx
+            .manager
+            .resume_unfinished_downloads(ResumeUnfinishedDownloads::new().chapter_mode(self.mode))
+            .await?;
+        progress.finish();
+        ctx.progress.remove(&progress);
+        for (entry, err) in &report.failed {
+            log::error!(
+                "Cannot resume {:?} {}: {err}",
+                entry.get_data_type(),
+                entry.get_id()
+            );
+        }
+        info!(
+            "Resumed {} downloads ({} failed, {} skipped)",
+            report.done.len(),
+            report.failed.len(),
+            report.skipped.len()
+        );
+        Ok(())
+    }
+}
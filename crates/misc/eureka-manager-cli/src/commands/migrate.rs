@@ -0,0 +1,35 @@
+use clap::Args;
+use eureka_mmanager::prelude::ModifyDirOptionAsyncTrait;
+use indicatif::ProgressBar;
+use log::info;
+
+use crate::StorageFormatArg;
+
+use super::{AsyncRun, AsyncRunContext};
+
+#[derive(Debug, Args)]
+pub struct MigrateArgs {
+    /// The new storage format
+    #[arg(long)]
+    pub to: StorageFormatArg,
+}
+
+impl AsyncRun for MigrateArgs {
+    async fn run(&self, ctx: AsyncRunContext) -> anyhow::Result<()> {
+        let progress = ctx.progress.add(ProgressBar::new_spinner());
+        progress.set_message(format!("Migrating the library to {:?}", self.to));
+        let report = ctx.manager.migrate_storage(self.to.into()).await?;
+        progress.finish();
+        ctx.progress.remove(&progress);
+        info!(
+            "Migrated {} files ({} failed, {} already migrated)",
+            report.migrated.len(),
+            report.failed.len(),
+            report.skipped.len()
+        );
+        if !report.is_success() {
+            anyhow::bail!("{} files cannot be migrated", report.failed.len());
+        }
+        Ok(())
+    }
+}
@@ -3,46 +3,11 @@ pub mod commands;
 use duration_string::DurationString;
 use fern::colors::ColoredLevelConfig;
 use log::{LevelFilter, Log};
-use std::{path::PathBuf, time::SystemTime};
+use std::{num::NonZeroU64, time::SystemTime};
 
-use clap::{Args, Parser};
+pub use api_core::file_dirs::args::{DirsOptionsArgs, StorageFormatArg};
+use clap::Parser;
 use commands::Commands;
-use eureka_mmanager::prelude::DirsOptionsCore;
-
-#[derive(Debug, Args, Clone)]
-pub struct DirsOptionsArgs {
-    /// data directory path
-    ///
-    /// Default: "output"
-    #[arg(long)]
-    pub data_dir: Option<PathBuf>,
-    /// chapter directory relative to `data_dir` (you can put an absolute path if you wanted to)
-    #[arg(long)]
-    pub chapters: Option<PathBuf>,
-    /// manga directory relative to `data_dir` (you can put an absolute path if you wanted to)
-    #[arg(long)]
-    pub mangas: Option<PathBuf>,
-    /// covers directory relative to `data_dir` (you can put an absolute path if you wanted to)
-    #[arg(long)]
-    pub covers: Option<PathBuf>,
-}
-
-impl From<DirsOptionsArgs> for DirsOptionsCore {
-    fn from(value: DirsOptionsArgs) -> Self {
-        let mut options =
-            DirsOptionsCore::new_from_data_dir(value.data_dir.unwrap_or(From::from("output")));
-        if let Some(chapters) = value.chapters {
-            options.chapters = options.data_dir_add(chapters);
-        }
-        if let Some(mangas) = value.mangas {
-            options.mangas = options.data_dir_add(mangas);
-        }
-        if let Some(covers) = value.covers {
-            options.covers = options.data_dir_add(covers);
-        }
-        options
-    }
-}
 
 #[derive(Debug, Parser)]
 #[command(version, about, long_about = None, propagate_version = true)]
@@ -52,6 +17,9 @@ pub struct Cli {
     verbose: bool,
     #[arg(long)]
     pub request_timeout: Option<DurationString>,
+    /// Maximum download speed in bytes per second, shared by every chapter and cover download
+    #[arg(long)]
+    pub max_bandwidth: Option<NonZeroU64>,
     #[command(flatten)]
     pub options: DirsOptionsArgs,
     #[command(subcommand)]
@@ -5,7 +5,7 @@ use eureka_manager_cli::{
     Cli,
 };
 use eureka_mmanager::{
-    prelude::{DirsOptions, DirsOptionsCore},
+    prelude::{DirsOptions, DirsOptionsCore, UpdateManagerStateData},
     DownloadManager,
 };
 use indicatif::MultiProgress;
@@ -45,7 +45,14 @@ fn main() {
             }
             builder.build().unwrap()
         });
-        sys.block_on(async move { DownloadManager::new(options, mangadex_client).start() })
+        let max_bandwidth = cli.max_bandwidth;
+        sys.block_on(async move {
+            let manager = DownloadManager::new(options, mangadex_client).start();
+            if max_bandwidth.is_some() {
+                manager.set_max_bandwidth(max_bandwidth).await.unwrap();
+            }
+            manager
+        })
     };
     let ctx = AsyncRunContext { manager, progress };
     sys.block_on(cli.commands.run(ctx)).unwrap();